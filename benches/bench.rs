@@ -2,7 +2,7 @@
 #![allow(non_snake_case)]
 extern crate test;
 
-use zhang_hilbert::HilbertScan32;
+use zhang_hilbert::{ArbHilbertScan32, HilbertScan16, HilbertScan32};
 
 fn scan32_run(size: [u32; 2], b: &mut test::Bencher) {
     b.iter(|| -> u32 { HilbertScan32::new(size).map(|[x, y]| x + y).sum() })
@@ -23,7 +23,85 @@ fn scan32__256__256(b: &mut test::Bencher) {
     scan32_run([256, 256], b);
 }
 
+// 256×256 takes the fast path for power-of-two squares. This one doesn't.
+#[bench]
+fn scan32__255__256(b: &mut test::Bencher) {
+    scan32_run([255, 256], b);
+}
+
 #[bench]
 fn scan32__114__514(b: &mut test::Bencher) {
     scan32_run([114, 514], b);
 }
+
+fn scan32_for_each_run(size: [u32; 2], b: &mut test::Bencher) {
+    b.iter(|| -> u32 {
+        let mut sum = 0;
+        HilbertScan32::new(size).for_each(|[x, y]| sum += x + y);
+        sum
+    })
+}
+
+#[bench]
+fn scan32__255__256_for_each(b: &mut test::Bencher) {
+    scan32_for_each_run([255, 256], b);
+}
+
+#[bench]
+fn scan32__114__514_for_each(b: &mut test::Bencher) {
+    scan32_for_each_run([114, 514], b);
+}
+
+#[bench]
+fn scan32__256__256_reset(b: &mut test::Bencher) {
+    let mut scan = HilbertScan32::new([256, 256]);
+    b.iter(|| -> u32 {
+        scan.reset();
+        scan.by_ref().map(|[x, y]| x + y).sum()
+    })
+}
+
+// A `u16` working state producing `u32` points. Compare with
+// `scan32__114__514`.
+#[bench]
+fn scan16_widened__114__514(b: &mut test::Bencher) {
+    b.iter(|| -> u32 {
+        HilbertScan16::new([114, 514])
+            .widened::<u32>()
+            .map(|[x, y]| x + y)
+            .sum()
+    })
+}
+
+fn arb32_run(size: [u32; 2], b: &mut test::Bencher) {
+    b.iter(|| -> u32 { ArbHilbertScan32::new(size).map(|[x, y]| x + y).sum() })
+}
+
+#[bench]
+fn arb32__256__256(b: &mut test::Bencher) {
+    arb32_run([256, 256], b);
+}
+
+// A few parts, each of which is a power-of-two square
+#[bench]
+fn arb32_1024___16(b: &mut test::Bencher) {
+    arb32_run([1024, 16], b);
+}
+
+// Many small parts, so the per-part re-initialization dominates
+#[bench]
+fn arb32_65536____3(b: &mut test::Bencher) {
+    arb32_run([65536, 3], b);
+}
+
+// A strip of two rows, which is scanned in one part
+#[bench]
+fn arb32_65536____2(b: &mut test::Bencher) {
+    arb32_run([65536, 2], b);
+}
+
+// Only the construction, which computes `division_count` for the first part
+#[bench]
+fn arb32_65536____3_new(b: &mut test::Bencher) {
+    b.iter(|| ArbHilbertScan32::new(test::black_box([65536, 3])))
+}
@@ -1,5 +1,10 @@
+use image::{Rgb, RgbImage};
 use ndarray::{s, Array2};
-use zhang_hilbert::{ArbHilbertScan32, HilbertScan32};
+use std::io;
+use zhang_hilbert::{
+    write_points_csv, write_points_json, write_points_tsv, Algorithm, BoustrophedonScan,
+    RowMajorScan, Scan, ScanAny32,
+};
 
 fn main() {
     use clap::{App, Arg};
@@ -24,16 +29,45 @@ fn main() {
                 .long("format")
                 .help("Set the output format")
                 .takes_value(true)
-                .possible_values(&["ascii", "svg", "json", "csv", "tsv"])
+                .possible_values(&["ascii", "svg", "json", "csv", "tsv", "png"])
                 .default_value("ascii"),
         )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .help("Set the output path (required by the png format)")
+                .takes_value(true)
+                .required_if("format", "png"),
+        )
+        .arg(
+            Arg::with_name("offset")
+                .long("offset")
+                .help("Add an offset to the emitted coordinates")
+                .value_names(&["X", "Y"])
+                .number_of_values(2)
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("reverse")
+                .long("reverse")
+                .help("Emit the points in the reverse order"),
+        )
+        .arg(
+            Arg::with_name("stride")
+                .long("stride")
+                .help("Emit only every N-th point, for a coarse preview")
+                .value_name("N")
+                .takes_value(true)
+                .default_value("1"),
+        )
         .arg(
             Arg::with_name("algorithm")
                 .short("a")
                 .long("algorithm")
                 .help("Set the algorithm")
                 .takes_value(true)
-                .possible_values(&["zhang", "zhang-arb"])
+                .possible_values(&["zhang", "zhang-arb", "raster", "serpentine"])
                 .default_value("zhang-arb"),
         )
         .get_matches();
@@ -47,29 +81,73 @@ fn main() {
         .and_then(|x| x.parse().ok())
         .expect("Invalid height");
 
-    let algo = matches.value_of("algorithm").unwrap();
-    let scan: Box<dyn Iterator<Item = [u32; 2]>> = if algo == "zhang" {
-        Box::new(HilbertScan32::new([size_w, size_h]))
-    } else if algo == "zhang-arb" {
-        Box::new(ArbHilbertScan32::new([size_w, size_h]))
+    let offset: Vec<u32> = match matches.values_of("offset") {
+        Some(values) => values
+            .map(|x| {
+                x.parse()
+                    .expect("Invalid offset (must be a non-negative integer)")
+            })
+            .collect(),
+        None => vec![0, 0],
+    };
+    let (offset_x, offset_y) = (offset[0], offset[1]);
+
+    // The canvas includes the area between the origin and the offset scan
+    let canvas_w = size_w.checked_add(offset_x).expect("Offset is too large");
+    let canvas_h = size_h.checked_add(offset_y).expect("Offset is too large");
+
+    let stride: usize = matches
+        .value_of("stride")
+        .and_then(|x| x.parse().ok())
+        .expect("Invalid stride");
+    let stride = stride.max(1);
+
+    let size = [size_w, size_h];
+    let scan: Box<dyn Scan<Coord = u32, Item = [u32; 2]>> =
+        match matches.value_of("algorithm").unwrap() {
+            "zhang" => Box::new(ScanAny32::new(Algorithm::Zhang, size)),
+            "zhang-arb" => Box::new(ScanAny32::new(Algorithm::ZhangArb, size)),
+            "raster" => Box::new(RowMajorScan::new(size)),
+            "serpentine" => Box::new(BoustrophedonScan::new(size)),
+            _ => unreachable!(),
+        };
+    let scan = scan.map(|[x, y]| [x + offset_x, y + offset_y]);
+    let scan: Box<dyn Iterator<Item = [u32; 2]>> = if matches.is_present("reverse") {
+        // The scan can't be iterated backward, so collect the points first
+        Box::new(scan.collect::<Vec<_>>().into_iter().rev())
     } else {
-        unreachable!()
+        Box::new(scan)
     };
+    let scan = scan.step_by(stride);
 
     let format = matches.value_of("format").unwrap();
 
     if format == "ascii" {
         // Warning: The coordinate space here is upside down - +Y is down, -Y is up
-        let mut grid: Array2<char> =
-            Array2::from_shape_fn((size_h as usize, size_w as usize * 2 - 1), |_| ' ');
+        // An empty canvas produces no output
+        let grid_w = (canvas_w as usize * 2).saturating_sub(1);
+        let grid_h = if grid_w == 0 { 0 } else { canvas_h as usize };
+        let mut grid: Array2<char> = Array2::from_shape_fn((grid_h, grid_w), |_| ' ');
         let mut p: Option<[i32; 2]> = None;
         let mut last_dir: Option<Dir> = None;
         for [x, y] in scan {
-            let [x, y] = [x as i32 * 2, (size_h - 1 - y) as i32];
+            let [x, y] = [x as i32 * 2, (canvas_h - 1 - y) as i32];
             if let Some([mut ox, mut oy]) = p {
-                if ox != x {
-                    assert!(oy == y);
-
+                if ox != x && oy != y {
+                    // `--stride` can skip corners. Draw a diagonal connector.
+                    let ch = if (x - ox).signum() == (y - oy).signum() {
+                        '\\'
+                    } else {
+                        '/'
+                    };
+                    let steps = (x - ox).abs().max((y - oy).abs());
+                    for k in 0..=steps {
+                        let cx = ox + (x - ox) * k / steps;
+                        let cy = oy + (y - oy) * k / steps;
+                        grid[[cy as usize, cx as usize]] = ch;
+                    }
+                    last_dir = None;
+                } else if ox != x {
                     let dir = (x - ox).signum();
 
                     grid[[oy as usize, ox as usize]] = match (last_dir, dir) {
@@ -110,30 +188,38 @@ fn main() {
             }
             p = Some([x, y]);
         }
-        for y in 0..size_h as usize {
+        if let Some([x, y]) = p {
+            // A lone point (e.g., a 1×1 scan) isn't covered by any connector
+            let cell = &mut grid[[y as usize, x as usize]];
+            if *cell == ' ' {
+                *cell = '.';
+            }
+        }
+        for y in 0..grid_h {
             let slice = grid.slice(s![y, ..]);
             let s: String = slice.iter().cloned().collect();
             println!("{}", s);
         }
     } else if format == "json" {
-        println!("[");
-        let mut scan = scan.peekable();
-        while let Some([x, y]) = scan.next() {
-            if scan.peek().is_some() {
-                println!("  [{}, {}],", x, y);
-            } else {
-                println!("  [{}, {}]", x, y);
-            }
-        }
-        println!("]");
+        write_points_json(io::stdout().lock(), scan).expect("Failed to write the output");
     } else if format == "csv" {
-        for [x, y] in scan {
-            println!("{}, {}", x, y);
-        }
+        write_points_csv(io::stdout().lock(), scan).expect("Failed to write the output");
     } else if format == "tsv" {
-        for [x, y] in scan {
-            println!("{}\t{}", x, y);
+        write_points_tsv(io::stdout().lock(), scan).expect("Failed to write the output");
+    } else if format == "png" {
+        if canvas_w == 0 || canvas_h == 0 {
+            eprintln!("The png format can't represent an empty image");
+            std::process::exit(1);
+        }
+        // Color each cell by its index along the curve. +Y is up, like svg.
+        let len = f64::from(size_w) * f64::from(size_h);
+        let mut image = RgbImage::new(canvas_w, canvas_h);
+        for (i, [x, y]) in scan.enumerate() {
+            let t = (i * stride) as f64 / len;
+            image.put_pixel(x, canvas_h - 1 - y, Rgb(hue_ramp(t)));
         }
+        let path = matches.value_of("output").unwrap();
+        image.save(path).expect("Failed to write the image");
     } else if format == "svg" {
         const SCALE: u32 = 10;
         println!(r#"<?xml version="1.0" encoding="utf-8"?>"#);
@@ -141,8 +227,8 @@ fn main() {
             r#"<svg version="1.1" xmlns="http://www.w3.org/2000/svg"
             xmlns:xlink="http://www.w3.org/1999/xlink" x="0px" y="0px"
             viewBox="0 0 {} {}">"#,
-            (size_w + 1) * SCALE,
-            (size_h + 1) * SCALE,
+            (canvas_w + 1) * SCALE,
+            (canvas_h + 1) * SCALE,
         );
         print!(r#"<path d=""#);
         for (i, [x, y]) in scan.enumerate() {
@@ -151,7 +237,7 @@ fn main() {
                 "{}{},{}",
                 cmd,
                 (x + 1) * SCALE,
-                (size_h - 1 - y + 1) * SCALE
+                (canvas_h - 1 - y + 1) * SCALE
             );
         }
         println!(r#"" fill="none" stroke="black"/>"#);
@@ -159,6 +245,20 @@ fn main() {
     }
 }
 
+/// Map `t` in `[0, 1)` to a fully saturated color, going from red through
+/// green and blue to magenta.
+fn hue_ramp(t: f64) -> [u8; 3] {
+    let h = t * 5.0;
+    let f = ((h.fract() * 255.0) as u8, 255 - (h.fract() * 255.0) as u8);
+    match h as u32 {
+        0 => [255, f.0, 0],
+        1 => [f.1, 255, 0],
+        2 => [0, 255, f.0],
+        3 => [0, f.1, 255],
+        _ => [f.0, 0, 255],
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Dir {
     PosX,
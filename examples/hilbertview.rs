@@ -1,17 +1,22 @@
 use sdl2::{
     event::{Event, WindowEvent},
     keyboard::Keycode,
-    rect::Point,
-    render::{Canvas, RenderTarget},
+    rect::{Point, Rect},
+    render::{BlendMode, Canvas, RenderTarget},
 };
 use std::cmp::max;
-use zhang_hilbert::{ArbHilbertScan32, HilbertScan32};
+use zhang_hilbert::{Algorithm, ScanAny32};
 
 fn main() {
     use clap::{App, Arg};
     // Use `clap` to parse command-line arguments
     let matches = App::new("hilbertview")
         .about("Displays a pseudo-Hilbert curve in a resizable window")
+        .after_help(
+            "Hover a cell to show its position and index (and the index in the overlaid \
+             curve with --compare). Press G to color the curve by the scan order. Press \
+             Esc to quit.",
+        )
         .arg(
             Arg::with_name("algorithm")
                 .short("a")
@@ -21,15 +26,29 @@ fn main() {
                 .possible_values(&["zhang", "zhang-arb"])
                 .default_value("zhang-arb"),
         )
+        .arg(
+            Arg::with_name("compare")
+                .long("compare")
+                .help("Overlay the curve of the other algorithm for comparison"),
+        )
         .get_matches();
 
-    let algo = matches.value_of("algorithm").unwrap();
-    let points_generator = if algo == "zhang" {
-        make_points_generator(|size| HilbertScan32::new(size))
-    } else if algo == "zhang-arb" {
-        make_points_generator(|size| ArbHilbertScan32::new(size))
+    let algorithm = match matches.value_of("algorithm").unwrap() {
+        "zhang" => Algorithm::Zhang,
+        "zhang-arb" => Algorithm::ZhangArb,
+        _ => unreachable!(),
+    };
+    let points_generator = make_points_generator(move |size| ScanAny32::new(algorithm, size));
+    let overlay_generator = if matches.is_present("compare") {
+        let other = match algorithm {
+            Algorithm::Zhang => Algorithm::ZhangArb,
+            Algorithm::ZhangArb => Algorithm::Zhang,
+        };
+        Some(make_points_generator(move |size| {
+            ScanAny32::new(other, size)
+        }))
     } else {
-        unreachable!()
+        None
     };
 
     let sdl_context = sdl2::init().unwrap();
@@ -44,10 +63,16 @@ fn main() {
         .unwrap();
 
     let mut canvas = window.into_canvas().build().unwrap();
+    canvas.set_blend_mode(BlendMode::Blend);
 
     let mut event_pump = sdl_context.event_pump().unwrap();
 
-    render(&mut canvas, &points_generator);
+    let mut curve = Curve::new(&mut canvas, &points_generator);
+    let mut overlay = overlay_generator
+        .as_ref()
+        .map(|generator| Curve::new(&mut canvas, generator));
+    let mut view = View::default();
+    render(&mut canvas, &curve, overlay.as_ref(), &view);
 
     'running: loop {
         for event in event_pump.wait_iter() {
@@ -65,7 +90,32 @@ fn main() {
                     win_event: WindowEvent::SizeChanged { .. },
                     ..
                 } => {
-                    render(&mut canvas, &points_generator);
+                    curve = Curve::new(&mut canvas, &points_generator);
+                    overlay = overlay_generator
+                        .as_ref()
+                        .map(|generator| Curve::new(&mut canvas, generator));
+                    render(&mut canvas, &curve, overlay.as_ref(), &view);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::G),
+                    ..
+                } => {
+                    view.gradient = !view.gradient;
+                    render(&mut canvas, &curve, overlay.as_ref(), &view);
+                }
+                Event::MouseMotion { x, y, .. } => {
+                    let hover = curve.cell_at(x, y);
+                    if hover != view.hover {
+                        view.hover = hover;
+                        render(&mut canvas, &curve, overlay.as_ref(), &view);
+                    }
+                }
+                Event::Window {
+                    win_event: WindowEvent::Leave,
+                    ..
+                } if view.hover.is_some() => {
+                    view.hover = None;
+                    render(&mut canvas, &curve, overlay.as_ref(), &view);
                 }
                 _ => {}
             }
@@ -75,33 +125,204 @@ fn main() {
 
 const SCALE: u32 = 10;
 
+/// The display settings toggled by the user
+#[derive(Debug, Default)]
+struct View {
+    /// The cell under the mouse cursor
+    hover: Option<[u32; 2]>,
+    /// Color the curve by the scan order (toggled by the G key)
+    gradient: bool,
+}
+
 fn make_points_generator<I: Iterator<Item = [u32; 2]>>(
     f: impl Fn([u32; 2]) -> I + 'static,
-) -> Box<dyn Fn([u32; 2]) -> Vec<Point>> {
-    Box::new(move |size| {
-        let mut points: Vec<Point> = Vec::with_capacity((size[0] * size[1]) as usize);
-        points.extend(f(size).map(|[x, y]| -> Point {
-            (((x + 1) * SCALE) as i32, ((y + 1) * SCALE) as i32).into()
-        }));
-        points
-    })
+) -> Box<dyn Fn([u32; 2]) -> Vec<[u32; 2]>> {
+    Box::new(move |size| f(size).collect())
+}
+
+/// A generated curve and the lookup table from cells to curve indices
+struct Curve {
+    size: [u32; 2],
+    points: Vec<Point>,
+    /// The curve index of each cell, in row-major order
+    indices: Vec<u32>,
 }
 
+impl Curve {
+    fn new<T: RenderTarget>(
+        canvas: &mut Canvas<T>,
+        points_generator: &dyn Fn([u32; 2]) -> Vec<[u32; 2]>,
+    ) -> Self {
+        let (canvas_w, canvas_h) = canvas.output_size().unwrap();
+
+        let size_w = max(canvas_w, SCALE) / SCALE - 1;
+        let size_h = max(canvas_h, SCALE) / SCALE - 1;
+
+        let cells = points_generator([size_w, size_h]);
+        let mut indices = vec![0; (size_w * size_h) as usize];
+        for (i, &[x, y]) in cells.iter().enumerate() {
+            indices[(y * size_w + x) as usize] = i as u32;
+        }
+        let points = cells.iter().map(|&[x, y]| cell_center([x, y])).collect();
+
+        Self {
+            size: [size_w, size_h],
+            points,
+            indices,
+        }
+    }
+
+    /// Find the cell under the given window coordinates.
+    fn cell_at(&self, x: i32, y: i32) -> Option<[u32; 2]> {
+        // Cell centers are at `(x + 1) * SCALE`, so round to the nearest one
+        let half = (SCALE / 2) as i32;
+        let cx = (x + half).div_euclid(SCALE as i32) - 1;
+        let cy = (y + half).div_euclid(SCALE as i32) - 1;
+        if cx < 0 || cy < 0 || cx as u32 >= self.size[0] || cy as u32 >= self.size[1] {
+            return None;
+        }
+        Some([cx as u32, cy as u32])
+    }
+
+    fn index_of(&self, [x, y]: [u32; 2]) -> u32 {
+        self.indices[(y * self.size[0] + x) as usize]
+    }
+}
+
+fn cell_center([x, y]: [u32; 2]) -> Point {
+    (((x + 1) * SCALE) as i32, ((y + 1) * SCALE) as i32).into()
+}
+
+/// The displacement of the overlaid curve, which keeps the segments shared
+/// by both curves distinguishable
+const OVERLAY_OFFSET: i32 = 3;
+
 fn render<T: RenderTarget>(
     canvas: &mut Canvas<T>,
-    points_generator: &Box<dyn Fn([u32; 2]) -> Vec<Point>>,
+    curve: &Curve,
+    overlay: Option<&Curve>,
+    view: &View,
 ) {
-    let (canvas_w, canvas_h) = canvas.output_size().unwrap();
-
-    let size_w = max(canvas_w, SCALE) / SCALE - 1;
-    let size_h = max(canvas_h, SCALE) / SCALE - 1;
-
     canvas.set_draw_color((0, 0, 0));
     canvas.clear();
 
-    let points = points_generator([size_w, size_h]);
-    canvas.set_draw_color((64, 255, 64));
-    canvas.draw_lines(&points[..]).unwrap();
+    if let Some(overlay) = overlay {
+        let points: Vec<Point> = overlay
+            .points
+            .iter()
+            .map(|p| p.offset(OVERLAY_OFFSET, OVERLAY_OFFSET))
+            .collect();
+        canvas.set_draw_color((255, 64, 255, 160));
+        canvas.draw_lines(&points[..]).unwrap();
+    }
+
+    if view.gradient {
+        let len = curve.points.len() as f64;
+        for (i, segment) in curve.points.windows(2).enumerate() {
+            canvas.set_draw_color(hue_ramp(i as f64 / len));
+            canvas.draw_line(segment[0], segment[1]).unwrap();
+        }
+    } else {
+        canvas.set_draw_color((64, 255, 64));
+        canvas.draw_lines(&curve.points[..]).unwrap();
+    }
+
+    if let Some(cell) = view.hover {
+        let center = cell_center(cell);
+        let half = (SCALE / 2) as i32;
+        canvas.set_draw_color((255, 255, 64));
+        canvas
+            .draw_rect(Rect::new(
+                center.x() - half,
+                center.y() - half,
+                SCALE,
+                SCALE,
+            ))
+            .unwrap();
+
+        let mut text = format!("[{}, {}] {}", cell[0], cell[1], curve.index_of(cell));
+        if let Some(overlay) = overlay {
+            text += &format!(" / {}", overlay.index_of(cell));
+        }
+        draw_text(canvas, &text, Point::new(4, 4));
+    }
 
     canvas.present();
 }
+
+/// Map `t` in `[0, 1)` to a fully saturated color, going from red through
+/// green and blue to magenta.
+fn hue_ramp(t: f64) -> (u8, u8, u8) {
+    let h = t * 5.0;
+    let f = ((h.fract() * 255.0) as u8, 255 - (h.fract() * 255.0) as u8);
+    match h as u32 {
+        0 => (255, f.0, 0),
+        1 => (f.1, 255, 0),
+        2 => (0, 255, f.0),
+        3 => (0, f.1, 255),
+        _ => (f.0, 0, 255),
+    }
+}
+
+/// The size of a pixel of `GLYPHS`
+const GLYPH_SCALE: u32 = 2;
+
+/// 3×5 bitmaps of the characters used by the readout. Each row is stored in
+/// the lowest three bits, with the leftmost pixel in the highest bit.
+const GLYPHS: &[(char, [u8; 5])] = &[
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b010, 0b010, 0b010]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('[', [0b110, 0b100, 0b100, 0b100, 0b110]),
+    (']', [0b011, 0b001, 0b001, 0b001, 0b011]),
+    (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]),
+    (' ', [0; 5]),
+];
+
+/// Draw `text` on an opaque background with the top-left corner at `origin`.
+fn draw_text<T: RenderTarget>(canvas: &mut Canvas<T>, text: &str, origin: Point) {
+    let advance = 4 * GLYPH_SCALE as i32;
+    let num_chars = text.chars().count() as u32;
+
+    canvas.set_draw_color((0, 0, 0));
+    canvas
+        .fill_rect(Rect::new(
+            origin.x(),
+            origin.y(),
+            (num_chars * 4 + 1) * GLYPH_SCALE,
+            7 * GLYPH_SCALE,
+        ))
+        .unwrap();
+
+    canvas.set_draw_color((255, 255, 255));
+    let mut pixels = Vec::new();
+    for (i, ch) in text.chars().enumerate() {
+        let glyph = match GLYPHS.iter().find(|&&(c, _)| c == ch) {
+            Some((_, glyph)) => glyph,
+            None => continue,
+        };
+        let x0 = origin.x() + GLYPH_SCALE as i32 + i as i32 * advance;
+        let y0 = origin.y() + GLYPH_SCALE as i32;
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) != 0 {
+                    pixels.push(Rect::new(
+                        x0 + col * GLYPH_SCALE as i32,
+                        y0 + row as i32 * GLYPH_SCALE as i32,
+                        GLYPH_SCALE,
+                        GLYPH_SCALE,
+                    ));
+                }
+            }
+        }
+    }
+    canvas.fill_rects(&pixels).unwrap();
+}
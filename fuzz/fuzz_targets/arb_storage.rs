@@ -0,0 +1,58 @@
+//! Feeds arbitrary sizes and storage lengths to
+//! `ArbHilbertScanCore::try_with_level_state_storage` and checks the scan.
+//!
+//! ```text
+//! cargo +nightly fuzz run arb_storage
+//! ```
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use zhang_hilbert::{num_levels_for_size, ArbHilbertScanVec, LevelState, ScanError};
+
+/// The number of points to check. Larger scans are only partially drained.
+const MAX_POINTS: u64 = 1 << 16;
+
+fuzz_target!(|input: ([u32; 2], u8)| {
+    let (size, storage_len) = input;
+    let [w, h] = size;
+    let level_states = vec![LevelState::default(); storage_len as usize];
+    let result = ArbHilbertScanVec::try_with_level_state_storage(level_states, size);
+
+    let area = u64::from(w) * u64::from(h);
+    let required = if area == 0 {
+        0
+    } else {
+        num_levels_for_size(size)
+    };
+    let mut scan = match result {
+        Ok(scan) => {
+            assert!(storage_len as usize >= required);
+            scan
+        }
+        Err(ScanError::StorageTooSmall { .. }) => {
+            assert!((storage_len as usize) < required);
+            return;
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+    };
+
+    assert_eq!(scan.remaining_len(), area);
+
+    let mut last: Option<[u32; 2]> = None;
+    let mut count = 0;
+    for [x, y] in scan.by_ref().take(MAX_POINTS as usize) {
+        assert!(x < w && y < h, "{:?} is outside {:?}", [x, y], size);
+        if let Some([px, py]) = last {
+            let step = px.max(x) - px.min(x) + py.max(y) - py.min(y);
+            assert_eq!(step, 1, "{:?} -> {:?} in {:?}", [px, py], [x, y], size);
+        }
+        last = Some([x, y]);
+        count += 1;
+    }
+
+    if area <= MAX_POINTS {
+        assert_eq!(count, area);
+        assert_eq!(scan.next(), None);
+    } else {
+        assert_eq!(scan.remaining_len(), area - MAX_POINTS);
+    }
+});
@@ -0,0 +1,839 @@
+//! Iterator adapters transforming the output points
+use num::{traits::float::FloatCore, PrimInt, Signed};
+use std::{borrow::BorrowMut, cmp::min, marker::PhantomData};
+
+use crate::{
+    coord::ScanCoord,
+    core::{check_origin, HilbertScanCore, LevelState, ScanError},
+};
+
+/// An iterator adapter that maps the points to the centers of the
+/// corresponding cells in the unit square `[0, 1]²`.
+///
+/// This `struct` is created by the `normalized` method of
+/// [`HilbertScanCore`](crate::HilbertScanCore) and
+/// [`ArbHilbertScanCore`](crate::ArbHilbertScanCore).
+///
+/// ```
+/// use zhang_hilbert::HilbertScan32;
+/// let points: Vec<[f32; 2]> = HilbertScan32::new([2, 2]).normalized().collect();
+/// assert_eq!(points, [[0.25, 0.25], [0.25, 0.75], [0.75, 0.75], [0.75, 0.25]]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Normalized<I, F> {
+    inner: I,
+    size: [F; 2],
+}
+
+impl<I, F: FloatCore> Normalized<I, F> {
+    pub(crate) fn new<T: ScanCoord>(inner: I, size: [T; 2]) -> Self {
+        Self {
+            inner,
+            size: size.map(|x| F::from(x.to_u128()).unwrap()),
+        }
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I, T, F> Iterator for Normalized<I, F>
+where
+    I: Iterator<Item = [T; 2]>,
+    T: ScanCoord,
+    F: FloatCore,
+{
+    type Item = [F; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let p = self.inner.next()?;
+        let half = F::from(0.5).unwrap();
+        Some([0, 1].map(|i| (F::from(p[i].to_u128()).unwrap() + half) / self.size[i]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I, T, F> std::iter::FusedIterator for Normalized<I, F>
+where
+    I: std::iter::FusedIterator<Item = [T; 2]>,
+    T: ScanCoord,
+    F: FloatCore,
+{
+}
+
+/// An iterator adapter that translates the points so that the rectangle is
+/// centered on the origin.
+///
+/// The points are translated by `-(size / 2)`, where the division is rounded
+/// down. Along an axis of an odd length `2n + 1`, the coordinates range from
+/// `-n` to `n`. Along an axis of an even length `2n`, they range from `-n` to
+/// `n - 1`, i.e., the extra cell is on the negative side.
+///
+/// This `struct` is created by the `centered` method of
+/// [`HilbertScanCore`](crate::HilbertScanCore) and
+/// [`ArbHilbertScanCore`](crate::ArbHilbertScanCore).
+///
+/// ```
+/// use zhang_hilbert::HilbertScan32;
+/// let points: Vec<[i64; 2]> = HilbertScan32::new([2, 3]).centered().collect();
+/// assert!(points.iter().all(|&[x, y]| (-1..=0).contains(&x) && (-1..=1).contains(&y)));
+/// ```
+///
+/// # Panics
+///
+/// The iterator panics if a translated coordinate doesn't fit in the output
+/// type.
+#[derive(Debug, Clone)]
+pub struct Centered<I, S> {
+    inner: I,
+    offset: [S; 2],
+}
+
+impl<I, S: PrimInt + Signed> Centered<I, S> {
+    pub(crate) fn new<T: ScanCoord>(inner: I, size: [T; 2]) -> Self {
+        let two = T::one() + T::one();
+        Self {
+            inner,
+            offset: size.map(|x| S::from((x / two).to_u128()).expect("the rectangle is too large")),
+        }
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I, T, S> Iterator for Centered<I, S>
+where
+    I: Iterator<Item = [T; 2]>,
+    T: ScanCoord,
+    S: PrimInt + Signed,
+{
+    type Item = [S; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let p = self.inner.next()?;
+        Some([0, 1].map(|i| {
+            S::from(p[i].to_u128()).expect("the coordinate is too large") - self.offset[i]
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I, T, S> std::iter::FusedIterator for Centered<I, S>
+where
+    I: std::iter::FusedIterator<Item = [T; 2]>,
+    T: ScanCoord,
+    S: PrimInt + Signed,
+{
+}
+
+/// An iterator adapter that translates the points by a signed offset.
+///
+/// This `struct` is created by the `translated` and `try_translated` methods
+/// of [`HilbertScanCore`](crate::HilbertScanCore) and
+/// [`ArbHilbertScanCore`](crate::ArbHilbertScanCore), which check that every
+/// coordinate fits in `S` both before and after the translation.
+///
+/// ```
+/// use zhang_hilbert::HilbertScan32;
+/// let points: Vec<[i32; 2]> = HilbertScan32::new([2, 2]).translated([-5, 3]).collect();
+/// assert_eq!(points, [[-5, 3], [-5, 4], [-4, 4], [-4, 3]]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Translated<I, S> {
+    inner: I,
+    origin: [S; 2],
+}
+
+impl<I, S: PrimInt + Signed> Translated<I, S> {
+    pub(crate) fn new<T: ScanCoord>(
+        inner: I,
+        size: [T; 2],
+        origin: [S; 2],
+    ) -> Result<Self, ScanError> {
+        if size[0] != T::zero() && size[1] != T::zero() {
+            for i in 0..2 {
+                // The point farthest from the origin must be representable
+                S::from((size[i] - T::one()).to_u128())
+                    .and_then(|x| x.checked_add(&origin[i]))
+                    .ok_or(ScanError::CoordinateOverflow)?;
+            }
+        }
+        Ok(Self { inner, origin })
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I, T, S> Iterator for Translated<I, S>
+where
+    I: Iterator<Item = [T; 2]>,
+    T: ScanCoord,
+    S: PrimInt + Signed,
+{
+    type Item = [S; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let p = self.inner.next()?;
+        // `new` has checked that this doesn't overflow
+        Some([0, 1].map(|i| S::from(p[i].to_u128()).unwrap() + self.origin[i]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I, T, S> std::iter::FusedIterator for Translated<I, S>
+where
+    I: std::iter::FusedIterator<Item = [T; 2]>,
+    T: ScanCoord,
+    S: PrimInt + Signed,
+{
+}
+
+/// An iterator adapter that converts the coordinates to a wider type `O`.
+///
+/// This allows the scan to use a narrow coordinate type for its working
+/// state (e.g., `LevelState<u16>`), which reduces the memory footprint,
+/// while producing the points in the type the caller works with.
+///
+/// This `struct` is created by the `widened` method of
+/// [`HilbertScanCore`](crate::HilbertScanCore) and
+/// [`ArbHilbertScanCore`](crate::ArbHilbertScanCore).
+///
+/// ```
+/// use zhang_hilbert::{HilbertScan16, HilbertScan32};
+/// let points: Vec<[u32; 2]> = HilbertScan16::new([300, 200]).widened().collect();
+/// assert!(points.into_iter().eq(HilbertScan32::new([300, 200])));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Widened<I, O> {
+    inner: I,
+    _output: PhantomData<fn() -> O>,
+}
+
+impl<I, O> Widened<I, O> {
+    pub(crate) fn new(inner: I) -> Self {
+        Self {
+            inner,
+            _output: PhantomData,
+        }
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I, T, O> Iterator for Widened<I, O>
+where
+    I: Iterator<Item = [T; 2]>,
+    T: Into<O>,
+{
+    type Item = [O; 2];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|[x, y]| [x.into(), y.into()])
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(|[x, y]| [x.into(), y.into()])
+    }
+
+    #[inline]
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        self.inner
+            .fold(init, move |acc, [x, y]| f(acc, [x.into(), y.into()]))
+    }
+}
+
+impl<I, T, O> std::iter::FusedIterator for Widened<I, O>
+where
+    I: std::iter::FusedIterator<Item = [T; 2]>,
+    T: Into<O>,
+{
+}
+
+/// A mapping of a rectangle onto itself or its transpose, preserving the
+/// adjacency of the cells.
+///
+/// The rotations are counterclockwise in a coordinate system where +X points
+/// right and +Y points up.
+///
+/// ```
+/// use zhang_hilbert::{HilbertScan32, Transform};
+/// // Find the entry and exit points of a rotated scan
+/// let size = [6, 7];
+/// let start = HilbertScan32::start_point(size).unwrap();
+/// let end = HilbertScan32::end_point(size).unwrap();
+/// assert_eq!(Transform::Rot90.size(size), [7, 6]);
+/// assert_eq!(Transform::Rot90.apply(start, size), [6, 0]);
+/// assert_eq!(Transform::Rot90.apply(end, size), [6, 5]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Transform {
+    /// `[x, y]` is mapped to itself.
+    #[default]
+    Identity,
+    /// `[x, y]` is mapped to `[w - 1 - x, y]`.
+    FlipX,
+    /// `[x, y]` is mapped to `[x, h - 1 - y]`.
+    FlipY,
+    /// `[x, y]` is mapped to `[y, x]`.
+    Transpose,
+    /// `[x, y]` is mapped to `[h - 1 - y, x]`.
+    Rot90,
+    /// `[x, y]` is mapped to `[w - 1 - x, h - 1 - y]`.
+    Rot180,
+    /// `[x, y]` is mapped to `[y, w - 1 - x]`.
+    Rot270,
+    /// `[x, y]` is mapped to `[h - 1 - y, w - 1 - x]`, i.e., the rectangle is
+    /// mirrored across the other diagonal.
+    AntiTranspose,
+}
+
+impl Transform {
+    /// All transformations, which form the symmetry group of a square.
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::FlipX,
+        Transform::FlipY,
+        Transform::Rot180,
+        Transform::Transpose,
+        Transform::Rot90,
+        Transform::Rot270,
+        Transform::AntiTranspose,
+    ];
+
+    /// Check if the transformation exchanges the X and Y axes.
+    pub fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            Self::Transpose | Self::Rot90 | Self::Rot270 | Self::AntiTranspose
+        )
+    }
+
+    /// Get the size of the transformed rectangle of the size `size`.
+    pub fn size<T>(self, [w, h]: [T; 2]) -> [T; 2] {
+        if self.swaps_axes() {
+            [h, w]
+        } else {
+            [w, h]
+        }
+    }
+
+    /// Map a point `[x, y]` in a rectangle of the size `size` (before the
+    /// transformation) to the transformed rectangle.
+    #[inline]
+    pub fn apply<T: ScanCoord>(self, [x, y]: [T; 2], [w, h]: [T; 2]) -> [T; 2] {
+        let flip_x = || w - T::one() - x;
+        let flip_y = || h - T::one() - y;
+        match self {
+            Self::Identity => [x, y],
+            Self::FlipX => [flip_x(), y],
+            Self::FlipY => [x, flip_y()],
+            Self::Transpose => [y, x],
+            Self::Rot90 => [flip_y(), x],
+            Self::Rot180 => [flip_x(), flip_y()],
+            Self::Rot270 => [y, flip_x()],
+            Self::AntiTranspose => [flip_y(), flip_x()],
+        }
+    }
+}
+
+/// An iterator adapter that applies a [`Transform`] to the points.
+///
+/// Every `Transform` maps adjacent cells to adjacent cells, so the
+/// transformed points still form a continuous curve. If the scan has an
+/// origin, the transformed rectangle is placed at the same origin.
+///
+/// This `struct` is created by the `transformed` method of
+/// [`HilbertScanCore`](crate::HilbertScanCore) and
+/// [`ArbHilbertScanCore`](crate::ArbHilbertScanCore).
+///
+/// ```
+/// use zhang_hilbert::{HilbertScan32, Transform};
+/// let points: Vec<[u32; 2]> = HilbertScan32::new([2, 2])
+///     .transformed(Transform::FlipX)
+///     .collect();
+/// assert_eq!(points, [[1, 0], [1, 1], [0, 1], [0, 0]]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transformed<I, T> {
+    inner: I,
+    transform: Transform,
+    size: [T; 2],
+    origin: [T; 2],
+}
+
+impl<I, T: ScanCoord> Transformed<I, T> {
+    pub(crate) fn new(inner: I, transform: Transform, size: [T; 2], origin: [T; 2]) -> Self {
+        check_origin(transform.size(size), origin).unwrap_or_else(|e| panic!("{}", e));
+        Self {
+            inner,
+            transform,
+            size,
+            origin,
+        }
+    }
+
+    /// Get the transformation applied to the points.
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// Get the size of the transformed rectangle.
+    pub fn size(&self) -> [T; 2] {
+        self.transform.size(self.size)
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+/// Apply `transform` to a point of a rectangle located at `origin`.
+#[inline]
+fn transform_point<T: ScanCoord>(
+    transform: Transform,
+    size: [T; 2],
+    [ox, oy]: [T; 2],
+    [x, y]: [T; 2],
+) -> [T; 2] {
+    let [x, y] = transform.apply([x - ox, y - oy], size);
+    [x + ox, y + oy]
+}
+
+impl<I, T> Iterator for Transformed<I, T>
+where
+    I: Iterator<Item = [T; 2]>,
+    T: ScanCoord,
+{
+    type Item = [T; 2];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let p = self.inner.next()?;
+        Some(transform_point(self.transform, self.size, self.origin, p))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let p = self.inner.nth(n)?;
+        Some(transform_point(self.transform, self.size, self.origin, p))
+    }
+
+    #[inline]
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        let Self {
+            inner,
+            transform,
+            size,
+            origin,
+        } = self;
+        inner.fold(init, move |acc, p| {
+            f(acc, transform_point(transform, size, origin, p))
+        })
+    }
+}
+
+impl<I, T> std::iter::FusedIterator for Transformed<I, T>
+where
+    I: std::iter::FusedIterator<Item = [T; 2]>,
+    T: ScanCoord,
+{
+}
+
+/// An iterator adapter that packs each point `[x, y]` into a `u32` as
+/// `(y << 16) | x`.
+///
+/// This `struct` is created by the `packed_u16x2` and `try_packed_u16x2`
+/// methods of [`HilbertScanCore`](crate::HilbertScanCore) and
+/// [`ArbHilbertScanCore`](crate::ArbHilbertScanCore) with `u32`
+/// coordinates, which check that every coordinate fits in 16 bits.
+///
+/// ```
+/// use zhang_hilbert::HilbertScan32;
+/// let packed: Vec<u32> = HilbertScan32::new([2, 2]).packed_u16x2().collect();
+/// assert_eq!(packed, [0x0000_0000, 0x0001_0000, 0x0001_0001, 0x0000_0001]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PackedU16x2<I> {
+    inner: I,
+}
+
+impl<I> PackedU16x2<I> {
+    pub(crate) fn new(inner: I, size: [u32; 2], origin: [u32; 2]) -> Result<Self, ScanError> {
+        if size[0] != 0 && size[1] != 0 {
+            for i in 0..2 {
+                // The caller guarantees that this doesn't overflow
+                if origin[i] + (size[i] - 1) > 0xffff {
+                    return Err(ScanError::CoordinateOverflow);
+                }
+            }
+        }
+        Ok(Self { inner })
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+#[inline]
+fn pack_u16x2([x, y]: [u32; 2]) -> u32 {
+    (y << 16) | x
+}
+
+impl<LevelSt> PackedU16x2<HilbertScanCore<u32, LevelSt>>
+where
+    LevelSt: BorrowMut<[LevelState<u32>]>,
+{
+    /// Write as many of the next packed points as fit into `out` and return
+    /// the number of points written, which is `0` if the scan is complete.
+    ///
+    /// See also: [`HilbertScanCore::fill_buffer`].
+    ///
+    /// ```
+    /// use zhang_hilbert::HilbertScan32;
+    /// let mut packed = HilbertScan32::new([300, 200]).packed_u16x2();
+    /// let mut buffer = vec![0u32; 300 * 200];
+    /// assert_eq!(packed.fill_packed(&mut buffer), buffer.len());
+    /// assert_eq!(packed.fill_packed(&mut buffer), 0);
+    /// ```
+    pub fn fill_packed(&mut self, out: &mut [u32]) -> usize {
+        let mut buffer = [[0; 2]; 64];
+        let mut written = 0;
+        while written < out.len() {
+            let len = min(out.len() - written, buffer.len());
+            let count = self.inner.fill_buffer(&mut buffer[..len]);
+            for (packed, &p) in out[written..].iter_mut().zip(&buffer[..count]) {
+                *packed = pack_u16x2(p);
+            }
+            written += count;
+            if count < len {
+                break;
+            }
+        }
+        written
+    }
+}
+
+impl<I> Iterator for PackedU16x2<I>
+where
+    I: Iterator<Item = [u32; 2]>,
+{
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(pack_u16x2)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(pack_u16x2)
+    }
+
+    #[inline]
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        self.inner.fold(init, move |acc, p| f(acc, pack_u16x2(p)))
+    }
+}
+
+impl<I> std::iter::FusedIterator for PackedU16x2<I> where
+    I: std::iter::FusedIterator<Item = [u32; 2]>
+{
+}
+
+/// An iterator adapter that yields the points as tuples `(x, y)`.
+///
+/// This `struct` is created by the `tuples` method of
+/// [`HilbertScanCore`](crate::HilbertScanCore) and
+/// [`ArbHilbertScanCore`](crate::ArbHilbertScanCore).
+///
+/// ```
+/// use zhang_hilbert::ArbHilbertScan32;
+/// let mut points = Vec::new();
+/// for (x, y) in ArbHilbertScan32::new([4, 4]).tuples() {
+///     points.push((x, y));
+/// }
+/// assert_eq!(&points[..4], [(0, 0), (1, 0), (1, 1), (0, 1)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tuples<I> {
+    inner: I,
+}
+
+impl<I> Tuples<I> {
+    pub(crate) fn new(inner: I) -> Self {
+        Self { inner }
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I, T> Iterator for Tuples<I>
+where
+    I: Iterator<Item = [T; 2]>,
+{
+    type Item = (T, T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|[x, y]| (x, y))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(|[x, y]| (x, y))
+    }
+
+    #[inline]
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        self.inner.fold(init, move |acc, [x, y]| f(acc, (x, y)))
+    }
+}
+
+impl<I, T> std::iter::FusedIterator for Tuples<I> where I: std::iter::FusedIterator<Item = [T; 2]> {}
+
+/// An iterator adapter that yields the points as `mint::Point2`s.
+///
+/// This `struct` is created by the `points_mint` method of
+/// [`HilbertScanCore`](crate::HilbertScanCore) and
+/// [`ArbHilbertScanCore`](crate::ArbHilbertScanCore). Requires the `mint`
+/// feature.
+///
+/// ```
+/// use zhang_hilbert::HilbertScan32;
+/// for p in HilbertScan32::new([11, 42]).points_mint() {
+///     assert!(p.x < 11 && p.y < 42);
+/// }
+/// ```
+#[cfg(feature = "mint")]
+#[derive(Debug, Clone)]
+pub struct MintPoints<I> {
+    inner: I,
+}
+
+#[cfg(feature = "mint")]
+impl<I> MintPoints<I> {
+    pub(crate) fn new(inner: I) -> Self {
+        Self { inner }
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+#[cfg(feature = "mint")]
+impl<I, T> Iterator for MintPoints<I>
+where
+    I: Iterator<Item = [T; 2]>,
+{
+    type Item = mint::Point2<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(mint::Point2::from)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(mint::Point2::from)
+    }
+
+    #[inline]
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        self.inner
+            .fold(init, move |acc, p| f(acc, mint::Point2::from(p)))
+    }
+}
+
+#[cfg(feature = "mint")]
+impl<I, T> std::iter::FusedIterator for MintPoints<I> where
+    I: std::iter::FusedIterator<Item = [T; 2]>
+{
+}
+
+/// An adapter that turns a scan into a `futures::Stream`.
+///
+/// Polling the stream produces the next point by calling `next` on the
+/// wrapped iterator, which never blocks. By default, every poll is ready
+/// immediately. [`yield_every`](ScanStream::yield_every) makes the stream
+/// periodically return `Pending` (after waking the task) so that a long scan
+/// doesn't starve the other tasks on the executor.
+///
+/// This `struct` is created by the `to_stream` method of
+/// [`HilbertScanCore`](crate::HilbertScanCore) and
+/// [`ArbHilbertScanCore`](crate::ArbHilbertScanCore). Requires the
+/// `futures` feature.
+#[cfg(feature = "futures")]
+#[derive(Debug, Clone)]
+pub struct ScanStream<I> {
+    inner: I,
+    yield_every: usize,
+    /// The number of points to produce until the next `Pending`.
+    budget: usize,
+}
+
+#[cfg(feature = "futures")]
+impl<I> ScanStream<I> {
+    pub(crate) fn new(inner: I) -> Self {
+        Self {
+            inner,
+            yield_every: usize::MAX,
+            budget: usize::MAX,
+        }
+    }
+
+    /// Return `Pending` once after every `count` points. `count` must not
+    /// be zero.
+    pub fn yield_every(self, count: usize) -> Self {
+        assert_ne!(count, 0, "the yield interval must not be zero");
+        Self {
+            yield_every: count,
+            budget: count,
+            ..self
+        }
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<I: Iterator + Unpin> futures_core::Stream for ScanStream<I> {
+    type Item = I::Item;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.budget == 0 {
+            this.budget = this.yield_every;
+            cx.waker().wake_by_ref();
+            return std::task::Poll::Pending;
+        }
+        if this.yield_every != usize::MAX {
+            this.budget -= 1;
+        }
+        std::task::Poll::Ready(this.inner.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
@@ -1,8 +1,24 @@
 //! Aspect ratio-bounded tiling
-use num::{PrimInt, Unsigned};
-use std::borrow::BorrowMut;
+use num::{traits::float::FloatCore, PrimInt, Signed};
+use std::{borrow::BorrowMut, cmp::max, convert::TryFrom, fmt, mem::MaybeUninit};
 
-use crate::core::{HilbertScanCore, LevelState};
+use crate::{
+    adapter::{
+        Centered, Normalized, PackedU16x2, Transform, Transformed, Translated, Tuples, Widened,
+    },
+    coord::ScanCoord,
+    core::{
+        area, check_origin, check_scan_size, init_level_states, num_levels_for_size,
+        size_hint_for_len, CurveVariant, HilbertScanCore, LevelState, PrimaryAxis, Rules,
+        ScanError, ScanSnapshot,
+    },
+    snapshot::{ensure, Reader, SnapshotError, Writer},
+};
+
+#[cfg(feature = "heapless")]
+use crate::core::new_heapless_level_states;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// An iterator wrapping [`HilbertScanCore`] that produces better results
 /// for rectangles having extreme proportions.
@@ -15,21 +31,247 @@ use crate::core::{HilbertScanCore, LevelState};
 /// the rectangle into multiple rectangles whose proportions are closer to
 /// square than the original rectangle is (thus *aspect-ratio bounded*).
 ///
-#[derive(Debug)]
+/// The way the rectangle is divided is selected by [`DividerKind`].
+///
+/// The same rules as [`HilbertScanCore`] apply to `Send` and `Sync`.
+///
+#[derive(Clone)]
 pub struct ArbHilbertScanCore<T, LevelSt> {
     inner: Option<HilbertScanCore<T, LevelSt>>,
+    size: [T; 2],
+    kind: DividerKind<T>,
+    /// The offset added to every point.
+    origin: [T; 2],
+    primary_axis: PrimaryAxis,
+    major_axis: u8,
+    parts: Parts<T>,
+    /// The current part.
+    part: Part<T>,
+}
+
+/// A snapshot of an [`ArbHilbertScanCore`].
+///
+/// This is produced by [`ArbHilbertScanCore::save_state`] and consumed by
+/// [`ArbHilbertScanCore::restore`]. See [`ScanSnapshot`] for the supported
+/// encodings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ArbScanSnapshot<T> {
+    inner: ScanSnapshot<T>,
+    size: [T; 2],
+    kind: DividerKind<T>,
+    origin: [T; 2],
+    primary_axis: PrimaryAxis,
     major_axis: u8,
-    divider: Divider<T>,
-    /// The current part's position.
-    pos: T,
-    /// The current part's size.
-    len: T,
+    parts: Parts<T>,
+    part: Part<T>,
+}
+
+/// The magic byte of an encoded `ArbScanSnapshot`.
+const SNAPSHOT_MAGIC: u8 = b'A';
+
+impl<T: ScanCoord> ArbScanSnapshot<T> {
+    /// Encode the snapshot into a byte sequence.
+    ///
+    /// See also: [`ScanSnapshot::to_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer::new(SNAPSHOT_MAGIC);
+        self.inner.write(&mut w);
+        w.uint(self.size[0]);
+        w.uint(self.size[1]);
+        match self.kind {
+            DividerKind::Strip => w.u8(0),
+            DividerKind::Grid { tile_side } => {
+                w.u8(1);
+                w.uint(tile_side);
+            }
+            DividerKind::AspectBound { max_ratio } => {
+                w.u8(2);
+                w.uint(max_ratio);
+            }
+        }
+        w.uint(self.origin[0]);
+        w.uint(self.origin[1]);
+        w.u8(self.primary_axis as u8);
+        w.u8(self.major_axis);
+        match &self.parts {
+            Parts::Strip { divider, pos } => {
+                w.uint(divider.remaining);
+                w.uint(divider.minor);
+                w.uint(*pos);
+            }
+            Parts::Grid(divider) => {
+                w.bool(divider.next.is_some());
+                let next = divider.next.unwrap_or([T::zero(); 2]);
+                w.uint(next[0]);
+                w.uint(next[1]);
+                w.uint(divider.remaining_len);
+            }
+        }
+        let part = &self.part;
+        w.uint(part.origin[0]);
+        w.uint(part.origin[1]);
+        w.uint(part.size[0]);
+        w.uint(part.size[1]);
+        w.u8(part.orientation as u8);
+        w.finish()
+    }
+
+    /// Decode a snapshot encoded by [`to_bytes`](ArbScanSnapshot::to_bytes).
+    ///
+    /// See also: [`ScanSnapshot::from_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut r = Reader::new(bytes, SNAPSHOT_MAGIC)?;
+        let inner = ScanSnapshot::read(&mut r)?;
+        let size = [r.uint()?, r.uint()?];
+        let kind = match r.u8()? {
+            0 => DividerKind::Strip,
+            1 => DividerKind::Grid {
+                tile_side: r.uint()?,
+            },
+            2 => {
+                let max_ratio = r.uint()?;
+                ensure(max_ratio >= T::one())?;
+                DividerKind::AspectBound { max_ratio }
+            }
+            _ => return Err(SnapshotError::Invalid),
+        };
+        let origin = [r.uint()?, r.uint()?];
+        let primary_axis = match r.u8()? {
+            0 => PrimaryAxis::X,
+            1 => PrimaryAxis::Y,
+            _ => return Err(SnapshotError::Invalid),
+        };
+        let major_axis = r.u8()?;
+
+        let empty = size[0] == T::zero() || size[1] == T::zero();
+        ensure(check_origin(size, origin).is_ok())?;
+        ensure(major_axis as usize == major_axis_for(size, primary_axis))?;
+        let mm_size = [size[major_axis as usize], size[major_axis as usize ^ 1]];
+
+        let parts = match (kind, empty) {
+            (DividerKind::Grid { tile_side }, false) => {
+                let mut divider = Divider2D::new(mm_size, tile_side);
+                let has_next = r.bool()?;
+                let next = [r.uint()?, r.uint()?];
+                ensure(next[0] < divider.count[0] && next[1] < divider.count[1])?;
+                divider.next = if has_next { Some(next) } else { None };
+                divider.remaining_len = r.uint()?;
+                ensure(divider.remaining_len <= area(size))?;
+                Parts::Grid(divider)
+            }
+            _ => {
+                let (remaining, minor) = (r.uint()?, r.uint()?);
+                Parts::Strip {
+                    divider: Divider {
+                        remaining,
+                        minor,
+                        max_width: max_part_width(minor, kind),
+                    },
+                    pos: r.uint()?,
+                }
+            }
+        };
+
+        let part: Part<T> = Part {
+            origin: [r.uint()?, r.uint()?],
+            size: [r.uint()?, r.uint()?],
+            orientation: match r.u8()? {
+                0 => Orientation::Normal,
+                1 => Orientation::Transposed,
+                2 => Orientation::Rotated,
+                _ => return Err(SnapshotError::Invalid),
+            },
+        };
+        r.finish()?;
+
+        // Check the invariants that the scan relies on to not panic
+        if empty {
+            ensure(part.origin == [T::zero(); 2] && part.size == size)?;
+            ensure(part.orientation == Orientation::Normal)?;
+            if let Parts::Strip { divider, pos } = &parts {
+                ensure(divider.remaining == T::zero() && divider.minor == T::zero())?;
+                ensure(*pos == T::zero())?;
+            }
+        } else {
+            for ((origin, len), &limit) in part.origin.iter().zip(&part.size).zip(&mm_size) {
+                let end = origin.checked_add(*len);
+                ensure(matches!(end, Some(end) if end <= limit))?;
+            }
+            if let Parts::Strip { divider, pos } = &parts {
+                ensure(divider.minor == mm_size[1] && part.size[1] == mm_size[1])?;
+                // `pos` may be ahead of `part` if `nth` ran past the end
+                ensure(*pos >= part.origin[0] + part.size[0])?;
+                ensure(pos.checked_add(divider.remaining) == Some(mm_size[0]))?;
+                ensure(part.orientation == Orientation::Normal)?;
+            }
+        }
+        ensure(inner.size() == part.scan_size())?;
+
+        Ok(Self {
+            inner,
+            size,
+            kind,
+            origin,
+            primary_axis,
+            major_axis,
+            parts,
+            part,
+        })
+    }
+}
+
+/// Get the rules for scanning the parts, which are always scanned along the
+/// major axis.
+fn part_rules(rules: Rules) -> Rules {
+    Rules {
+        primary_axis: PrimaryAxis::X,
+        ..rules
+    }
+}
+
+/// Find the axis along which a rectangle of the size `size` is divided into
+/// parts. `primary_axis` breaks the tie between the sides of a square.
+fn major_axis_for<T: ScanCoord>(size: [T; 2], primary_axis: PrimaryAxis) -> usize {
+    if size[0] == T::zero() || size[1] == T::zero() {
+        0
+    } else if size[0] == size[1] {
+        primary_axis as usize
+    } else {
+        (size[1] > size[0]) as usize
+    }
+}
+
+/// Specifies how [`ArbHilbertScanCore`] divides the rectangle into parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DividerKind<T> {
+    /// Divide the rectangle along the major axis only, into parts whose
+    /// proportions are close to square. This is the default.
+    Strip,
+    /// Divide the rectangle along both axes, into a grid of tiles whose sides
+    /// are close to `tile_side`. The tiles are visited row by row, reversing
+    /// the direction in every other row, and each tile is scanned in an
+    /// orientation that makes it connect seamlessly to the next one.
+    ///
+    /// This is useful for bounding the working set of a scan over a large
+    /// rectangle. `tile_side` smaller than `2` is treated as `2`.
+    Grid { tile_side: T },
+    /// Divide the rectangle along the major axis only, into parts whose
+    /// aspect ratios (the longer side divided by the shorter side) do not
+    /// exceed `max_ratio`.
+    ///
+    /// The bound is guaranteed to be met if `max_ratio` is at least `2`.
+    /// `max_ratio = 1` produces the tightest parts, but the bound may be
+    /// exceeded if the rectangle cannot be divided into even-width parts
+    /// satisfying it.
+    AspectBound { max_ratio: T },
 }
 
 impl<T, LevelSt> ArbHilbertScanCore<T, LevelSt>
 where
     LevelSt: BorrowMut<[LevelState<T>]>,
-    T: PrimInt + Unsigned + std::fmt::Debug,
+    T: ScanCoord,
 {
     /// Construct a `ArbHilbertScanCore` with a default-constructed `LevelSt` .
     ///
@@ -48,50 +290,779 @@ where
     /// to `size` and it can be calculated using `num_levels_for_size`.
     /// The elements do not have to be initialized as they are overwritten
     /// by this function.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_level_state_storage`]
+    /// returns an error.
+    ///
+    /// [`try_with_level_state_storage`]: ArbHilbertScanCore::try_with_level_state_storage
     pub fn with_level_state_storage(level_states: LevelSt, size: [T; 2]) -> Self {
+        Self::with_divider_kind(level_states, size, DividerKind::Strip)
+    }
+
+    /// Construct a `ArbHilbertScanCore` with an explicit `LevelSt`, returning
+    /// an error instead of panicking if `level_states` is too small or `size`
+    /// is not supported.
+    ///
+    /// See also: [`HilbertScanCore::try_with_level_state_storage`].
+    pub fn try_with_level_state_storage(
+        level_states: LevelSt,
+        size: [T; 2],
+    ) -> Result<Self, ScanError> {
+        Self::try_with_divider_kind(level_states, size, DividerKind::Strip)
+    }
+
+    /// Construct a `ArbHilbertScanCore` with a default-constructed `LevelSt`
+    /// and [`DividerKind::AspectBound`].
+    ///
+    /// ```
+    /// use zhang_hilbert::ArbHilbertScan32;
+    /// let scan = ArbHilbertScan32::with_aspect_bound([100, 7], 4);
+    /// assert_eq!(scan.count(), 700);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_ratio` is zero.
+    pub fn with_aspect_bound(size: [T; 2], max_ratio: T) -> Self
+    where
+        LevelSt: Default,
+    {
+        Self::with_divider_kind(
+            LevelSt::default(),
+            size,
+            DividerKind::AspectBound { max_ratio },
+        )
+    }
+
+    /// Construct a `ArbHilbertScanCore` with an explicit `LevelSt` and
+    /// [`DividerKind`].
+    ///
+    /// The requirement on `level_states` is the same as that of
+    /// [`with_level_state_storage`](Self::with_level_state_storage).
+    ///
+    /// ```
+    /// use zhang_hilbert::{ArbHilbertScan32, DividerKind, LevelState};
+    /// let scan = ArbHilbertScan32::with_divider_kind(
+    ///     [LevelState::default(); 32],
+    ///     [100, 60],
+    ///     DividerKind::Grid { tile_side: 16 },
+    /// );
+    /// assert_eq!(scan.count(), 6000);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_divider_kind`] returns
+    /// an error or panics.
+    ///
+    /// [`try_with_divider_kind`]: ArbHilbertScanCore::try_with_divider_kind
+    pub fn with_divider_kind(level_states: LevelSt, size: [T; 2], kind: DividerKind<T>) -> Self {
+        Self::try_with_divider_kind(level_states, size, kind).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `ArbHilbertScanCore` with an explicit `LevelSt` and
+    /// [`DividerKind`], returning an error instead of panicking if
+    /// `level_states` is too small or `size` is not supported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `kind` is [`DividerKind::AspectBound`] with a zero
+    /// `max_ratio`.
+    pub fn try_with_divider_kind(
+        level_states: LevelSt,
+        size: [T; 2],
+        kind: DividerKind<T>,
+    ) -> Result<Self, ScanError> {
+        Self::try_with_rules(level_states, size, kind, Rules::default(), [T::zero(); 2])
+    }
+
+    /// Construct a `ArbHilbertScanCore` with an explicit `LevelSt` and
+    /// [`PrimaryAxis`].
+    ///
+    /// The rectangle is still divided along the major axis, that is, the
+    /// axis of the longer side, and every part is scanned along it. The
+    /// primary axis is used as the major axis of a square, making the scan
+    /// of a square the transpose of the one with [`PrimaryAxis::X`].
+    ///
+    /// See also: [`HilbertScanCore::with_primary_axis`].
+    ///
+    /// ```
+    /// use zhang_hilbert::{ArbHilbertScan32, LevelState, PrimaryAxis};
+    /// let scan = ArbHilbertScan32::with_primary_axis(
+    ///     [LevelState::default(); 32],
+    ///     [6, 6],
+    ///     PrimaryAxis::Y,
+    /// );
+    /// assert_eq!(scan.major_axis(), 1);
+    /// assert_eq!(scan.last(), Some([0, 5]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_primary_axis`] returns
+    /// an error.
+    ///
+    /// [`try_with_primary_axis`]: ArbHilbertScanCore::try_with_primary_axis
+    pub fn with_primary_axis(
+        level_states: LevelSt,
+        size: [T; 2],
+        primary_axis: PrimaryAxis,
+    ) -> Self {
+        Self::try_with_primary_axis(level_states, size, primary_axis)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `ArbHilbertScanCore` with an explicit `LevelSt` and
+    /// [`PrimaryAxis`], returning an error instead of panicking if
+    /// `level_states` is too small or `size` is not supported.
+    pub fn try_with_primary_axis(
+        level_states: LevelSt,
+        size: [T; 2],
+        primary_axis: PrimaryAxis,
+    ) -> Result<Self, ScanError> {
+        let rules = Rules {
+            primary_axis,
+            ..Rules::default()
+        };
+        Self::try_with_rules(
+            level_states,
+            size,
+            DividerKind::Strip,
+            rules,
+            [T::zero(); 2],
+        )
+    }
+
+    /// Construct a `ArbHilbertScanCore` with an explicit `LevelSt` and
+    /// [`DividerKind`], translating the points by `origin`.
+    ///
+    /// Unlike [`HilbertScanCore::with_origin`], the translation is applied
+    /// along with the mapping from each part to the whole rectangle, which
+    /// is done for every point anyway.
+    ///
+    /// ```
+    /// use zhang_hilbert::{ArbHilbertScan32, ArbHilbertScanCore, DividerKind, LevelState};
+    /// let scan = ArbHilbertScanCore::with_origin(
+    ///     [LevelState::default(); 32],
+    ///     [100u32, 7],
+    ///     DividerKind::Strip,
+    ///     [5, 1000],
+    /// );
+    /// assert!(scan.eq(ArbHilbertScan32::new([100, 7]).map(|[x, y]| [x + 5, y + 1000])));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_origin`] returns an
+    /// error or panics.
+    ///
+    /// [`try_with_origin`]: ArbHilbertScanCore::try_with_origin
+    pub fn with_origin(
+        level_states: LevelSt,
+        size: [T; 2],
+        kind: DividerKind<T>,
+        origin: [T; 2],
+    ) -> Self {
+        Self::try_with_origin(level_states, size, kind, origin).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `ArbHilbertScanCore` with an explicit `LevelSt` and
+    /// [`DividerKind`], translating the points by `origin`, returning an
+    /// error instead of panicking if `level_states` is too small, `size` is
+    /// not supported, or some translated point doesn't fit in `T`.
+    ///
+    /// See also: [`HilbertScanCore::try_with_origin`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `kind` is [`DividerKind::AspectBound`] with a zero
+    /// `max_ratio`.
+    pub fn try_with_origin(
+        level_states: LevelSt,
+        size: [T; 2],
+        kind: DividerKind<T>,
+        origin: [T; 2],
+    ) -> Result<Self, ScanError> {
+        Self::try_with_rules(level_states, size, kind, Rules::default(), origin)
+    }
+
+    /// Construct an `ArbHilbertScanCore` with the given rules, translating
+    /// the points by `origin`. This is what [`ScanBuilder`] uses.
+    ///
+    /// The parts are scanned with `rules` except for the primary axis, which
+    /// only selects the major axis of a square. The parts are connected at the
+    /// exit points of [`CurveVariant::Modified`], so `rules` must use it.
+    ///
+    /// [`ScanBuilder`]: crate::ScanBuilder
+    pub(crate) fn try_with_rules(
+        level_states: LevelSt,
+        size: [T; 2],
+        kind: DividerKind<T>,
+        rules: Rules,
+        origin: [T; 2],
+    ) -> Result<Self, ScanError> {
+        if let DividerKind::AspectBound { max_ratio } = kind {
+            assert!(max_ratio >= T::one(), "invalid aspect bound: 0");
+        }
+        if rules.variant != CurveVariant::Modified {
+            return Err(ScanError::UnsupportedVariant);
+        }
+
+        // The parts are never larger than the whole rectangle, so this also
+        // ensures the storage is large enough for every part
+        check_scan_size(level_states.borrow(), size)?;
+        check_origin(size, origin)?;
+
+        let primary_axis = rules.primary_axis;
+        let part_rules = part_rules(rules);
+
         if size[0] == T::zero() || size[1] == T::zero() {
-            return Self {
-                inner: Some(HilbertScanCore::with_level_state_storage(
+            return Ok(Self {
+                inner: Some(HilbertScanCore::try_with_rules(
                     level_states,
                     size,
-                )),
+                    part_rules,
+                    [T::zero(); 2],
+                )?),
+                size,
+                kind,
+                origin,
+                primary_axis,
                 major_axis: 0,
-                divider: Divider {
-                    remaining: T::zero(),
-                    minor: T::zero(),
+                parts: Parts::Strip {
+                    divider: Divider {
+                        remaining: T::zero(),
+                        minor: T::zero(),
+                        max_width: max_part_width(T::zero(), kind),
+                    },
+                    pos: T::zero(),
                 },
-                pos: T::zero(),
-                len: T::zero(),
-            };
+                part: Part {
+                    origin: [T::zero(); 2],
+                    size,
+                    orientation: Orientation::Normal,
+                },
+            });
         }
 
-        let major_axis = (size[1] > size[0]) as usize;
-        let mut divider = Divider {
-            remaining: size[major_axis],
-            minor: size[major_axis ^ 1],
-        };
+        let major_axis = major_axis_for(size, primary_axis);
+        let mut parts = Parts::new([size[major_axis], size[major_axis ^ 1]], kind);
 
         // The first part
-        let len = divider.next().unwrap_or_else(|| T::zero());
+        let part = parts.next().unwrap();
+        trace!(
+            "starting part: origin={:?} size={:?} orientation={:?}",
+            crate::core::debug_point(part.origin),
+            crate::core::debug_point(part.size),
+            part.orientation
+        );
 
-        Self {
-            inner: Some(HilbertScanCore::with_level_state_storage(
+        Ok(Self {
+            inner: Some(HilbertScanCore::try_with_rules(
                 level_states,
-                [len, divider.minor],
-            )),
+                part.scan_size(),
+                part_rules,
+                [T::zero(); 2],
+            )?),
+            size,
+            kind,
+            origin,
+            primary_axis,
             major_axis: major_axis as u8,
-            divider,
-            pos: T::zero(),
-            len,
+            parts,
+            part,
+        })
+    }
+
+    /// Re-initialize the iterator to scan a rectangle of a different size,
+    /// reusing the existing `LevelSt`.
+    ///
+    /// See also: [`HilbertScanCore::set_size`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice borrowed by `LevelSt` has fewer elements than
+    /// required by [`num_levels_for_size`]`(size)` or the rectangle has more
+    /// points than `u64` can count.
+    ///
+    /// [`num_levels_for_size`]: crate::num_levels_for_size
+    pub fn set_size(&mut self, size: [T; 2]) {
+        let inner = self.inner.as_mut().unwrap();
+        // Check the storage size (the inner scan is re-initialized
+        // by `reset` anyway)
+        inner.set_size(size);
+        check_origin(size, self.origin).unwrap_or_else(|e| panic!("{}", e));
+
+        self.size = size;
+        self.reset();
+    }
+
+    /// Get the first point of the scan of the given size.
+    ///
+    /// See also: [`HilbertScanCore::start_point`].
+    pub fn start_point(size: [T; 2]) -> Option<[T; 2]> {
+        HilbertScanCore::<T, LevelSt>::start_point(size)
+    }
+
+    /// Get the last point of the scan of the given size without performing
+    /// the scan. Returns `None` if the rectangle is empty.
+    ///
+    /// See also: [`HilbertScanCore::end_point`].
+    pub fn end_point(size: [T; 2]) -> Option<[T; 2]> {
+        Self::end_point_with_divider_kind(size, DividerKind::Strip)
+    }
+
+    /// Get the last point of the scan of the given size and [`DividerKind`]
+    /// without performing the scan. Returns `None` if the rectangle is empty.
+    pub fn end_point_with_divider_kind(size: [T; 2], kind: DividerKind<T>) -> Option<[T; 2]> {
+        Self::end_point_with_rules(size, kind, Rules::default())
+    }
+
+    pub(crate) fn end_point_with_rules(
+        size: [T; 2],
+        kind: DividerKind<T>,
+        rules: Rules,
+    ) -> Option<[T; 2]> {
+        if size[0] == T::zero() || size[1] == T::zero() {
+            return None;
         }
+
+        let major_axis = major_axis_for(size, rules.primary_axis);
+        let mut parts = Parts::new([size[major_axis], size[major_axis ^ 1]], kind);
+
+        // Find the last part
+        let mut part = parts.next().unwrap();
+        while let Some(next_part) = parts.next() {
+            part = next_part;
+        }
+
+        let p = HilbertScanCore::<T, LevelSt>::end_point_with_rules(
+            part.scan_size(),
+            part_rules(rules),
+        )?;
+        Some(part.to_global(p, major_axis != 0))
+    }
+
+    /// Map the points to the centers of the corresponding cells in the unit
+    /// square `[0, 1]²`.
+    ///
+    /// See also: [`HilbertScanCore::normalized`].
+    pub fn normalized<F: FloatCore>(self) -> Normalized<Self, F> {
+        let size = self.size;
+        Normalized::new(self, size)
     }
 
-    fn to_global(&self, mut p: [T; 2]) -> [T; 2] {
-        p[0] = p[0] + self.pos;
-        if self.major_axis != 0 {
-            [p[1], p[0]]
+    /// Translate the points so that the rectangle is centered on the
+    /// origin.
+    ///
+    /// See also: [`HilbertScanCore::centered`].
+    pub fn centered<S: PrimInt + Signed>(self) -> Centered<Self, S> {
+        let size = self.size;
+        Centered::new(self, size)
+    }
+
+    /// Translate the points by `origin`, producing signed coordinates.
+    ///
+    /// See also: [`HilbertScanCore::translated`].
+    pub fn translated<S: PrimInt + Signed>(self, origin: [S; 2]) -> Translated<Self, S> {
+        self.try_translated(origin)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Translate the points by `origin`, producing signed coordinates.
+    ///
+    /// See also: [`HilbertScanCore::try_translated`].
+    pub fn try_translated<S: PrimInt + Signed>(
+        self,
+        origin: [S; 2],
+    ) -> Result<Translated<Self, S>, ScanError> {
+        let size = self.size;
+        Translated::new(self, size, origin)
+    }
+
+    /// Convert the coordinates to a wider type `O`.
+    ///
+    /// See also: [`HilbertScanCore::widened`].
+    pub fn widened<O>(self) -> Widened<Self, O>
+    where
+        T: Into<O>,
+    {
+        Widened::new(self)
+    }
+
+    /// Yield the points as `mint::Point2`s instead of arrays.
+    ///
+    /// See also: [`HilbertScanCore::points_mint`].
+    #[cfg(feature = "mint")]
+    pub fn points_mint(self) -> crate::adapter::MintPoints<Self> {
+        crate::adapter::MintPoints::new(self)
+    }
+
+    /// Wrap the scan in a `futures::Stream`.
+    ///
+    /// See also: [`HilbertScanCore::to_stream`].
+    ///
+    /// ```
+    /// use futures_executor::block_on_stream;
+    /// use zhang_hilbert::ArbHilbertScan32;
+    /// let stream = ArbHilbertScan32::new([40, 7]).to_stream();
+    /// assert!(block_on_stream(stream).eq(ArbHilbertScan32::new([40, 7])));
+    /// ```
+    #[cfg(feature = "futures")]
+    pub fn to_stream(self) -> crate::adapter::ScanStream<Self> {
+        crate::adapter::ScanStream::new(self)
+    }
+
+    /// Apply a [`Transform`] to the points.
+    ///
+    /// See also: [`HilbertScanCore::transformed`].
+    pub fn transformed(self, transform: Transform) -> Transformed<Self, T> {
+        let (size, origin) = (self.size, self.origin);
+        Transformed::new(self, transform, size, origin)
+    }
+
+    /// Yield the points as tuples `(x, y)` instead of arrays.
+    pub fn tuples(self) -> Tuples<Self> {
+        Tuples::new(self)
+    }
+
+    /// Get the sub-rectangles scanned one by one by the scan of the given
+    /// size. Each item is a pair of the rectangle's origin and size.
+    ///
+    /// The sub-rectangles are produced in the scan order and tile the
+    /// rectangle without gaps or overlaps.
+    ///
+    /// ```
+    /// use zhang_hilbert::ArbHilbertScan32;
+    /// let parts: Vec<_> = ArbHilbertScan32::parts([40, 7]).collect();
+    /// assert_eq!(parts[0], ([0, 0], [6, 7]));
+    /// assert_eq!(parts.iter().map(|(_, [w, _])| w).sum::<u32>(), 40);
+    /// ```
+    pub fn parts(size: [T; 2]) -> impl Iterator<Item = ([T; 2], [T; 2])> {
+        Self::parts_with_divider_kind(size, DividerKind::Strip)
+    }
+
+    /// Get the sub-rectangles scanned one by one by the scan of the given
+    /// size and [`DividerKind`].
+    ///
+    /// See [`parts`](ArbHilbertScanCore::parts) for details.
+    pub fn parts_with_divider_kind(
+        size: [T; 2],
+        kind: DividerKind<T>,
+    ) -> impl Iterator<Item = ([T; 2], [T; 2])> {
+        let major_axis = (size[1] > size[0]) as usize;
+        let mut parts = if size[0] == T::zero() || size[1] == T::zero() {
+            None
         } else {
-            [p[0], p[1]]
+            Some(Parts::new([size[major_axis], size[major_axis ^ 1]], kind))
+        };
+
+        std::iter::from_fn(move || {
+            let part = parts.as_mut()?.next()?;
+            Some(part.global_rect(major_axis != 0))
+        })
+    }
+
+    /// Rewind the iterator to the beginning of the scan, reusing the existing
+    /// `LevelSt`.
+    ///
+    /// See also: [`HilbertScanCore::reset`].
+    pub fn reset(&mut self) {
+        let rules = self.rules();
+        let level_states = self.inner.take().unwrap().into_level_states();
+        *self = Self::try_with_rules(level_states, self.size, self.kind, rules, self.origin)
+            .unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    /// Take a snapshot of the scan, which can be used by [`restore`] later to
+    /// resume the scan.
+    ///
+    /// See also: [`HilbertScanCore::save_state`].
+    ///
+    /// [`restore`]: ArbHilbertScanCore::restore
+    pub fn save_state(&self) -> ArbScanSnapshot<T> {
+        ArbScanSnapshot {
+            inner: self.inner.as_ref().unwrap().save_state(),
+            size: self.size,
+            kind: self.kind,
+            origin: self.origin,
+            primary_axis: self.primary_axis,
+            major_axis: self.major_axis,
+            parts: self.parts.clone(),
+            part: self.part,
+        }
+    }
+
+    /// Construct an `ArbHilbertScanCore` that continues the scan from the
+    /// point where `snapshot` was taken by [`save_state`].
+    ///
+    /// See also: [`HilbertScanCore::restore`].
+    ///
+    /// [`save_state`]: ArbHilbertScanCore::save_state
+    pub fn restore(snapshot: &ArbScanSnapshot<T>, level_states: LevelSt) -> Self {
+        Self {
+            inner: Some(HilbertScanCore::restore(&snapshot.inner, level_states)),
+            size: snapshot.size,
+            kind: snapshot.kind,
+            origin: snapshot.origin,
+            primary_axis: snapshot.primary_axis,
+            major_axis: snapshot.major_axis,
+            parts: snapshot.parts.clone(),
+            part: snapshot.part,
+        }
+    }
+
+    /// Get the number of points yet to be produced.
+    ///
+    /// See also: [`HilbertScanCore::remaining_len`].
+    pub fn remaining_len(&self) -> u64 {
+        let inner = self.inner.as_ref().unwrap();
+        inner
+            .remaining_len()
+            .saturating_add(self.parts.remaining_len())
+    }
+
+    /// Get the size of the rectangle being scanned.
+    pub fn size(&self) -> [T; 2] {
+        self.size
+    }
+
+    /// Get the first point of the whole scan, including the origin. Returns
+    /// `None` if the rectangle is empty.
+    ///
+    /// See also: [`HilbertScanCore::entry_point`].
+    pub fn entry_point(&self) -> Option<[T; 2]> {
+        Self::start_point(self.size).map(|[x, y]| [x + self.origin[0], y + self.origin[1]])
+    }
+
+    /// Get the last point of the whole scan, including the origin, without
+    /// performing the scan. Returns `None` if the rectangle is empty.
+    ///
+    /// See also: [`HilbertScanCore::exit_point`].
+    pub fn exit_point(&self) -> Option<[T; 2]> {
+        Self::end_point_with_rules(self.size, self.kind, self.rules())
+            .map(|[x, y]| [x + self.origin[0], y + self.origin[1]])
+    }
+
+    /// Get the offset added to every point. See
+    /// [`with_origin`](ArbHilbertScanCore::with_origin).
+    pub fn origin(&self) -> [T; 2] {
+        self.origin
+    }
+
+    /// Get the [`PrimaryAxis`] used by the scan. See
+    /// [`with_primary_axis`](ArbHilbertScanCore::with_primary_axis).
+    pub fn primary_axis(&self) -> PrimaryAxis {
+        self.primary_axis
+    }
+
+    /// Get the rules the scan was constructed with.
+    fn rules(&self) -> Rules {
+        Rules {
+            primary_axis: self.primary_axis,
+            ..self.inner.as_ref().unwrap().rules()
+        }
+    }
+
+    /// Get the axis (X = `0`, Y = `1`) along which the rectangle is divided
+    /// into parts. This is the axis of the longer side, the primary axis if
+    /// the sides are equal, or `0` if the rectangle is empty.
+    pub fn major_axis(&self) -> usize {
+        self.major_axis as usize
+    }
+
+    /// Get the position of the current part along the major axis.
+    ///
+    /// The current part is the one containing the point most recently
+    /// produced by the iterator, or the first part if there's none.
+    ///
+    /// ```
+    /// use zhang_hilbert::ArbHilbertScan32;
+    /// let mut scan = ArbHilbertScan32::new([7, 40]);
+    /// assert_eq!(scan.major_axis(), 1);
+    /// scan.nth(7 * 6);
+    /// assert_eq!((scan.part_pos(), scan.part_len()), (6, 6));
+    /// ```
+    pub fn part_pos(&self) -> T {
+        self.part.origin[0]
+    }
+
+    /// Get the length of the current part along the major axis.
+    ///
+    /// See [`part_pos`](ArbHilbertScanCore::part_pos) for the definition of
+    /// the current part.
+    pub fn part_len(&self) -> T {
+        self.part.size[0]
+    }
+
+    /// Get the wrapped `LevelSt`, consuming `self`.
+    ///
+    /// See also: [`HilbertScanCore::into_level_states`].
+    pub fn into_level_states(self) -> LevelSt {
+        self.inner.unwrap().into_level_states()
+    }
+
+    /// Borrow the level states used by the scan of the current part.
+    ///
+    /// See also: [`HilbertScanCore::level_states`].
+    pub fn level_states(&self) -> &[LevelState<T>] {
+        self.inner.as_ref().unwrap().level_states()
+    }
+
+    fn to_global(&self, p: [T; 2]) -> [T; 2] {
+        let [x, y] = self.part.to_global(p, self.major_axis != 0);
+        [x + self.origin[0], y + self.origin[1]]
+    }
+
+    /// Replace the inner scan with one for `self.part`.
+    fn start_part(&mut self) {
+        trace!(
+            "starting part: origin={:?} size={:?} orientation={:?}",
+            crate::core::debug_point(self.part.origin),
+            crate::core::debug_point(self.part.size),
+            self.part.orientation
+        );
+
+        let inner = self.inner.take().unwrap();
+        let rules = inner.rules();
+        self.inner = Some(
+            HilbertScanCore::try_with_rules(
+                inner.into_level_states(),
+                self.part.scan_size(),
+                rules,
+                [T::zero(); 2],
+            )
+            .unwrap_or_else(|e| panic!("{}", e)),
+        );
+    }
+}
+
+impl<LevelSt> ArbHilbertScanCore<u32, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<u32>]>,
+{
+    /// Pack each point `[x, y]` into a `u32` as `(y << 16) | x`.
+    ///
+    /// See also: [`HilbertScanCore::packed_u16x2`].
+    pub fn packed_u16x2(self) -> PackedU16x2<Self> {
+        self.try_packed_u16x2().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Pack each point `[x, y]` into a `u32` as `(y << 16) | x`.
+    ///
+    /// See also: [`HilbertScanCore::try_packed_u16x2`].
+    pub fn try_packed_u16x2(self) -> Result<PackedU16x2<Self>, ScanError> {
+        let (size, origin) = (self.size, self.origin);
+        PackedU16x2::new(self, size, origin)
+    }
+}
+
+impl<T> ArbHilbertScanCore<T, Vec<LevelState<T>>>
+where
+    T: ScanCoord,
+{
+    /// Construct a `ArbHilbertScanCore` with a `Vec` having exactly as many
+    /// elements as required by [`num_levels_for_size`]`(size)`.
+    ///
+    /// See also: [`HilbertScanCore::new_vec`].
+    pub fn new_vec(size: [T; 2]) -> Self {
+        let level_states = vec![LevelState::new([T::zero(); 2], 0, 0); num_levels_for_size(size)];
+        Self::with_level_state_storage(level_states, size)
+    }
+}
+
+impl<T, const LEVELS: usize> ArbHilbertScanCore<T, [LevelState<T>; LEVELS]>
+where
+    T: ScanCoord,
+{
+    /// Construct a `ArbHilbertScanCore` with an array of `LEVELS` elements.
+    ///
+    /// See also: [`HilbertScanCore::new_array`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_new_array`] returns an error.
+    ///
+    /// [`try_new_array`]: ArbHilbertScanCore::try_new_array
+    pub fn new_array(size: [T; 2]) -> Self {
+        Self::try_new_array(size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `ArbHilbertScanCore` with an array of `LEVELS` elements,
+    /// returning an error if `LEVELS` is too small for `size` or `size` is
+    /// not supported.
+    pub fn try_new_array(size: [T; 2]) -> Result<Self, ScanError> {
+        Self::try_with_level_state_storage([LevelState::new([T::zero(); 2], 0, 0); LEVELS], size)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<T, const N: usize> ArbHilbertScanCore<T, heapless::Vec<LevelState<T>, N>>
+where
+    T: ScanCoord,
+{
+    /// Construct a `ArbHilbertScanCore` with a `heapless::Vec` having exactly
+    /// as many elements as required by [`num_levels_for_size`]`(size)`.
+    ///
+    /// See also: [`HilbertScanCore::new_heapless`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_new_heapless`] returns an
+    /// error.
+    ///
+    /// [`try_new_heapless`]: ArbHilbertScanCore::try_new_heapless
+    pub fn new_heapless(size: [T; 2]) -> Self {
+        Self::try_new_heapless(size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `ArbHilbertScanCore` with a `heapless::Vec`, returning an
+    /// error if the capacity `N` is too small for `size` or `size` is not
+    /// supported.
+    pub fn try_new_heapless(size: [T; 2]) -> Result<Self, ScanError> {
+        Self::try_with_level_state_storage(new_heapless_level_states(size), size)
+    }
+}
+
+impl<'a, T> ArbHilbertScanCore<T, &'a mut [LevelState<T>]>
+where
+    T: ScanCoord,
+{
+    /// Construct a `ArbHilbertScanCore` with uninitialized storage.
+    ///
+    /// See also: [`HilbertScanCore::with_uninit_storage`].
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where
+    /// [`HilbertScanCore::try_with_uninit_storage`] returns an error.
+    pub fn with_uninit_storage(
+        storage: &'a mut [MaybeUninit<LevelState<T>>],
+        size: [T; 2],
+    ) -> Self {
+        Self::with_level_state_storage(init_level_states(storage, size), size)
+    }
+}
+
+/// Prints the divider state followed by the compact representation of the
+/// `HilbertScanCore` scanning the current part.
+impl<T, LevelSt> fmt::Debug for ArbHilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "ArbHilbertScanCore {{ size: {:?}, kind: {:?}, origin: {:?}, major_axis: {}, \
+             parts: {:?}, part: {:?} }}",
+            self.size, self.kind, self.origin, self.major_axis, self.parts, self.part,
+        )?;
+        match &self.inner {
+            Some(inner) => write!(f, "{:?}", inner),
+            None => write!(f, "(no inner scan)"),
         }
     }
 }
@@ -99,14 +1070,28 @@ where
 impl<T, LevelSt> std::iter::FusedIterator for ArbHilbertScanCore<T, LevelSt>
 where
     LevelSt: BorrowMut<[LevelState<T>]>,
-    T: PrimInt + Unsigned + std::fmt::Debug,
+    T: ScanCoord,
+{
+}
+
+// Safety: `size_hint` is derived from `remaining_len`, which is the exact
+// number of points yet to be produced (the remaining points in the current
+// part plus the areas of the parts yet to be started), and never saturates
+// because the sum doesn't exceed the checked area of the whole rectangle.
+// `size_hint_for_len` returns `None` as the upper bound only when it doesn't
+// fit in `usize`.
+#[cfg(feature = "nightly")]
+unsafe impl<T, LevelSt> std::iter::TrustedLen for ArbHilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
 {
 }
 
 impl<T, LevelSt> Iterator for ArbHilbertScanCore<T, LevelSt>
 where
     LevelSt: BorrowMut<[LevelState<T>]>,
-    T: PrimInt + Unsigned + std::fmt::Debug,
+    T: ScanCoord,
 {
     type Item = [T; 2];
 
@@ -115,43 +1100,233 @@ where
             return Some(self.to_global(p));
         }
 
-        let next_len = if let Some(x) = self.divider.next() {
-            x
+        self.part = self.parts.next()?;
+        self.start_part();
+
+        let p = self.inner.as_mut().unwrap().next().unwrap();
+        Some(self.to_global(p))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let mut n = n as u64;
+        let inner_len = self.inner.as_ref().unwrap().remaining_len();
+
+        if n >= inner_len {
+            // Exhaust the current part
+            self.inner.as_mut().unwrap().nth_u64(inner_len);
+            n -= inner_len;
+
+            // Skip whole parts
+            self.part = loop {
+                let part = self.parts.next()?;
+
+                let part_len = area(part.size);
+                if n < part_len {
+                    break part;
+                }
+                n -= part_len;
+            };
+
+            self.start_part();
+        }
+
+        let p = self.inner.as_mut().unwrap().nth_u64(n)?;
+        Some(self.to_global(p))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint_for_len(self.remaining_len())
+    }
+
+    fn count(self) -> usize {
+        usize::try_from(self.remaining_len()).expect("count overflow")
+    }
+
+    fn last(self) -> Option<Self::Item> {
+        if self.remaining_len() == 0 {
+            None
         } else {
-            return None;
+            self.exit_point()
+        }
+    }
+}
+
+/// A sub-rectangle scanned by a single `HilbertScanCore`. The coordinates
+/// are specified in the (major axis, minor axis) coordinate space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Part<T> {
+    origin: [T; 2],
+    size: [T; 2],
+    orientation: Orientation,
+}
+
+/// Specifies how a part's scan is mapped onto the part. `HilbertScanCore`
+/// starts at the bottom-left corner and (given an even width) leaves at the
+/// bottom-right corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Orientation {
+    /// Bottom-left → bottom-right
+    Normal,
+    /// Bottom-left → top-left (requires an even height)
+    Transposed,
+    /// Top-right → top-left
+    Rotated,
+}
+
+impl<T: ScanCoord> Part<T> {
+    /// Get the size of the rectangle to be scanned by `HilbertScanCore`.
+    fn scan_size(&self) -> [T; 2] {
+        match self.orientation {
+            Orientation::Transposed => [self.size[1], self.size[0]],
+            _ => self.size,
+        }
+    }
+
+    /// Get the origin and size of the part in the global coordinate space.
+    fn global_rect(self, transpose: bool) -> ([T; 2], [T; 2]) {
+        let [origin, size] = [self.origin, self.size];
+        if transpose {
+            ([origin[1], origin[0]], [size[1], size[0]])
+        } else {
+            (origin, size)
+        }
+    }
+
+    fn to_global(self, p: [T; 2], transpose: bool) -> [T; 2] {
+        let [x, y] = match self.orientation {
+            Orientation::Normal => p,
+            Orientation::Transposed => [p[1], p[0]],
+            Orientation::Rotated => [
+                self.size[0] - T::one() - p[0],
+                self.size[1] - T::one() - p[1],
+            ],
         };
+        let [x, y] = [x + self.origin[0], y + self.origin[1]];
+        if transpose {
+            [y, x]
+        } else {
+            [x, y]
+        }
+    }
+}
 
-        let level_states = self.inner.take().unwrap().into_level_states();
-        let minor = self.divider.minor;
-        self.inner = Some(HilbertScanCore::with_level_state_storage(
-            level_states,
-            [next_len, minor],
-        ));
-        self.pos = self.pos + self.len;
-        self.len = next_len;
+/// Produces `Part`s in the scan order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Parts<T> {
+    Strip { divider: Divider<T>, pos: T },
+    Grid(Divider2D<T>),
+}
 
-        let p = self.inner.as_mut().unwrap().next().unwrap();
-        Some(self.to_global(p))
+impl<T: ScanCoord> Parts<T> {
+    /// Construct a `Parts` for a non-empty rectangle specified in the
+    /// (major axis, minor axis) coordinate space.
+    fn new(size: [T; 2], kind: DividerKind<T>) -> Self {
+        match kind {
+            DividerKind::Strip | DividerKind::AspectBound { .. } => Parts::Strip {
+                divider: Divider {
+                    remaining: size[0],
+                    minor: size[1],
+                    max_width: max_part_width(size[1], kind),
+                },
+                pos: T::zero(),
+            },
+            DividerKind::Grid { tile_side } => Parts::Grid(Divider2D::new(size, tile_side)),
+        }
+    }
+
+    fn next(&mut self) -> Option<Part<T>> {
+        match self {
+            Parts::Strip { divider, pos } => {
+                let len = divider.next()?;
+                let part = Part {
+                    origin: [*pos, T::zero()],
+                    size: [len, divider.minor],
+                    orientation: Orientation::Normal,
+                };
+                *pos = *pos + len;
+                Some(part)
+            }
+            Parts::Grid(divider) => divider.next(),
+        }
+    }
+
+    /// Get the number of points in the parts yet to be produced.
+    fn remaining_len(&self) -> u64 {
+        match self {
+            Parts::Strip { divider, .. } => area([divider.remaining, divider.minor]),
+            Parts::Grid(divider) => divider.remaining_len,
+        }
     }
 }
 
-#[derive(Debug)]
-struct Divider<T> {
+/// Divides a rectangle along its major axis into parts whose proportions are
+/// close to square. This is the logic used by [`ArbHilbertScanCore`] with
+/// [`DividerKind::Strip`].
+///
+/// `Divider` is an iterator producing the widths (the sizes along the major
+/// axis) of the parts in order. Every width but the last one is an even
+/// number. The widths add up to the length of the major axis. A rectangle
+/// whose minor side is `1` or `2` is not divided at all.
+///
+/// ```
+/// use zhang_hilbert::Divider;
+/// // Divide a 40×7 rectangle
+/// let widths: Vec<u32> = Divider::new(40, 7).collect();
+/// assert_eq!(widths, [6, 6, 8, 6, 8, 6]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Divider<T> {
     remaining: T,
     minor: T,
+    /// The maximum width of a part, which must be an even number. If `None`,
+    /// the number of parts is determined by `division_count`.
+    max_width: Option<T>,
 }
 
-impl<T> Divider<T>
-where
-    T: PrimInt + Unsigned,
-{
+impl<T: ScanCoord> Divider<T> {
+    /// Construct a `Divider` for a rectangle having the size `major` along
+    /// the major axis and `minor` along the minor axis.
+    pub fn new(major: T, minor: T) -> Self {
+        Self {
+            remaining: major,
+            minor,
+            max_width: None,
+        }
+    }
+
+    /// Get the length along the major axis not yet covered by the produced
+    /// parts.
+    pub fn remaining(&self) -> T {
+        self.remaining
+    }
+}
+
+impl<T: ScanCoord> Iterator for Divider<T> {
+    type Item = T;
+
+    /// Get the width of the next part. Returns `None` when the whole
+    /// rectangle has been covered.
     fn next(&mut self) -> Option<T> {
         if self.remaining == T::zero() {
             return None;
         }
 
-        let count = division_count(self.remaining, self.minor);
         let remaining = self.remaining;
+        let count = match self.max_width {
+            // A line or a strip of two rows is scanned in one go. The latter
+            // is scanned by `HilbertScanCore` as a boustrophedon over pairs
+            // of cells, which is also what dividing it into 2×2 parts would
+            // produce. Dividing it would only add overhead.
+            None if self.minor <= T::from_u8(2) => T::one(),
+            None => division_count(remaining, self.minor),
+            // `ceil(remaining / max_width)`. Since `max_width` is even, rounding
+            // `remaining / count` up to an even number doesn't exceed it.
+            Some(max_width) => (remaining - T::one()) / max_width + T::one(),
+        };
 
         let width = if count == T::one() {
             remaining
@@ -171,28 +1346,272 @@ where
     }
 }
 
-/// Estimate the optimal subdivision count.
-fn division_count<T: PrimInt + Unsigned>(major: T, minor: T) -> T {
+impl<T: ScanCoord> std::iter::FusedIterator for Divider<T> {}
+
+/// Divides a rectangle into a grid of tiles and produces them in a
+/// boustrophedon order.
+///
+/// The tiles in the last column and row absorb the remainder, so all other
+/// tiles have even sides. The scan of a tile leaves at a corner adjacent to
+/// the next tile:
+///
+///  - In an even-numbered row (going right), every tile but the last one is
+///    `Normal`. The last one is `Transposed` and leaves upward.
+///  - In an odd-numbered row (going left), the first tile is `Transposed` and
+///    the rest are `Rotated`. The last one leaves upward from its top-left
+///    corner.
+///
+/// `Transposed` requires an even height, so the last row must be an
+/// even-numbered one if it has an odd height (unless there is only one
+/// column).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Divider2D<T> {
+    size: [T; 2],
+    /// The size of non-last tiles.
+    tile: [T; 2],
+    /// The number of columns and rows.
+    count: [T; 2],
+    /// The column and row of the next tile.
+    next: Option<[T; 2]>,
+    remaining_len: u64,
+}
+
+impl<T: ScanCoord> Divider2D<T> {
+    fn new(size: [T; 2], tile_side: T) -> Self {
+        let two = T::one() + T::one();
+        let tile_side = max(tile_side, two);
+
+        let mut count = [
+            grid_count(size[0], tile_side),
+            grid_count(size[1], tile_side),
+        ];
+        if count[0] > T::one()
+            && (count[1] & T::one()) == T::zero()
+            && (size[1] & T::one()) != T::zero()
+        {
+            // Merge the last two rows
+            count[1] = count[1] - T::one();
+        }
+
+        let tile = [
+            max((size[0] / count[0]) >> 1 << 1, two),
+            max((size[1] / count[1]) >> 1 << 1, two),
+        ];
+
+        Self {
+            size,
+            tile,
+            count,
+            next: Some([T::zero(); 2]),
+            remaining_len: area(size),
+        }
+    }
+
+    fn next(&mut self) -> Option<Part<T>> {
+        let [col, row] = self.next?;
+        let last = [self.count[0] - T::one(), self.count[1] - T::one()];
+        let going_right = (row & T::one()) == T::zero();
+
+        let origin = [col * self.tile[0], row * self.tile[1]];
+        let size = [
+            if col == last[0] {
+                self.size[0] - origin[0]
+            } else {
+                self.tile[0]
+            },
+            if row == last[1] {
+                self.size[1] - origin[1]
+            } else {
+                self.tile[1]
+            },
+        ];
+
+        let orientation = if going_right {
+            if col == last[0] && row != last[1] {
+                Orientation::Transposed
+            } else {
+                Orientation::Normal
+            }
+        } else if col == last[0] {
+            if last[0] == T::zero() && row == last[1] {
+                // The final tile in a single-column grid can be in any
+                // orientation starting at the bottom-left corner
+                Orientation::Normal
+            } else {
+                Orientation::Transposed
+            }
+        } else {
+            Orientation::Rotated
+        };
+
+        // Find the next tile
+        self.next = match (going_right, col == last[0], col == T::zero()) {
+            (true, false, _) => Some([col + T::one(), row]),
+            (false, _, false) => Some([col - T::one(), row]),
+            _ if row == last[1] => None,
+            _ => Some([col, row + T::one()]),
+        };
+
+        self.remaining_len = self.remaining_len.saturating_sub(area(size));
+
+        Some(Part {
+            origin,
+            size,
+            orientation,
+        })
+    }
+}
+
+/// Calculate the number of tiles along an axis of length `len`
+/// (`len / tile_side`, rounded to nearest, at least `1`).
+fn grid_count<T: ScanCoord>(len: T, tile_side: T) -> T {
+    let mut count = len / tile_side;
+    if len % tile_side >= tile_side - tile_side / (T::one() + T::one()) {
+        count = count + T::one();
+    }
+    max(count, T::one())
+}
+
+/// Calculate `Divider::max_width` for `DividerKind::AspectBound`.
+fn max_part_width<T: ScanCoord>(minor: T, kind: DividerKind<T>) -> Option<T> {
+    if let DividerKind::AspectBound { max_ratio } = kind {
+        // A width that doesn't fit in `T` can't be reached by any part, so
+        // it's saturated without being made even
+        let width = minor
+            .checked_mul(max_ratio)
+            .map_or_else(T::max_value, |w| w >> 1 << 1);
+        Some(max(width, T::one() + T::one()))
+    } else {
+        None
+    }
+}
+
+/// Estimate the optimal number of parts to divide a rectangle into along its
+/// major axis.
+///
+/// `major` and `minor` are the sizes of the rectangle along the major and
+/// minor axes, respectively. Dividing it into `n` parts produces parts of
+/// width `major / n`. Let `k = major / minor` (rounded down). The parts are
+/// at least as wide as `minor` with `k` parts and narrower than `minor` with
+/// `k + 1` parts. This function returns the one of them producing parts whose
+/// widths (rounded down) are closer to `minor`, i.e., whose proportions are
+/// closer to square. Ties are resolved in favor of `k + 1`.
+///
+/// Returns `1` if `major <= minor`. `minor` must not be zero.
+///
+/// ```
+/// use zhang_hilbert::division_count;
+/// // 45 / 6 = 7 is closer to 7 than 45 / 7 = 6
+/// assert_eq!(division_count(45u32, 7), 6);
+/// // 40 / 5 = 8 and 40 / 6 = 6 are equally close to 7
+/// assert_eq!(division_count(40u32, 7), 6);
+/// assert_eq!(division_count(5u32, 7), 1);
+/// ```
+pub fn division_count<T: ScanCoord>(major: T, minor: T) -> T {
     if major <= minor {
         T::one()
     } else {
-        // I can't believe how many integer divisions I wrote here...
-        // (They are really slow and not fully pipelined on any known
-        // processors)
+        // Integer divisions are really slow and not fully pipelined on any
+        // known processors, so this function is written to do only two of
+        // them (`major % minor` is computed by the same instruction as
+        // `major / minor` on most processors).
         let k = major / minor;
+        let r = major % minor;
 
-        let w1 = major / k;
-        let w2 = major / (k + T::one());
-
-        let d1 = w1 - minor;
-        let d2 = minor - w2;
+        // Let `w1 = major / k` and `w2 = major / (k + 1)`. Since
+        // `major = k * minor + r = (k + 1) * minor - (minor - r)`:
+        //
+        //     d1 = w1 - minor = r / k
+        //     d2 = minor - w2 = ceil((minor - r) / (k + 1))
+        //
+        // and `d1 < d2` is equivalent to `d1 * (k + 1) < minor - r`.
+        let d1 = r / k;
+        let d1_is_smaller = matches!(
+            d1.checked_mul(k + T::one()),
+            Some(x) if x < minor - r
+        );
 
         // Choose the one of `k` and `k + 1` that makes the proportion closer to
         // square
-        if d1 < d2 {
+        if d1_is_smaller {
             k
         } else {
             k + T::one()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::min;
+
+    /// The original implementation of `division_count`.
+    fn division_count_naive(major: u32, minor: u32) -> u32 {
+        if major <= minor {
+            1
+        } else {
+            let k = major / minor;
+            let d1 = major / k - minor;
+            let d2 = minor - major / (k + 1);
+            if d1 < d2 {
+                k
+            } else {
+                k + 1
+            }
+        }
+    }
+
+    #[test]
+    fn division_count_matches_naive() {
+        for minor in 1..=2000 {
+            for major in 1..=4000 {
+                assert_eq!(
+                    division_count(major, minor),
+                    division_count_naive(major, minor),
+                    "{:?}",
+                    (major, minor)
+                );
+            }
+        }
+        for &(major, minor) in &[
+            (u32::MAX, 2),
+            (u32::MAX, 3),
+            (u32::MAX - 1, u32::MAX / 2),
+            (u32::MAX, u32::MAX / 2 + 1),
+            (u32::MAX, u32::MAX / 3),
+        ] {
+            assert_eq!(
+                division_count(major, minor),
+                division_count_naive(major, minor),
+                "{:?}",
+                (major, minor)
+            );
+        }
+    }
+
+    #[test]
+    fn aspect_bound_is_respected() {
+        for &max_ratio in &[2u32, 3, 4, 5, 10] {
+            let kind = DividerKind::AspectBound { max_ratio };
+            for &minor in &[1u32, 2, 3, 5, 7, 16, 33, 100] {
+                for major in (minor..minor * 40).chain(Some(100_000)) {
+                    let mut parts = Parts::new([major, minor], kind);
+                    let mut total = 0;
+                    while let Some(Part { size: [w, h], .. }) = parts.next() {
+                        assert!(
+                            max(w, h) <= min(w, h) * max_ratio,
+                            "{:?} {} {:?}",
+                            [major, minor],
+                            max_ratio,
+                            [w, h]
+                        );
+                        total += w;
+                    }
+                    assert_eq!(total, major);
+                }
+            }
+        }
+    }
+}
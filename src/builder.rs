@@ -0,0 +1,644 @@
+//! Configuring scans through a builder
+use std::{borrow::BorrowMut, fmt};
+
+use crate::{
+    adapter::{Transform, Transformed},
+    arb::{ArbHilbertScanCore, DividerKind},
+    coord::ScanCoord,
+    core::{
+        check_origin, num_levels_for_size, CornerError, CurveVariant, DivisionRule,
+        HilbertScanCore, LevelState, PrimaryAxis, Rules, ScanError,
+    },
+    tiling::{tile_transforms_by, TileOrientation},
+    ScanAnyVec,
+};
+
+/// Selects the algorithm used to produce a scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Algorithm {
+    /// The algorithm by Zhang, et al., applied to the whole rectangle.
+    /// Implemented by [`HilbertScanCore`].
+    Zhang,
+    /// The algorithm by Zhang, et al., applied to the parts of the rectangle
+    /// whose proportions are closer to square. Implemented by
+    /// [`ArbHilbertScanCore`].
+    #[default]
+    ZhangArb,
+}
+
+/// A scan produced by an algorithm selected at runtime.
+///
+/// This is the iterator type produced by [`ScanBuilder`].
+#[derive(Clone)]
+pub enum ScanAny<T, LevelSt> {
+    /// [`Algorithm::Zhang`]
+    Plain(HilbertScanCore<T, LevelSt>),
+    /// [`Algorithm::ZhangArb`]
+    Arb(ArbHilbertScanCore<T, LevelSt>),
+}
+
+impl<T, LevelSt> ScanAny<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    /// Construct a `ScanAny` with a default-constructed `LevelSt`.
+    ///
+    /// This avoids boxing a `dyn Iterator` when the algorithm is selected at
+    /// runtime. See [`HilbertScanCore::new`] for the requirement on `LevelSt`.
+    ///
+    /// ```
+    /// use zhang_hilbert::{Algorithm, ArbHilbertScan32, ScanAny32};
+    /// let scan = ScanAny32::new(Algorithm::ZhangArb, [40, 7]);
+    /// assert!(scan.eq(ArbHilbertScan32::new([40, 7])));
+    /// ```
+    pub fn new(algorithm: Algorithm, size: [T; 2]) -> Self
+    where
+        LevelSt: Default,
+    {
+        Self::with_level_state_storage(algorithm, LevelSt::default(), size)
+    }
+
+    /// Construct a `ScanAny` with an explicit `LevelSt`.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where
+    /// [`HilbertScanCore::try_with_level_state_storage`] returns an error.
+    pub fn with_level_state_storage(
+        algorithm: Algorithm,
+        level_states: LevelSt,
+        size: [T; 2],
+    ) -> Self {
+        match algorithm {
+            Algorithm::Zhang => ScanAny::Plain(HilbertScanCore::with_level_state_storage(
+                level_states,
+                size,
+            )),
+            Algorithm::ZhangArb => ScanAny::Arb(ArbHilbertScanCore::with_level_state_storage(
+                level_states,
+                size,
+            )),
+        }
+    }
+}
+
+impl<T, LevelSt> ScanAny<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    /// Get the size of the rectangle being scanned.
+    pub fn size(&self) -> [T; 2] {
+        match self {
+            ScanAny::Plain(scan) => scan.size(),
+            ScanAny::Arb(scan) => scan.size(),
+        }
+    }
+
+    /// Get the first point of the whole scan. See
+    /// [`HilbertScanCore::entry_point`].
+    pub fn entry_point(&self) -> Option<[T; 2]> {
+        match self {
+            ScanAny::Plain(scan) => scan.entry_point(),
+            ScanAny::Arb(scan) => scan.entry_point(),
+        }
+    }
+
+    /// Get the last point of the whole scan. See
+    /// [`HilbertScanCore::exit_point`].
+    pub fn exit_point(&self) -> Option<[T; 2]> {
+        match self {
+            ScanAny::Plain(scan) => scan.exit_point(),
+            ScanAny::Arb(scan) => scan.exit_point(),
+        }
+    }
+}
+
+impl<T, LevelSt> ScanAny<T, LevelSt> {
+    /// Get the algorithm of the scan.
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            ScanAny::Plain(_) => Algorithm::Zhang,
+            ScanAny::Arb(_) => Algorithm::ZhangArb,
+        }
+    }
+
+    /// Get the wrapped [`HilbertScanCore`] if the algorithm is
+    /// [`Algorithm::Zhang`].
+    pub fn into_plain(self) -> Option<HilbertScanCore<T, LevelSt>> {
+        match self {
+            ScanAny::Plain(scan) => Some(scan),
+            ScanAny::Arb(_) => None,
+        }
+    }
+
+    /// Get the wrapped [`ArbHilbertScanCore`] if the algorithm is
+    /// [`Algorithm::ZhangArb`].
+    pub fn into_arb(self) -> Option<ArbHilbertScanCore<T, LevelSt>> {
+        match self {
+            ScanAny::Plain(_) => None,
+            ScanAny::Arb(scan) => Some(scan),
+        }
+    }
+}
+
+impl<T, LevelSt> fmt::Debug for ScanAny<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanAny::Plain(scan) => scan.fmt(f),
+            ScanAny::Arb(scan) => scan.fmt(f),
+        }
+    }
+}
+
+impl<T, LevelSt> Iterator for ScanAny<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    type Item = [T; 2];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ScanAny::Plain(scan) => scan.next(),
+            ScanAny::Arb(scan) => scan.next(),
+        }
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        match self {
+            ScanAny::Plain(scan) => scan.nth(n),
+            ScanAny::Arb(scan) => scan.nth(n),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            ScanAny::Plain(scan) => scan.size_hint(),
+            ScanAny::Arb(scan) => scan.size_hint(),
+        }
+    }
+
+    fn fold<B, F>(self, init: B, f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        match self {
+            ScanAny::Plain(scan) => scan.fold(init, f),
+            ScanAny::Arb(scan) => scan.fold(init, f),
+        }
+    }
+
+    fn count(self) -> usize {
+        match self {
+            ScanAny::Plain(scan) => scan.count(),
+            ScanAny::Arb(scan) => scan.count(),
+        }
+    }
+
+    fn last(self) -> Option<Self::Item> {
+        match self {
+            ScanAny::Plain(scan) => scan.last(),
+            ScanAny::Arb(scan) => scan.last(),
+        }
+    }
+}
+
+impl<T, LevelSt> std::iter::FusedIterator for ScanAny<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+}
+
+/// A corner of a rectangle. +X points right and +Y points up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corner {
+    /// `[0, 0]`
+    BottomLeft,
+    /// `[w - 1, 0]`
+    BottomRight,
+    /// `[0, h - 1]`
+    TopLeft,
+    /// `[w - 1, h - 1]`
+    TopRight,
+}
+
+impl Corner {
+    /// Get the point at the corner of a non-empty rectangle of the size
+    /// `size`.
+    pub fn point<T: ScanCoord>(self, [w, h]: [T; 2]) -> [T; 2] {
+        let (x0, y0) = (T::zero(), T::zero());
+        let (x1, y1) = (w - T::one(), h - T::one());
+        match self {
+            Corner::BottomLeft => [x0, y0],
+            Corner::BottomRight => [x1, y0],
+            Corner::TopLeft => [x0, y1],
+            Corner::TopRight => [x1, y1],
+        }
+    }
+}
+
+/// Find the transformation that makes the scan end at `corner`, given the
+/// function computing the last point of the scan of a given size.
+fn exit_corner_transform<T: ScanCoord>(
+    size: [T; 2],
+    corner: Corner,
+    end_point: impl Fn([T; 2]) -> Option<[T; 2]>,
+) -> Result<Transform, ScanError> {
+    if size[0] == T::zero() || size[1] == T::zero() {
+        return Err(ScanError::UnreachableCorner(CornerError::EmptyRect));
+    }
+
+    let target = corner.point(size);
+    Transform::ALL
+        .iter()
+        .cloned()
+        .find(|&transform| {
+            // The scan before the transformation
+            let size = transform.size(size);
+            transform.apply(end_point(size).unwrap(), size) == target
+        })
+        .ok_or(ScanError::UnreachableCorner(CornerError::EndsMidSide))
+}
+
+/// The storage of a [`ScanBuilder`] whose storage hasn't been specified.
+/// [`ScanBuilder::build`] allocates a `Vec` having exactly as many elements
+/// as required.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutoStorage;
+
+/// Configures and constructs a scan.
+///
+/// This is the recommended way to construct a scan. The constructors of
+/// [`HilbertScanCore`] and [`ArbHilbertScanCore`] remain available, but each
+/// of them sets only one option, whereas the options set here can be
+/// combined freely.
+///
+/// ```
+/// use zhang_hilbert::{Algorithm, HilbertScan32, LevelState, PrimaryAxis, ScanBuilder};
+///
+/// // `Algorithm::ZhangArb` with a `Vec` allocated on demand
+/// let points: Vec<[u32; 2]> = ScanBuilder::new([5, 3]).build().collect();
+/// assert_eq!(points.len(), 15);
+///
+/// // `Algorithm::Zhang` with an array-based working area
+/// let scan = ScanBuilder::new([5u32, 3])
+///     .algorithm(Algorithm::Zhang)
+///     .storage([LevelState::default(); 32])
+///     .build();
+/// assert!(scan.eq(HilbertScan32::new([5, 3])));
+///
+/// // The concrete type, combining options
+/// let mut scan = ScanBuilder::new([6u32, 4])
+///     .algorithm(Algorithm::Zhang)
+///     .primary_axis(PrimaryAxis::Y)
+///     .origin([100, 20])
+///     .build()
+///     .into_plain()
+///     .unwrap();
+/// assert_eq!(scan.rank([100, 23]), 23);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScanBuilder<T, LevelSt = AutoStorage> {
+    size: [T; 2],
+    algorithm: Algorithm,
+    level_states: LevelSt,
+    rules: Rules,
+    origin: [T; 2],
+    kind: DividerKind<T>,
+}
+
+impl<T: ScanCoord> ScanBuilder<T> {
+    /// Start configuring a scan of a rectangle of the given size.
+    pub fn new(size: [T; 2]) -> Self {
+        Self {
+            size,
+            algorithm: Algorithm::default(),
+            level_states: AutoStorage,
+            rules: Rules::default(),
+            origin: [T::zero(); 2],
+            kind: DividerKind::Strip,
+        }
+    }
+}
+
+impl<T, LevelSt> ScanBuilder<T, LevelSt> {
+    /// Set the algorithm. Defaults to [`Algorithm::ZhangArb`].
+    pub fn algorithm(self, algorithm: Algorithm) -> Self {
+        Self { algorithm, ..self }
+    }
+
+    /// Set the storage for the working area. See
+    /// [`HilbertScanCore::with_level_state_storage`] for the requirements.
+    pub fn storage<NewLevelSt>(self, level_states: NewLevelSt) -> ScanBuilder<T, NewLevelSt> {
+        ScanBuilder {
+            size: self.size,
+            algorithm: self.algorithm,
+            level_states,
+            rules: self.rules,
+            origin: self.origin,
+            kind: self.kind,
+        }
+    }
+
+    /// Set the [`CurveVariant`]. Defaults to [`CurveVariant::Modified`].
+    ///
+    /// [`Algorithm::ZhangArb`] connects the parts at the exit points of
+    /// [`CurveVariant::Modified`] and doesn't support the other variants.
+    /// Building such a scan returns [`ScanError::UnsupportedVariant`].
+    ///
+    /// ```
+    /// use zhang_hilbert::{Algorithm, CurveVariant, ScanBuilder};
+    /// let scan = ScanBuilder::new([4u32, 3])
+    ///     .algorithm(Algorithm::Zhang)
+    ///     .variant(CurveVariant::Original)
+    ///     .build();
+    /// // The scan leaves the rectangle at the top-right corner as described
+    /// // in the paper
+    /// assert_eq!(scan.last(), Some([3, 2]));
+    /// ```
+    pub fn variant(mut self, variant: CurveVariant) -> Self {
+        self.rules.variant = variant;
+        self
+    }
+
+    /// Set the [`DivisionRule`]. Defaults to [`DivisionRule::Fast`].
+    pub fn division_rule(mut self, division: DivisionRule) -> Self {
+        self.rules.division = division;
+        self
+    }
+
+    /// Enable or disable the extra subdivision. Enabled by default.
+    ///
+    /// The smallest blocks produced by the algorithm are divided once more
+    /// if both of their sides are `3` or longer, which improves the
+    /// locality. If disabled, such blocks are scanned by the basic scanning
+    /// pattern (a zigzag) instead. The output is still a valid scan.
+    pub fn extra_subdivision(mut self, extra_subdivision: bool) -> Self {
+        self.rules.extra_subdivision = extra_subdivision;
+        self
+    }
+
+    /// Set the [`PrimaryAxis`]. Defaults to [`PrimaryAxis::X`].
+    ///
+    /// [`Algorithm::ZhangArb`] still divides the rectangle along the axis of
+    /// the longer side and scans every part along it. The primary axis is
+    /// used as the major axis of a square, making the scan of a square the
+    /// transpose of the one with [`PrimaryAxis::X`].
+    ///
+    /// ```
+    /// use zhang_hilbert::{Algorithm, PrimaryAxis, ScanBuilder};
+    /// let scan = ScanBuilder::new([6u32, 4])
+    ///     .algorithm(Algorithm::Zhang)
+    ///     .primary_axis(PrimaryAxis::Y)
+    ///     .build();
+    /// // The scan leaves the rectangle at the top-left corner
+    /// assert_eq!(scan.last(), Some([0, 3]));
+    /// ```
+    pub fn primary_axis(mut self, primary_axis: PrimaryAxis) -> Self {
+        self.rules.primary_axis = primary_axis;
+        self
+    }
+
+    /// Translate the points by `origin`. Defaults to `[0, 0]`.
+    ///
+    /// The translation doesn't add any cost per point. The `rank` method of
+    /// [`HilbertScanCore`] accepts translated points as well. Building the
+    /// scan fails with [`ScanError::CoordinateOverflow`] if some translated
+    /// point doesn't fit in `T`.
+    ///
+    /// ```
+    /// use zhang_hilbert::{ArbHilbertScan32, ScanBuilder};
+    /// let scan = ScanBuilder::new([100u32, 7]).origin([5, 1000]).build();
+    /// assert!(scan.eq(ArbHilbertScan32::new([100, 7]).map(|[x, y]| [x + 5, y + 1000])));
+    /// ```
+    pub fn origin(self, origin: [T; 2]) -> Self {
+        Self { origin, ..self }
+    }
+
+    /// Set the [`DividerKind`] used by [`Algorithm::ZhangArb`]. Defaults to
+    /// [`DividerKind::Strip`]. Ignored by [`Algorithm::Zhang`].
+    ///
+    /// # Panics
+    ///
+    /// Building the scan panics if `kind` is [`DividerKind::AspectBound`]
+    /// with a zero `max_ratio`.
+    pub fn divider_kind(self, kind: DividerKind<T>) -> Self {
+        Self { kind, ..self }
+    }
+}
+
+impl<T: ScanCoord, LevelSt> ScanBuilder<T, LevelSt> {
+    /// Set the minimum side length of the blocks that undergo the extra
+    /// subdivision. Defaults to `3`.
+    ///
+    /// The smallest blocks are divided once more only if both of their sides
+    /// are `min_subdivision_size` or longer. `3` is also the lower bound;
+    /// smaller values are treated as `3`. Raising it trades the locality for
+    /// the iteration speed. The shorter side of the smallest blocks is never
+    /// longer than `4`, so `5` or a greater value has the same effect as
+    /// disabling the extra subdivision by
+    /// [`extra_subdivision`](ScanBuilder::extra_subdivision).
+    ///
+    /// This applies to every part scanned by [`Algorithm::ZhangArb`].
+    ///
+    /// ```
+    /// use zhang_hilbert::{Algorithm, ScanBuilder};
+    /// let scan = ScanBuilder::new([7u32, 7])
+    ///     .algorithm(Algorithm::Zhang)
+    ///     .min_subdivision_size(4)
+    ///     .build()
+    ///     .into_plain()
+    ///     .unwrap();
+    /// assert_eq!(scan.min_subdivision_size(), 4);
+    /// assert_eq!(scan.count(), 49);
+    /// ```
+    pub fn min_subdivision_size(mut self, min_subdivision_size: T) -> Self {
+        // Saturating doesn't change the output because the shorter side of
+        // the smallest blocks is never longer than `4`
+        self.rules.min_subdivision_size = min_subdivision_size
+            .max(T::from_u8(3))
+            .min(T::from_u8(u8::MAX))
+            .to_u128() as u8;
+        self
+    }
+
+    /// Get the last point of the scan of the given size with the options of
+    /// `self`, not including the origin.
+    fn end_point(&self, size: [T; 2]) -> Option<[T; 2]> {
+        match self.algorithm {
+            Algorithm::Zhang => {
+                HilbertScanCore::<T, Vec<LevelState<T>>>::end_point_with_rules(size, self.rules)
+            }
+            Algorithm::ZhangArb => {
+                ArbHilbertScanCore::<T, Vec<LevelState<T>>>::end_point_with_rules(
+                    size, self.kind, self.rules,
+                )
+            }
+        }
+    }
+
+    /// Check that the algorithm supports the [`CurveVariant`]. Building the
+    /// scan checks it as well, but the transformations are found before that.
+    fn check_variant(&self) -> Result<(), ScanError> {
+        if self.algorithm == Algorithm::ZhangArb && self.rules.variant != CurveVariant::Modified {
+            return Err(ScanError::UnsupportedVariant);
+        }
+        Ok(())
+    }
+}
+
+impl<T> ScanBuilder<T, AutoStorage>
+where
+    T: ScanCoord,
+{
+    /// Construct the scan with a `Vec`-based working area.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_build`] returns an error.
+    ///
+    /// [`try_build`]: ScanBuilder::try_build
+    pub fn build(self) -> ScanAnyVec<T> {
+        self.try_build().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct the scan with a `Vec`-based working area, returning an error
+    /// if the size is not supported.
+    pub fn try_build(self) -> Result<ScanAnyVec<T>, ScanError> {
+        let level_states =
+            vec![LevelState::new([T::zero(); 2], 0, 0); num_levels_for_size(self.size)];
+        self.storage(level_states).try_build()
+    }
+
+    /// Construct the scan with a `Vec`-based working area, transformed to end
+    /// at `corner`. See the `exit_corner` method of a `ScanBuilder` with an
+    /// explicit storage.
+    pub fn exit_corner(self, corner: Corner) -> Result<Transformed<ScanAnyVec<T>, T>, ScanError> {
+        let level_states =
+            vec![LevelState::new([T::zero(); 2], 0, 0); num_levels_for_size(self.size)];
+        self.storage(level_states).exit_corner(corner)
+    }
+
+    /// Construct the scan with a `Vec`-based working area, oriented for a
+    /// tile in a row of tiles. See the `tile_orientation` method of a
+    /// `ScanBuilder` with an explicit storage.
+    pub fn tile_orientation(
+        self,
+        orientation: TileOrientation,
+    ) -> Result<Transformed<ScanAnyVec<T>, T>, ScanError> {
+        let level_states =
+            vec![LevelState::new([T::zero(); 2], 0, 0); num_levels_for_size(self.size)];
+        self.storage(level_states).tile_orientation(orientation)
+    }
+}
+
+impl<T, LevelSt> ScanBuilder<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    /// Construct the scan.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_build`] returns an error.
+    ///
+    /// [`try_build`]: ScanBuilder::try_build
+    pub fn build(self) -> ScanAny<T, LevelSt> {
+        self.try_build().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct the scan, returning an error instead of panicking if the
+    /// storage is too small or the size is not supported.
+    pub fn try_build(self) -> Result<ScanAny<T, LevelSt>, ScanError> {
+        Ok(match self.algorithm {
+            Algorithm::Zhang => ScanAny::Plain(HilbertScanCore::try_with_rules(
+                self.level_states,
+                self.size,
+                self.rules,
+                self.origin,
+            )?),
+            Algorithm::ZhangArb => ScanAny::Arb(ArbHilbertScanCore::try_with_rules(
+                self.level_states,
+                self.size,
+                self.kind,
+                self.rules,
+                self.origin,
+            )?),
+        })
+    }
+
+    /// Construct the scan, transformed to end at `corner`.
+    ///
+    /// The scan is flipped, transposed, or rotated as needed, so it may start
+    /// at any corner. Returns [`ScanError::UnreachableCorner`] if no
+    /// transformation of the scan ends at `corner`.
+    ///
+    /// ```
+    /// use zhang_hilbert::{Algorithm, Corner, ScanBuilder};
+    /// let scan = ScanBuilder::new([6u32, 7])
+    ///     .algorithm(Algorithm::Zhang)
+    ///     .exit_corner(Corner::TopLeft)
+    ///     .unwrap();
+    /// assert_eq!(scan.last(), Some([0, 6]));
+    /// ```
+    pub fn exit_corner(
+        self,
+        corner: Corner,
+    ) -> Result<Transformed<ScanAny<T, LevelSt>, T>, ScanError> {
+        self.check_variant()?;
+        let transform = exit_corner_transform(self.size, corner, |size| self.end_point(size))?;
+        self.try_build_transformed(transform)
+    }
+
+    /// Construct the scan, oriented for a tile in a row of tiles chained
+    /// into a continuous path.
+    ///
+    /// Returns [`ScanError::NoSeamlessTiling`] if tiles of the size can't be
+    /// chained. See [`TileOrientation`] and [`tile_sequence`].
+    ///
+    /// [`tile_sequence`]: crate::tile_sequence
+    ///
+    /// ```
+    /// use zhang_hilbert::{Algorithm, ScanBuilder, TileOrientation};
+    /// let scan = ScanBuilder::new([3u32, 3])
+    ///     .algorithm(Algorithm::Zhang)
+    ///     .tile_orientation(TileOrientation::Mirrored)
+    ///     .unwrap();
+    /// let points: Vec<_> = scan.collect();
+    /// assert_eq!(points[0], [0, 2]);
+    /// assert_eq!(points[8], [2, 0]);
+    /// ```
+    pub fn tile_orientation(
+        self,
+        orientation: TileOrientation,
+    ) -> Result<Transformed<ScanAny<T, LevelSt>, T>, ScanError> {
+        self.check_variant()?;
+        let [normal, mirrored] = tile_transforms_by(self.size, |size| self.end_point(size))?;
+        let transform = match orientation {
+            TileOrientation::Normal => normal,
+            TileOrientation::Mirrored => mirrored,
+        };
+        self.try_build_transformed(transform)
+    }
+
+    /// Construct the scan of the rectangle transformed by `transform`.
+    fn try_build_transformed(
+        self,
+        transform: Transform,
+    ) -> Result<Transformed<ScanAny<T, LevelSt>, T>, ScanError> {
+        // `Transformed::new` panics on the overflow
+        check_origin(self.size, self.origin)?;
+        let (size, origin) = (transform.size(self.size), self.origin);
+        let scan = ScanBuilder { size, ..self }.try_build()?;
+        Ok(Transformed::new(scan, transform, size, origin))
+    }
+}
@@ -0,0 +1,118 @@
+//! Periodic snapshots
+use std::borrow::BorrowMut;
+
+use crate::{
+    arb::{ArbHilbertScanCore, ArbScanSnapshot},
+    coord::ScanCoord,
+    core::{HilbertScanCore, LevelState, ScanSnapshot},
+};
+
+/// An iterator whose progress can be saved as a snapshot.
+pub trait SaveState {
+    /// The snapshot type.
+    type Snapshot;
+
+    /// Take a snapshot of the iterator. Resuming from the snapshot yields
+    /// the items that `self` would yield from this point.
+    fn save_state(&self) -> Self::Snapshot;
+}
+
+impl<T, LevelSt> SaveState for HilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    type Snapshot = ScanSnapshot<T>;
+
+    fn save_state(&self) -> Self::Snapshot {
+        HilbertScanCore::save_state(self)
+    }
+}
+
+impl<T, LevelSt> SaveState for ArbHilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    type Snapshot = ArbScanSnapshot<T>;
+
+    fn save_state(&self) -> Self::Snapshot {
+        ArbHilbertScanCore::save_state(self)
+    }
+}
+
+/// An iterator adapter that attaches a snapshot to every `every_n`-th item.
+///
+/// Resuming from a snapshot attached to an item continues the scan from the
+/// item immediately following it.
+///
+/// ```
+/// use zhang_hilbert::{Checkpointed, HilbertScan32};
+/// let mut last_snapshot = None;
+/// for (_point, snapshot) in Checkpointed::new(HilbertScan32::new([11, 42]), 100).take(250) {
+///     if snapshot.is_some() {
+///         last_snapshot = snapshot;
+///     }
+/// }
+///
+/// // Resume from the 200th point
+/// let resumed = HilbertScan32::restore(&last_snapshot.unwrap(), Default::default());
+/// assert!(resumed.eq(HilbertScan32::new([11, 42]).skip(200)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Checkpointed<I> {
+    inner: I,
+    every_n: usize,
+    /// The number of items to yield until the next snapshot.
+    countdown: usize,
+}
+
+impl<I: Iterator + SaveState> Checkpointed<I> {
+    /// Construct a `Checkpointed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `every_n` is zero.
+    pub fn new(inner: I, every_n: usize) -> Self {
+        assert_ne!(every_n, 0, "every_n must not be zero");
+        Self {
+            inner,
+            every_n,
+            countdown: every_n,
+        }
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I: Iterator + SaveState> Iterator for Checkpointed<I> {
+    type Item = (I::Item, Option<I::Snapshot>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+
+        self.countdown -= 1;
+        let snapshot = if self.countdown == 0 {
+            self.countdown = self.every_n;
+            Some(self.inner.save_state())
+        } else {
+            None
+        };
+
+        Some((item, snapshot))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I: std::iter::FusedIterator + SaveState> std::iter::FusedIterator for Checkpointed<I> {}
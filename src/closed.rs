@@ -0,0 +1,246 @@
+//! Closed scans
+use std::{borrow::BorrowMut, fmt};
+
+use crate::{
+    coord::ScanCoord,
+    core::{size_hint_for_len, HilbertScanCore, LevelState, ScanError},
+};
+
+/// A scan whose last point is adjacent to its first point `(0, 0)`, so the
+/// path can be followed in a loop.
+///
+/// The rectangle is split into two halves across an even side, and each half
+/// is scanned by [`HilbertScanCore`] so that the scan enters and leaves the
+/// half on the side facing the other half. The result is a cycle similar to
+/// the Moore curve, which starts at `(0, 0)` by beginning in the middle of
+/// the first half and finishing the first half after the second half.
+///
+/// ```
+/// use zhang_hilbert::ClosedHilbertScan32;
+/// let points: Vec<[u32; 2]> = ClosedHilbertScan32::new([4, 4]).collect();
+/// assert_eq!(points.len(), 16);
+/// assert_eq!(points[0], [0, 0]);
+/// assert_eq!(points[15], [1, 0]);
+/// ```
+///
+/// # Supported sizes
+///
+/// A closed scan exists if and only if both sides are at least 2 and at
+/// least one of them is even. Color the cells like a checkerboard: every
+/// step changes the color, so a path returning next to its start visits as
+/// many cells of one color as the other, which is impossible if both sides
+/// are odd. The constructors return [`ScanError::NoClosedScan`] for the
+/// other sizes, including empty rectangles.
+#[derive(Clone)]
+pub struct ClosedHilbertScan<T, LevelSt> {
+    /// The scans of the first and second halves
+    halves: [HilbertScanCore<T, LevelSt>; 2],
+    /// The width of the first half
+    split: T,
+    /// The height of the rectangle, or the width if `transposed` is set
+    height: T,
+    /// The halves are stacked vertically because the height is odd
+    transposed: bool,
+    /// The number of points of the first half preceding `(0, 0)`, which are
+    /// produced at the end
+    head_len: u64,
+    phase: Phase,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// The points of the first half from `(0, 0)`
+    FirstTail,
+    /// The points of the second half
+    Second,
+    /// The points of the first half preceding `(0, 0)`
+    FirstHead,
+}
+
+impl<T, LevelSt> ClosedHilbertScan<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    /// Construct a `ClosedHilbertScan` with default-constructed `LevelSt`s.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_new`] returns an error.
+    ///
+    /// [`try_new`]: ClosedHilbertScan::try_new
+    pub fn new(size: [T; 2]) -> Self
+    where
+        LevelSt: Default,
+    {
+        Self::try_new(size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `ClosedHilbertScan` with default-constructed `LevelSt`s,
+    /// returning an error if the size is not supported.
+    ///
+    /// ```
+    /// use zhang_hilbert::{ClosedHilbertScan32, ScanError};
+    /// assert_eq!(
+    ///     ClosedHilbertScan32::try_new([5, 3]).unwrap_err(),
+    ///     ScanError::NoClosedScan,
+    /// );
+    /// ```
+    pub fn try_new(size: [T; 2]) -> Result<Self, ScanError>
+    where
+        LevelSt: Default,
+    {
+        Self::try_with_level_state_storage([LevelSt::default(), LevelSt::default()], size)
+    }
+
+    /// Construct a `ClosedHilbertScan` with explicit `LevelSt`s, one for
+    /// each half. Each of them must be as large as required by
+    /// [`HilbertScanCore::with_level_state_storage`] for `size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_level_state_storage`]
+    /// returns an error.
+    ///
+    /// [`try_with_level_state_storage`]: ClosedHilbertScan::try_with_level_state_storage
+    pub fn with_level_state_storage(level_states: [LevelSt; 2], size: [T; 2]) -> Self {
+        Self::try_with_level_state_storage(level_states, size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `ClosedHilbertScan` with explicit `LevelSt`s, returning
+    /// an error instead of panicking if `level_states` are too small or
+    /// `size` is not supported.
+    pub fn try_with_level_state_storage(
+        level_states: [LevelSt; 2],
+        size: [T; 2],
+    ) -> Result<Self, ScanError> {
+        let [w, h] = size;
+        let two = T::from_u8(2);
+        let is_even = |x: T| x % two == T::zero();
+        if w < two || h < two || !(is_even(w) || is_even(h)) {
+            return Err(ScanError::NoClosedScan);
+        }
+
+        // Split across the even side
+        let transposed = !is_even(h);
+        let [width, height] = if transposed { [h, w] } else { [w, h] };
+        let split = width / two;
+
+        // The scan of each half has an even width (`height`) and therefore
+        // ends at the bottom-right corner, which is mapped to the side
+        // facing the other half
+        let [first_st, second_st] = level_states;
+        let mut first = HilbertScanCore::try_with_level_state_storage(first_st, [height, split])?;
+        let second =
+            HilbertScanCore::try_with_level_state_storage(second_st, [height, width - split])?;
+
+        // Start at the point mapped to `(0, 0)`
+        let head_len = first.rank([T::zero(), split - T::one()]);
+
+        Ok(Self {
+            halves: [first, second],
+            split,
+            height,
+            transposed,
+            head_len,
+            phase: Phase::FirstTail,
+        })
+    }
+
+    /// Get the size of the rectangle being scanned.
+    pub fn size(&self) -> [T; 2] {
+        let size = [
+            self.halves[0].size()[1] + self.halves[1].size()[1],
+            self.height,
+        ];
+        if self.transposed {
+            [size[1], size[0]]
+        } else {
+            size
+        }
+    }
+
+    /// Get the number of points yet to be produced.
+    pub fn remaining_len(&self) -> u64 {
+        match self.phase {
+            Phase::FirstTail => {
+                self.halves[0].remaining_len() + self.halves[1].remaining_len() + self.head_len
+            }
+            Phase::Second => self.halves[1].remaining_len() + self.head_len,
+            Phase::FirstHead => self.head_len,
+        }
+    }
+
+    /// Map a point produced by `self.halves[i]` to the rectangle.
+    fn map_point(&self, i: usize, [u, v]: [T; 2]) -> [T; 2] {
+        let p = if i == 0 {
+            [self.split - T::one() - v, u]
+        } else {
+            [self.split + v, self.height - T::one() - u]
+        };
+        if self.transposed {
+            [p[1], p[0]]
+        } else {
+            p
+        }
+    }
+}
+
+impl<T, LevelSt> fmt::Debug for ClosedHilbertScan<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClosedHilbertScan")
+            .field("size", &self.size())
+            .field("transposed", &self.transposed)
+            .field("head_len", &self.head_len)
+            .field("phase", &self.phase)
+            .field("halves", &self.halves)
+            .finish()
+    }
+}
+
+impl<T, LevelSt> std::iter::FusedIterator for ClosedHilbertScan<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+}
+
+impl<T, LevelSt> Iterator for ClosedHilbertScan<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    type Item = [T; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.phase == Phase::FirstTail {
+            if let Some(p) = self.halves[0].next() {
+                return Some(self.map_point(0, p));
+            }
+            self.phase = Phase::Second;
+        }
+
+        if self.phase == Phase::Second {
+            if let Some(p) = self.halves[1].next() {
+                return Some(self.map_point(1, p));
+            }
+            self.halves[0].reset();
+            self.phase = Phase::FirstHead;
+        }
+
+        if self.head_len == 0 {
+            return None;
+        }
+        self.head_len -= 1;
+        let p = self.halves[0].next().unwrap();
+        Some(self.map_point(0, p))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint_for_len(self.remaining_len())
+    }
+}
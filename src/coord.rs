@@ -0,0 +1,259 @@
+//! The coordinate type
+use std::{
+    convert::TryFrom,
+    ops::{Add, BitAnd, BitOr, Div, Mul, Rem, Shl, Shr, Sub},
+};
+
+/// An unsigned integer type usable as the coordinates of a scan.
+///
+/// This is implemented for `u8`, `u16`, `u32`, `u64`, `u128`, and `usize`.
+/// It can be implemented for a newtype around one of them, in which case only
+/// [`BITS`], [`to_u128`], and [`from_u128`] and the operators are required.
+/// The other methods are provided through `u128`, though overriding them
+/// with the native operations is faster.
+///
+/// ```
+/// use std::ops::{Add, BitAnd, BitOr, Div, Mul, Rem, Shl, Shr, Sub};
+/// use zhang_hilbert::{HilbertScan, ScanCoord};
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+/// struct Col(u32);
+///
+/// impl ScanCoord for Col {
+///     const BITS: u32 = 32;
+///     fn to_u128(self) -> u128 {
+///         self.0.into()
+///     }
+///     fn from_u128(x: u128) -> Option<Self> {
+///         std::convert::TryFrom::try_from(x).ok().map(Col)
+///     }
+/// }
+/// # impl Add for Col { type Output = Self; fn add(self, r: Self) -> Self { Col(self.0 + r.0) } }
+/// # impl Sub for Col { type Output = Self; fn sub(self, r: Self) -> Self { Col(self.0 - r.0) } }
+/// # impl Mul for Col { type Output = Self; fn mul(self, r: Self) -> Self { Col(self.0 * r.0) } }
+/// # impl Div for Col { type Output = Self; fn div(self, r: Self) -> Self { Col(self.0 / r.0) } }
+/// # impl Rem for Col { type Output = Self; fn rem(self, r: Self) -> Self { Col(self.0 % r.0) } }
+/// # impl BitAnd for Col { type Output = Self; fn bitand(self, r: Self) -> Self { Col(self.0 & r.0) } }
+/// # impl BitOr for Col { type Output = Self; fn bitor(self, r: Self) -> Self { Col(self.0 | r.0) } }
+/// # impl Shl<usize> for Col { type Output = Self; fn shl(self, r: usize) -> Self { Col(self.0 << r) } }
+/// # impl Shr<usize> for Col { type Output = Self; fn shr(self, r: usize) -> Self { Col(self.0 >> r) } }
+///
+/// let points: Vec<[Col; 2]> = HilbertScan::<Col, 32>::new([Col(5), Col(3)]).collect();
+/// assert_eq!(points.len(), 15);
+/// ```
+///
+/// [`BITS`]: ScanCoord::BITS
+/// [`to_u128`]: ScanCoord::to_u128
+/// [`from_u128`]: ScanCoord::from_u128
+pub trait ScanCoord:
+    Copy
+    + Ord
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + Shl<usize, Output = Self>
+    + Shr<usize, Output = Self>
+{
+    /// The number of bits. Must be in range `8..=128`.
+    const BITS: u32;
+
+    /// Convert `self` to `u128`.
+    fn to_u128(self) -> u128;
+
+    /// Convert `x` to `Self`, returning `None` if it doesn't fit.
+    fn from_u128(x: u128) -> Option<Self>;
+
+    /// Zero.
+    fn zero() -> Self {
+        Self::from_u8(0)
+    }
+
+    /// One.
+    fn one() -> Self {
+        Self::from_u8(1)
+    }
+
+    /// The largest value representable by `Self`.
+    fn max_value() -> Self {
+        Self::from_u128(u128::MAX >> (128 - Self::BITS)).unwrap()
+    }
+
+    /// Convert a small constant to `Self`. Panics if it doesn't fit.
+    fn from_u8(x: u8) -> Self {
+        Self::from_u128(x.into()).unwrap()
+    }
+
+    /// Convert `x` to `Self`, returning `None` if it doesn't fit.
+    fn from_u64(x: u64) -> Option<Self> {
+        Self::from_u128(x.into())
+    }
+
+    /// Convert `x` to `Self`, returning `None` if it doesn't fit.
+    fn from_usize(x: usize) -> Option<Self> {
+        Self::from_u128(x as u128)
+    }
+
+    /// Convert `self` to `u8`, returning `None` if it doesn't fit.
+    fn to_u8(self) -> Option<u8> {
+        u8::try_from(self.to_u128()).ok()
+    }
+
+    /// Convert `self` to `u64`, returning `None` if it doesn't fit.
+    fn to_u64(self) -> Option<u64> {
+        u64::try_from(self.to_u128()).ok()
+    }
+
+    /// Convert `self` to `usize`, returning `None` if it doesn't fit.
+    fn to_usize(self) -> Option<usize> {
+        usize::try_from(self.to_u128()).ok()
+    }
+
+    /// The number of leading zero bits in `BITS` bits.
+    fn leading_zeros(self) -> u32 {
+        self.to_u128().leading_zeros() - (128 - Self::BITS)
+    }
+
+    /// The number of trailing zero bits, which is `BITS` for zero.
+    fn trailing_zeros(self) -> u32 {
+        self.to_u128().trailing_zeros().min(Self::BITS)
+    }
+
+    /// The number of one bits.
+    fn count_ones(self) -> u32 {
+        self.to_u128().count_ones()
+    }
+
+    /// The number of zero bits in `BITS` bits.
+    fn count_zeros(self) -> u32 {
+        Self::BITS - self.count_ones()
+    }
+
+    /// `self + rhs`, returning `None` on overflow.
+    fn checked_add(self, rhs: Self) -> Option<Self> {
+        Self::from_u128(self.to_u128().checked_add(rhs.to_u128())?)
+    }
+
+    /// `self - rhs`, returning `None` on underflow.
+    fn checked_sub(self, rhs: Self) -> Option<Self> {
+        Self::from_u128(self.to_u128().checked_sub(rhs.to_u128())?)
+    }
+
+    /// `self * rhs`, returning `None` on overflow.
+    fn checked_mul(self, rhs: Self) -> Option<Self> {
+        Self::from_u128(self.to_u128().checked_mul(rhs.to_u128())?)
+    }
+
+    /// `self + rhs`, saturating at [`max_value`](ScanCoord::max_value).
+    fn saturating_add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).unwrap_or_else(Self::max_value)
+    }
+
+    /// `self - rhs`, saturating at zero.
+    fn saturating_sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs).unwrap_or_else(Self::zero)
+    }
+
+    /// `self * rhs`, saturating at [`max_value`](ScanCoord::max_value).
+    fn saturating_mul(self, rhs: Self) -> Self {
+        self.checked_mul(rhs).unwrap_or_else(Self::max_value)
+    }
+}
+
+macro_rules! impl_scan_coord {
+    ($($t:ty),*) => {$(
+        impl ScanCoord for $t {
+            const BITS: u32 = <$t>::BITS;
+
+            #[inline]
+            fn to_u128(self) -> u128 {
+                self as u128
+            }
+            #[inline]
+            fn from_u128(x: u128) -> Option<Self> {
+                <$t>::try_from(x).ok()
+            }
+            #[inline]
+            fn zero() -> Self {
+                0
+            }
+            #[inline]
+            fn one() -> Self {
+                1
+            }
+            #[inline]
+            fn max_value() -> Self {
+                <$t>::MAX
+            }
+            #[inline]
+            fn from_u8(x: u8) -> Self {
+                x as $t
+            }
+            #[inline]
+            fn from_u64(x: u64) -> Option<Self> {
+                <$t>::try_from(x).ok()
+            }
+            #[inline]
+            fn from_usize(x: usize) -> Option<Self> {
+                <$t>::try_from(x).ok()
+            }
+            #[inline]
+            fn to_u8(self) -> Option<u8> {
+                u8::try_from(self).ok()
+            }
+            #[inline]
+            fn to_u64(self) -> Option<u64> {
+                u64::try_from(self).ok()
+            }
+            #[inline]
+            fn to_usize(self) -> Option<usize> {
+                usize::try_from(self).ok()
+            }
+            #[inline]
+            fn leading_zeros(self) -> u32 {
+                <$t>::leading_zeros(self)
+            }
+            #[inline]
+            fn trailing_zeros(self) -> u32 {
+                <$t>::trailing_zeros(self)
+            }
+            #[inline]
+            fn count_ones(self) -> u32 {
+                <$t>::count_ones(self)
+            }
+            #[inline]
+            fn count_zeros(self) -> u32 {
+                <$t>::count_zeros(self)
+            }
+            #[inline]
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+            #[inline]
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+            #[inline]
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+            #[inline]
+            fn saturating_add(self, rhs: Self) -> Self {
+                <$t>::saturating_add(self, rhs)
+            }
+            #[inline]
+            fn saturating_sub(self, rhs: Self) -> Self {
+                <$t>::saturating_sub(self, rhs)
+            }
+            #[inline]
+            fn saturating_mul(self, rhs: Self) -> Self {
+                <$t>::saturating_mul(self, rhs)
+            }
+        }
+    )*};
+}
+
+impl_scan_coord!(u8, u16, u32, u64, u128, usize);
@@ -1,7 +1,24 @@
 //! The core implementation of the algorithm.
-use array::Array2;
-use num::{PrimInt, Unsigned};
-use std::{borrow::BorrowMut, cmp::min};
+use num::{traits::float::FloatCore, PrimInt, Signed};
+use std::{
+    borrow::BorrowMut,
+    cmp::{max, min},
+    convert::TryFrom,
+    fmt,
+    mem::MaybeUninit,
+    num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize},
+};
+
+use crate::{
+    adapter::{
+        Centered, Normalized, PackedU16x2, Transform, Transformed, Translated, Tuples, Widened,
+    },
+    coord::ScanCoord,
+    snapshot::{ensure, Reader, SnapshotError, Writer},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Stores pre-calculated values used to generate a pseudo-Hilbert scan of
 /// a specific size.
@@ -11,23 +28,93 @@ pub struct LevelInfo {}
 /// Stores the state data required for a single subdivision level.
 ///
 /// `T` is a type used to represent the output coordinates.
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
 pub struct LevelState<T> {
     size: [T; 2],
-    /// The curve type of this block. Only used for block address assignment.
+    /// The curve type (bits `0..4`) and the progress (bits `4..8`) of this
+    /// level, packed into a single byte.
     ///
-    /// Invariant: `i == 0 || cur.curve_type == CURVE_INDUCTION_TABLE[prev.curve_type][prev.progress]`
+    /// The curve type is only used for block address assignment.
+    /// Invariant: `i == 0 || cur.curve_type() == CURVE_INDUCTION_TABLE[prev.curve_type()][prev.progress()]`
     /// where `cur` is `level_states[i]` and `prev` is `level_states[i - 1]`.
     ///
-    /// Invalid for the last level (`last_level`).
-    curve_type: u8,
-    /// An integer in `0..4`. Indicates which subblock we are in this level's
-    /// block.
+    /// The progress is an integer in `0..4`. Indicates which subblock we are
+    /// in this level's block.
     ///
-    /// Invalid for the last level (`last_level`).
+    /// Both are invalid for the last level (`last_level`).
+    packed: u8,
+}
+
+impl<T> LevelState<T> {
+    pub(crate) fn new(size: [T; 2], curve_type: u8, progress: u8) -> Self {
+        debug_assert!(curve_type < 16 && progress < 16);
+        Self {
+            size,
+            packed: curve_type | (progress << 4),
+        }
+    }
+
+    fn curve_type(&self) -> u8 {
+        self.packed & 0xf
+    }
+
+    fn set_curve_type(&mut self, curve_type: u8) {
+        self.packed = (self.packed & 0xf0) | curve_type;
+    }
+
+    fn progress(&self) -> u8 {
+        self.packed >> 4
+    }
+
+    fn set_progress(&mut self, progress: u8) {
+        self.packed = (self.packed & 0xf) | (progress << 4);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LevelState<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LevelState")
+            .field("size", &self.size)
+            .field("curve_type", &self.curve_type())
+            .field("progress", &self.progress())
+            .finish()
+    }
+}
+
+/// The serialized form of [`LevelState`], which doesn't expose the packing.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "LevelState")]
+struct LevelStateRepr<S> {
+    size: S,
+    curve_type: u8,
     progress: u8,
 }
 
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for LevelState<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LevelStateRepr {
+            size: &self.size,
+            curve_type: self.curve_type(),
+            progress: self.progress(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for LevelState<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let x = LevelStateRepr::<[T; 2]>::deserialize(deserializer)?;
+        if x.curve_type < 16 && x.progress < 16 {
+            Ok(Self::new(x.size, x.curve_type, x.progress))
+        } else {
+            Err(serde::de::Error::custom("invalid level state"))
+        }
+    }
+}
+
 /// The curve type address sequence table.
 ///
 /// Let `Tₜᵣₘ[γ][i]` be `(CURVE_ADDRESS_TABLE[γ] >> (i * 2)) & 0b11`.
@@ -113,9 +200,181 @@ fn curve_end_point(c: u8) -> u8 {
     CURVE_ADDRESS_TABLE[c as usize] >> 6
 }
 
+/// An error returned by the fallible constructors of [`HilbertScanCore`] and
+/// [`ArbHilbertScanCore`](crate::ArbHilbertScanCore).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScanError {
+    /// The slice borrowed by `LevelSt` has fewer elements than required by
+    /// [`num_levels_for_size`].
+    StorageTooSmall { required: usize, provided: usize },
+    /// The rectangle has more points than `u64` can count.
+    UnsupportedSize,
+    /// Some coordinates don't fit in the output type, e.g., after
+    /// translation.
+    CoordinateOverflow,
+    /// The scan can't be oriented to end at the corner requested by
+    /// [`ScanBuilder::exit_corner`](crate::ScanBuilder::exit_corner).
+    UnreachableCorner(CornerError),
+    /// The rectangle doesn't have a closed scan. See
+    /// [`ClosedHilbertScan`](crate::ClosedHilbertScan).
+    NoClosedScan,
+    /// Tiles of the size can't be chained into a continuous path. See
+    /// [`TileOrientation`](crate::TileOrientation).
+    NoSeamlessTiling,
+    /// The mask passed to [`MaskedScan::new`](crate::MaskedScan::new) has a
+    /// different number of elements than the rectangle has cells.
+    MaskSizeMismatch { required: u64, provided: usize },
+    /// The scan passed to [`MaskedScan::new`](crate::MaskedScan::new) has
+    /// already produced some points.
+    ScanInProgress,
+    /// [`Algorithm::ZhangArb`](crate::Algorithm::ZhangArb) doesn't support
+    /// the [`CurveVariant`] requested by
+    /// [`ScanBuilder::variant`](crate::ScanBuilder::variant).
+    UnsupportedVariant,
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::StorageTooSmall { required, provided } => write!(
+                f,
+                "the level state storage is too small (required: {}, provided: {})",
+                required, provided
+            ),
+            ScanError::UnsupportedSize => write!(f, "the rectangle is too large"),
+            ScanError::CoordinateOverflow => {
+                write!(f, "the coordinates don't fit in the output type")
+            }
+            ScanError::UnreachableCorner(reason) => {
+                write!(f, "the scan can't end at the requested corner: {}", reason)
+            }
+            ScanError::NoClosedScan => write!(f, "the rectangle has no closed scan"),
+            ScanError::NoSeamlessTiling => {
+                write!(
+                    f,
+                    "tiles of this size can't be chained into a continuous path"
+                )
+            }
+            ScanError::MaskSizeMismatch { required, provided } => write!(
+                f,
+                "the mask size is incorrect (required: {}, provided: {})",
+                required, provided
+            ),
+            ScanError::ScanInProgress => write!(f, "the scan has already started"),
+            ScanError::UnsupportedVariant => {
+                write!(f, "the curve variant isn't supported by the algorithm")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+/// The reason why a scan can't end at a requested corner. See
+/// [`ScanError::UnreachableCorner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CornerError {
+    /// The rectangle is empty and has no corners.
+    EmptyRect,
+    /// The scan ends in the middle of a side of the rectangle, whichever
+    /// orientation it's in. This happens with
+    /// [`Algorithm::ZhangArb`](crate::Algorithm::ZhangArb) for some sizes
+    /// having an odd side and an even side.
+    EndsMidSide,
+}
+
+impl fmt::Display for CornerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CornerError::EmptyRect => write!(f, "the rectangle is empty"),
+            CornerError::EndsMidSide => write!(
+                f,
+                "the scan of this size ends in the middle of a side in every orientation"
+            ),
+        }
+    }
+}
+
+/// Check if a scan of the given size can be performed using `level_states`.
+///
+/// The storage isn't checked for an empty rectangle, which doesn't use it at
+/// all.
+pub(crate) fn check_scan_size<T: ScanCoord>(
+    level_states: &[LevelState<T>],
+    size: [T; 2],
+) -> Result<(), ScanError> {
+    if size[0] == T::zero() || size[1] == T::zero() {
+        return Ok(());
+    }
+
+    check_nonzero_scan_size(level_states, size)
+}
+
+/// [`check_scan_size`] for a rectangle known to be non-empty.
+fn check_nonzero_scan_size<T: ScanCoord>(
+    level_states: &[LevelState<T>],
+    size: [T; 2],
+) -> Result<(), ScanError> {
+    let [w, h] = size.map(|x| x.to_u64());
+    if w.zip(h).and_then(|(w, h)| w.checked_mul(h)).is_none() {
+        return Err(ScanError::UnsupportedSize);
+    }
+
+    let required = num_levels_for_size(size);
+    let provided = level_states.len();
+    if provided < required {
+        return Err(ScanError::StorageTooSmall { required, provided });
+    }
+
+    Ok(())
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A non-zero unsigned integer type accepted by
+/// [`HilbertScanCore::new_nonzero`].
+///
+/// This trait is sealed and implemented for `NonZeroU8`, `NonZeroU16`,
+/// `NonZeroU32`, `NonZeroU64`, and `NonZeroUsize`.
+pub trait NonZeroCoord: sealed::Sealed + Copy {
+    /// The corresponding primitive integer type.
+    type Int: ScanCoord;
+
+    /// Get the value as a primitive integer.
+    fn get(self) -> Self::Int;
+}
+
+macro_rules! impl_non_zero_coord {
+    ($($nz:ty => $int:ty),*) => {$(
+        impl sealed::Sealed for $nz {}
+
+        impl NonZeroCoord for $nz {
+            type Int = $int;
+
+            #[inline]
+            fn get(self) -> $int {
+                <$nz>::get(self)
+            }
+        }
+    )*};
+}
+
+impl_non_zero_coord!(
+    NonZeroU8 => u8,
+    NonZeroU16 => u16,
+    NonZeroU32 => u32,
+    NonZeroU64 => u64,
+    NonZeroUsize => usize
+);
+
 /// Get the number of [`LevelState`]s required by [`HilbertScanCore`] to
 /// hold its internal state.
-pub fn num_levels_for_size<T: PrimInt + Unsigned>(size: [T; 2]) -> usize {
+///
+/// Degenerate rectangles (those with a side no longer than `1`) are scanned
+/// as a straight line and require only one level.
+pub fn num_levels_for_size<T: ScanCoord>(size: [T; 2]) -> usize {
     if size[0] <= T::one() || size[1] <= T::one() {
         // In this case, the contents of `level_states` aren't used at all but
         // they are still accessed
@@ -127,27 +386,592 @@ pub fn num_levels_for_size<T: PrimInt + Unsigned>(size: [T; 2]) -> usize {
     }
 }
 
-fn log2_floor<T: PrimInt>(x: T) -> u32 {
+/// Get the number of [`LevelState`]s used by [`HilbertScanCore`] to subdivide
+/// the rectangle. Returns `None` if the rectangle is degenerate (i.e., has a
+/// side no longer than `1`) and is scanned without subdivision.
+///
+/// The returned value, if any, is equal to [`num_levels_for_size`]`(size)`.
+/// The last level is only used by the blocks that undergo the extra
+/// subdivision, so the scan may leave it untouched for some sizes.
+pub fn checked_num_levels_for_size<T: ScanCoord>(size: [T; 2]) -> Option<usize> {
+    if size[0] <= T::one() || size[1] <= T::one() {
+        None
+    } else {
+        Some(num_levels_for_size(size))
+    }
+}
+
+/// A `const fn` version of [`num_levels_for_size`] for `u32`, which can be
+/// used to size an array of [`LevelState`]s at compile time.
+///
+/// ```
+/// use zhang_hilbert::{num_levels_for_size_u32, HilbertScanCore, LevelState};
+/// const MAX_SIZE: [u32; 2] = [1920, 1080];
+/// type Levels = [LevelState<u32>; num_levels_for_size_u32(MAX_SIZE)];
+///
+/// // Any rectangle fitting in `MAX_SIZE` can be scanned
+/// let levels: Levels = Default::default();
+/// let scan = HilbertScanCore::with_level_state_storage(levels, [1280, 720]);
+/// assert_eq!(scan.count(), 1280 * 720);
+/// ```
+pub const fn num_levels_for_size_u32(size: [u32; 2]) -> usize {
+    let min = if size[0] < size[1] { size[0] } else { size[1] };
+    if min <= 1 {
+        1
+    } else {
+        (u32::BITS - min.leading_zeros()) as usize
+    }
+}
+
+/// Get the number of [`LevelState`]s sufficient for any rectangle whose
+/// sides are not longer than `max_dim`. This is a `const fn` and can be used
+/// to specify the number of levels of [`HilbertScan`](crate::HilbertScan).
+///
+/// ```
+/// use zhang_hilbert::{levels_for_max_dim, HilbertScan};
+/// type Scan = HilbertScan<u16, { levels_for_max_dim(256) }>;
+/// assert_eq!(Scan::new_array([256, 200]).count(), 256 * 200);
+/// assert!(Scan::try_new_array([512, 512]).is_err());
+/// ```
+pub const fn levels_for_max_dim(max_dim: u32) -> usize {
+    num_levels_for_size_u32([max_dim, max_dim])
+}
+
+/// Get the number of [`LevelState`]s sufficient for any rectangle whose
+/// sides fit in an unsigned integer of `bits` bits. This is a `const fn` and
+/// can be used to specify the number of levels of
+/// [`HilbertScan`](crate::HilbertScan).
+///
+/// ```
+/// use zhang_hilbert::{max_levels_for_bits, HilbertScan};
+/// type Scan = HilbertScan<u16, { max_levels_for_bits(u16::BITS) }>;
+/// assert_eq!(Scan::new_array([u16::MAX, 3]).count(), 65535 * 3);
+/// ```
+pub const fn max_levels_for_bits(bits: u32) -> usize {
+    if bits <= 1 {
+        1
+    } else {
+        bits as usize
+    }
+}
+
+/// A `const fn` version of [`num_levels_for_size`] for `u64`.
+pub const fn num_levels_for_size_u64(size: [u64; 2]) -> usize {
+    let min = if size[0] < size[1] { size[0] } else { size[1] };
+    if min <= 1 {
+        1
+    } else {
+        (u64::BITS - min.leading_zeros()) as usize
+    }
+}
+
+/// Selects the curve-type selection rule for the last `T_B(E, O)` block in
+/// a `T_R(E, O)` rectangle.
+///
+/// See the "Differences from the original algorithm" section of the crate
+/// documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[doc(alias = "LastBlockRule")]
+pub enum CurveVariant {
+    /// Use the reversed type-1 basic pattern with a helper row, making the
+    /// scan leave the rectangle at the bottom-right corner. This is the
+    /// default.
+    #[default]
+    Modified,
+    /// Use the reversed type-2 basic pattern like the other `T_B(E, O)`
+    /// blocks, as described in the paper.
+    Original,
+}
+
+/// Selects the definition of the function that finds the split position of
+/// a side.
+///
+/// See the "Differences from the original algorithm" section of the crate
+/// documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DivisionRule {
+    /// A definition modified for efficient implementation, which differs from
+    /// the paper for the sides of the form `3⋅2ⁿ`. This is the default.
+    #[default]
+    Fast,
+    /// The definition given in the paper.
+    PaperExact,
+}
+
+impl DivisionRule {
+    /// Find the split position (l₁) of a side.
+    fn l1<T: ScanCoord>(self, size: T) -> T {
+        match self {
+            DivisionRule::Fast => division_l1(size),
+            DivisionRule::PaperExact => division_l1_paper(size),
+        }
+    }
+}
+
+/// Selects the axis along which the top level of [`HilbertScanCore`]
+/// progresses.
+///
+/// The scan of a rectangle of the size `[w, h]` with [`PrimaryAxis::Y`] is
+/// the transpose of the scan of `[h, w]` with [`PrimaryAxis::X`]. This is
+/// achieved by transposing the curve type of every level rather than the
+/// output points, so it doesn't add any cost per point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PrimaryAxis {
+    /// Progress along the X axis, leaving a rectangle having an even width
+    /// at the bottom-right corner. This is the default.
+    #[default]
+    X,
+    /// Progress along the Y axis, leaving a rectangle having an even height
+    /// at the top-left corner.
+    Y,
+}
+
+/// The rules selected at construction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct Rules {
+    pub(crate) variant: CurveVariant,
+    pub(crate) division: DivisionRule,
+    /// Whether the smallest blocks of size 3×3 or larger are divided once
+    /// more instead of being scanned by the basic pattern directly.
+    pub(crate) extra_subdivision: bool,
+    /// The minimum length of the sides of the smallest blocks that undergo
+    /// the extra subdivision. Always `3` or greater.
+    pub(crate) min_subdivision_size: u8,
+    pub(crate) primary_axis: PrimaryAxis,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            variant: CurveVariant::default(),
+            division: DivisionRule::default(),
+            extra_subdivision: true,
+            min_subdivision_size: 3,
+            primary_axis: PrimaryAxis::default(),
+        }
+    }
+}
+
+impl Rules {
+    /// Get the curve type of the top level, which is `1` (transposed) if
+    /// the primary axis is Y. This is also the index of the primary axis.
+    fn top_curve_type(&self) -> u8 {
+        self.primary_axis as u8
+    }
+
+    /// Check if a smallest block of the size `size` undergoes the extra
+    /// subdivision.
+    #[inline]
+    fn subdivides<T: ScanCoord>(&self, [w, h]: [T; 2]) -> bool {
+        let min = T::from_u8(self.min_subdivision_size);
+        self.extra_subdivision && w >= min && h >= min
+    }
+}
+
+/// Transpose `p` if `transpose` is set.
+#[inline]
+fn transpose_if<T>([x, y]: [T; 2], transpose: bool) -> [T; 2] {
+    if transpose {
+        [y, x]
+    } else {
+        [x, y]
+    }
+}
+
+/// The amount of [`LevelState`]s used by [`HilbertScanCore`], returned by
+/// [`storage_requirement`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StorageReq {
+    /// The number of levels the constructors of [`HilbertScanCore`] require,
+    /// which is equal to [`num_levels_for_size`]. A snapshot taken by
+    /// [`HilbertScanCore::save_state`] has this many levels.
+    pub sufficient: usize,
+    /// The number of levels the scan actually writes. The elements beyond
+    /// this count are never touched.
+    pub exact: usize,
+}
+
+/// Get the number of [`LevelState`]s used by [`HilbertScanCore`] to scan a
+/// rectangle of the size `size`.
+///
+/// The last level required by [`num_levels_for_size`] is used only if some
+/// of the smallest blocks are large enough to undergo the extra subdivision.
+/// This function finds whether that's the case without performing the scan.
+///
+/// The result is for the default [`CurveVariant`] and [`DivisionRule`]. The
+/// other rules may use the last level in more cases.
+///
+/// ```
+/// use zhang_hilbert::{storage_requirement, StorageReq};
+/// assert_eq!(
+///     storage_requirement([1920u32, 1080]),
+///     StorageReq { sufficient: 11, exact: 11 },
+/// );
+/// assert_eq!(
+///     storage_requirement([1024u32, 1024]),
+///     StorageReq { sufficient: 11, exact: 10 },
+/// );
+/// ```
+pub fn storage_requirement<T: ScanCoord>(size: [T; 2]) -> StorageReq {
+    storage_requirement_for_rules(size, Rules::default())
+}
+
+/// [`storage_requirement`] for arbitrary [`Rules`].
+fn storage_requirement_for_rules<T: ScanCoord>(size: [T; 2], rules: Rules) -> StorageReq {
+    let sufficient = num_levels_for_size(size);
+    let exact = if size[0] <= T::one() || size[1] <= T::one() {
+        // Scanned without subdivision
+        0
+    } else if uses_extra_level(size, sufficient, rules) {
+        sufficient
+    } else {
+        sufficient - 1
+    };
+    StorageReq { sufficient, exact }
+}
+
+/// The sizes of the blocks at a specific level along one axis.
+#[derive(Clone, Copy)]
+struct AxisBlocks<T> {
+    /// The size of the block nearest to the origin.
+    first: T,
+    /// The size of the block farthest from the origin.
+    last: T,
+    /// The distinct sizes of the other blocks.
+    middle: [Option<T>; 3],
+    /// Whether there's only one block.
+    single: bool,
+}
+
+impl<T: ScanCoord> AxisBlocks<T> {
+    fn new(size: T) -> Self {
+        Self {
+            first: size,
+            last: size,
+            middle: [None; 3],
+            single: true,
+        }
+    }
+
+    /// Divide every block into two.
+    fn subdivide(self, division: DivisionRule) -> Self {
+        let division_l1 = |x| division.l1(x);
+        let mut middle = [None; 3];
+        let mut add = |x: T| {
+            if !middle.contains(&Some(x)) {
+                // There are at most two distinct values in practice
+                *middle.iter_mut().find(|m| m.is_none()).unwrap() = Some(x);
+            }
+        };
+
+        // A block is divided into `x - l1` (near side) and `l1` (far side)
+        if !self.single {
+            add(division_l1(self.first));
+            add(self.last - division_l1(self.last));
+        }
+        for &x in self.middle.iter().flatten() {
+            add(division_l1(x));
+            add(x - division_l1(x));
+        }
+
+        Self {
+            first: self.first - division_l1(self.first),
+            last: division_l1(self.last),
+            middle,
+            single: false,
+        }
+    }
+
+    /// Enumerate the sizes with flags indicating whether they are the first
+    /// and the last block, respectively.
+    fn iter(self) -> impl Iterator<Item = (T, bool, bool)> {
+        let ends = if self.single {
+            [Some((self.first, true, true)), None]
+        } else {
+            [
+                Some((self.first, true, false)),
+                Some((self.last, false, true)),
+            ]
+        };
+        IntoIterator::into_iter(ends).flatten().chain(
+            IntoIterator::into_iter(self.middle)
+                .flatten()
+                .map(|x| (x, false, false)),
+        )
+    }
+}
+
+/// Find whether some bottom-level blocks undergo the extra subdivision,
+/// which uses the last level. This mirrors the decisions made by `reset` and
+/// `next_block`.
+fn uses_extra_level<T: ScanCoord>(size: [T; 2], num_levels: usize, rules: Rules) -> bool {
+    if !rules.extra_subdivision {
+        return false;
+    }
+    // The decisions below are made in the frame where X is primary
+    let size = transpose_if(size, rules.primary_axis == PrimaryAxis::Y);
+
+    let depth = num_levels - 2;
+    let [blocks_x, blocks_y] =
+        size.map(|x| (0..depth).fold(AxisBlocks::new(x), |b, _| b.subdivide(rules.division)));
+    let variant = rules.variant;
+    let odd = |x: T| (x & T::one()) != T::zero();
+
+    blocks_x.iter().any(|(w, first_x, last_x)| {
+        blocks_y.iter().any(|(h, first_y, _)| {
+            let [mut w, mut h] = [w, h];
+            if first_x && first_y {
+                // The first block may have a helper row, see `reset`
+                if odd(size[0]) {
+                    w = w - T::one();
+                } else if odd(size[1]) && (num_levels != 2 || variant == CurveVariant::Original) {
+                    h = h - T::one();
+                }
+            } else if variant == CurveVariant::Modified && last_x && first_y && !odd(w) && odd(h) {
+                // The last block may have a helper row, see `next_block`
+                h = h - T::one();
+            }
+            rules.subdivides([w, h])
+        })
+    })
+}
+
+fn log2_floor<T: ScanCoord>(x: T) -> u32 {
     T::zero().leading_zeros() - 1 - x.leading_zeros()
 }
 
+/// Convert a point or a size to a value that can be formatted by `{:?}`
+/// without requiring `T: Debug`.
+pub(crate) fn debug_point<T: ScanCoord>(p: [T; 2]) -> [u128; 2] {
+    p.map(|x| x.to_u128())
+}
+
+/// Check if the points of a rectangle of the size `size` fit in `T` when
+/// translated by `origin`.
+pub(crate) fn check_origin<T: ScanCoord>(size: [T; 2], origin: [T; 2]) -> Result<(), ScanError> {
+    if size[0] == T::zero() || size[1] == T::zero() {
+        return Ok(());
+    }
+    for i in 0..2 {
+        (size[i] - T::one())
+            .checked_add(origin[i])
+            .ok_or(ScanError::CoordinateOverflow)?;
+    }
+    Ok(())
+}
+
+/// Translate `p` by `origin`.
+#[inline]
+fn translate<T: ScanCoord>(p: [T; 2], origin: [T; 2]) -> [T; 2] {
+    [p[0] + origin[0], p[1] + origin[1]]
+}
+
+/// Get the number of points in a rectangle, saturating at `u64::MAX`.
+pub(crate) fn area<T: ScanCoord>(size: [T; 2]) -> u64 {
+    let [w, h] = size.map(|x| x.to_u64().unwrap_or(u64::MAX));
+    w.saturating_mul(h)
+}
+
+/// Convert the number of remaining points to a value returned by
+/// `Iterator::size_hint`.
+///
+/// `len` must be exact. This is true of the scans because the number of
+/// points is checked against `u64` on construction, so `u64::MAX` (e.g.,
+/// `[u64::MAX, 1]`) is a real count, not a saturated one.
+pub(crate) fn size_hint_for_len(len: u64) -> (usize, Option<usize>) {
+    match usize::try_from(len) {
+        Ok(len) => (len, Some(len)),
+        Err(_) => (usize::MAX, None),
+    }
+}
+
+/// Check if `size` is a square whose side is a power of two, for which
+/// [`HilbertScanCore`] uses the classic Hilbert curve algorithm.
+///
+/// The side is limited to `2³¹` so that point indices fit in `u64`.
+fn is_pow2_square<T: ScanCoord>(size: [T; 2]) -> bool {
+    size[0] == size[1]
+        && size[0] > T::one()
+        && size[0].count_ones() == 1
+        && size[0].trailing_zeros() < 32
+}
+
+/// Find the `index`-th point of the classic Hilbert curve filling a square of
+/// side `2ᵒʳᵈᵉʳ`. The curve is identical to the one produced by the generic
+/// algorithm for the same square.
+fn pow2_point<T: ScanCoord>(order: u32, index: u64) -> [T; 2] {
+    let [mut x, mut y] = [0u64, 0u64];
+    let mut t = index;
+    for i in 0..order {
+        let s = 1u64 << i;
+        let rx = (t >> 1) & 1;
+        let ry = (t ^ rx) & 1;
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        x += s * rx;
+        y += s * ry;
+        t >>= 2;
+    }
+    [T::from_u64(x).unwrap(), T::from_u64(y).unwrap()]
+}
+
+/// Append the range `[start, end)` to `out`, merging it with the last one if
+/// they are adjacent. The ranges must be appended in the ascending order.
+fn push_interval(out: &mut Vec<(u64, u64)>, start: u64, end: u64) {
+    match out.last_mut() {
+        Some(last) if last.1 == start => last.1 = end,
+        _ => out.push((start, end)),
+    }
+}
+
+/// Get the index of `point` in the classic Hilbert curve of the order
+/// `order`. This is the inverse of `pow2_point`.
+fn pow2_index<T: ScanCoord>(order: u32, point: [T; 2]) -> u64 {
+    let [mut x, mut y] = point.map(|v| v.to_u64().unwrap());
+    let mut index = 0;
+    for i in (0..order).rev() {
+        let s = 1u64 << i;
+        let rx = (x & s != 0) as u64;
+        let ry = (y & s != 0) as u64;
+        index += s * s * ((3 * rx) ^ ry);
+        x &= s - 1;
+        y &= s - 1;
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+    }
+    index
+}
+
+/// Get the curve type of the basic block containing the `index`-th point in
+/// the classic Hilbert curve of the order `order`. The generic algorithm
+/// divides such a square into 2×2 basic blocks, whose curve types are
+/// determined by the base-4 digits of `index` above the lowest one (see
+/// [`Pow2State`]).
+fn pow2_curve_type(order: u32, index: u64) -> u8 {
+    let (mut zeros_odd, mut threes_odd) = (false, false);
+    for i in 1..order {
+        let digit = (index >> (i * 2)) & 3;
+        zeros_odd ^= digit == 0;
+        threes_odd ^= digit == 3;
+    }
+    // The moves in the basic block are `(!axis, neg)`, `(axis, neg)`, and
+    // `(!axis, !neg)`, where `axis = zeros_odd ^ threes_odd` and
+    // `neg = threes_odd`
+    (zeros_odd ^ threes_odd) as u8 | ((threes_odd as u8) << 1)
+}
+
+/// The state of the classic Hilbert curve algorithm used by
+/// [`HilbertScanCore`] for squares whose side is a power of two.
+///
+/// The move from the `d`-th point to the next one happens within the
+/// sub-square corresponding to the lowest base-4 digit of `d` that is not
+/// `3`. Each digit `0` above it transposes the sub-square, and each digit `3`
+/// anti-transposes it. Since these two transformations are commutative
+/// involutions, only the parities of their counts matter, which can be
+/// updated incrementally.
+#[derive(Debug, Clone, Copy)]
+struct Pow2State {
+    /// `log2(size[0])`
+    order: u32,
+    /// Whether the index of `position` has an odd number of `0` digits.
+    zeros_odd: bool,
+    /// Whether the index of `position` has an odd number of `3` digits.
+    threes_odd: bool,
+}
+
+impl Pow2State {
+    fn new(order: u32, index: u64) -> Self {
+        let mut this = Self {
+            order,
+            zeros_odd: false,
+            threes_odd: false,
+        };
+        for i in 0..order {
+            let digit = (index >> (i * 2)) & 3;
+            this.zeros_odd ^= digit == 0;
+            this.threes_odd ^= digit == 3;
+        }
+        this
+    }
+
+    /// Find the direction from the `index`-th point to the next one, and
+    /// update `self` for the next point. Returns the axis and whether the
+    /// move is toward the negative direction.
+    fn step(&mut self, index: u64) -> (usize, bool) {
+        let level = index.trailing_ones() / 2;
+        let digit = (index >> (level * 2)) & 3;
+        let trailing_threes_odd = level & 1 != 0;
+
+        // The digits above `level`
+        let zeros_odd = self.zeros_odd ^ (digit == 0);
+        let threes_odd = self.threes_odd ^ trailing_threes_odd;
+
+        // The moves in the untransformed sub-square: `0 → 1` is `+y`,
+        // `1 → 2` is `+x`, and `2 → 3` is `-y`
+        let (axis, neg) = (digit != 1, digit == 2);
+
+        // Trailing `3`s become `0`s, and `digit` is incremented
+        self.zeros_odd = zeros_odd ^ trailing_threes_odd;
+        self.threes_odd = threes_odd ^ (digit == 2);
+
+        ((axis ^ zeros_odd ^ threes_odd) as usize, neg ^ threes_odd)
+    }
+}
+
 /// Find the split position (l₁) of a side.
-fn division_l1<T: PrimInt + Unsigned>(size: T) -> T {
+fn division_l1<T: ScanCoord>(size: T) -> T {
     let m = log2_floor(size) - 1;
 
-    let mask = T::one().unsigned_shl(m);
+    let mask = T::one() << m as usize;
     (size & mask) + mask
 }
 
+/// [`division_l1`] as defined in the paper. The result differs when `size` is
+/// of the form `3⋅2ⁿ`, where `division_l1` returns the larger candidate.
+fn division_l1_paper<T: ScanCoord>(size: T) -> T {
+    let m = log2_floor(size) - 1;
+
+    let mask = T::one() << m as usize;
+    if size > mask + mask + mask {
+        mask + mask
+    } else {
+        mask
+    }
+}
+
 /// Get the size of a extra-subdivided subblock.
 ///
 /// `curve_type` is the curve type of the block containing the extra-subdivided
 /// subblock. `pos` specifies a subblock within the block.
-fn extra_division_subblock_size<T: PrimInt + Unsigned + std::fmt::Debug>(
+///
+/// If `transposed` is set, the block belongs to a scan whose primary axis is
+/// Y, and the result is found in the transposed frame.
+fn extra_division_subblock_size<T: ScanCoord>(
     size: [T; 2],
     mut pos: u8,
     curve_type: u8,
+    transposed: bool,
 ) -> [T; 2] {
+    if transposed {
+        let pos = ((pos & 0b01) << 1) | ((pos >> 1) & 0b01);
+        let size = extra_division_subblock_size([size[1], size[0]], pos, curve_type ^ 1, false);
+        return [size[1], size[0]];
+    }
+
     // If the block is odd-sized (`T_B(O, _)` and/or `T_B(_, O)`), we must
     // be careful to make the subblocks' sizes compatible with their curve types.
     //
@@ -197,8 +1021,9 @@ fn extra_division_subblock_size<T: PrimInt + Unsigned + std::fmt::Debug>(
     //    | | | |  }l0
     //    '-' '-' /
     //
-    let three = T::from(3).unwrap();
-    let size_l1 = size.map(|x| (x + three) >> 2 << 1);
+    let three = T::from_u8(3);
+    // `(x + 3) >> 2 << 1` without overflowing
+    let size_l1 = size.map(|x| ((x >> 2) + (((x & three) + three) >> 2)) << 1);
     let size_l0 = [size[0] - size_l1[0], size[1] - size_l1[1]];
 
     pos ^= (curve_type == 0 || curve_type == 5) as u8;
@@ -230,13 +1055,30 @@ fn extra_division_subblock_size<T: PrimInt + Unsigned + std::fmt::Debug>(
 ///  - `y` is `0` if both of `size[0]` and `size[1]` are even numbers.
 ///  - `y` is `0` if `size[0]` is an even number.
 ///
-#[derive(Debug)]
+/// The last point can be calculated without performing the scan by
+/// [`end_point`](HilbertScanCore::end_point).
+///
+/// The above is for the default [`PrimaryAxis::X`]. With
+/// [`PrimaryAxis::Y`], the roles of the X and Y axes are swapped.
+///
+/// # Thread safety
+///
+/// `HilbertScanCore<T, LevelSt>` is `Send` (resp. `Sync`) if and only if
+/// both `T` and `LevelSt` are. This holds for all of the provided aliases
+/// such as [`HilbertScan32`](crate::HilbertScan32) and
+/// [`HilbertScanVec`](crate::HilbertScanVec). A scan borrowing
+/// `&mut [LevelState<T>]` is `Send` and `Sync` as well, but it can't
+/// outlive the borrowed storage.
+///
+#[derive(Clone)]
 pub struct HilbertScanCore<T, LevelSt> {
     size: [T; 2],
     num_levels: usize,
     /// `num_levels - 1` or `last_level - 2`
     last_level: usize,
     level_states: LevelSt,
+    /// The first point of the scan. `position` includes this offset.
+    origin: [T; 2],
     position: [T; 2],
 
     // ============ Basic (last-level block) scanning state =============
@@ -245,25 +1087,235 @@ pub struct HilbertScanCore<T, LevelSt> {
     bb_curve_type: u8,
     bb_end: u8,
     bb_helper_row: bool,
+    /// The size of the current basic block along the secondary axis of
+    /// `bb_curve_type`. Cached so that `next` doesn't have to access
+    /// `level_states` until it reaches the end of a column.
+    bb_sec_width: T,
+    /// The value of `remaining` when the cursor entered the current basic
+    /// block or was repositioned. Used by `current_curve_type`.
+    bb_enter_remaining: u64,
+    /// The curve type of the basic block containing the point produced
+    /// right before the cursor entered the current basic block, or `None` if
+    /// the cursor was repositioned without producing a point.
+    bb_prev_curve_type: Option<u8>,
+
+    /// The number of points yet to be produced.
+    remaining: u64,
+    done: bool,
+    rules: Rules,
+
+    /// Use the classic Hilbert curve algorithm because `size` is a square
+    /// whose side is a power of two (see [`is_pow2_square`]). In this case,
+    /// only `position`, `remaining`, `done`, and `pow2` are updated as the
+    /// scan progresses.
+    pow2: Option<Pow2State>,
+}
 
+/// The state of a [`HilbertScanCore`] except for its `LevelSt`.
+///
+/// This is produced by [`HilbertScanCore::into_raw_parts`] and consumed by
+/// [`HilbertScanCore::from_raw_parts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScanParts<T> {
+    size: [T; 2],
+    num_levels: usize,
+    last_level: usize,
+    origin: [T; 2],
+    position: [T; 2],
+    bb_progress: [T; 2],
+    bb_secondary_neg: bool,
+    bb_curve_type: u8,
+    bb_end: u8,
+    bb_helper_row: bool,
+    remaining: u64,
     done: bool,
+    rules: Rules,
 }
 
-impl<T, LevelSt> HilbertScanCore<T, LevelSt>
-where
-    LevelSt: BorrowMut<[LevelState<T>]>,
-    T: PrimInt + Unsigned + std::fmt::Debug,
-{
-    /// Construct a `HilbertScanCore` with a default-constructed `LevelSt`.
-    ///
-    /// **Warning**: As noted in the documentation of [`with_level_state_storage`],
-    /// the supplied `LevelSt` must have a certain number of elements. When
-    /// `LevelSt` is `Vec`, this function always panics because `Vec` is
+/// A snapshot of a [`HilbertScanCore`], including the level states used by
+/// the scan.
+///
+/// This is produced by [`HilbertScanCore::save_state`] and consumed by
+/// [`HilbertScanCore::restore`]. It can be converted to a compact byte
+/// sequence by [`to_bytes`](ScanSnapshot::to_bytes), and also supports
+/// `serde` if the `serde` feature is enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScanSnapshot<T> {
+    level_states: Vec<LevelState<T>>,
+    parts: ScanParts<T>,
+}
+
+/// The magic byte of an encoded `ScanSnapshot`.
+const SNAPSHOT_MAGIC: u8 = b'H';
+
+impl<T: ScanCoord> ScanSnapshot<T> {
+    /// Get the size of the rectangle being scanned.
+    pub(crate) fn size(&self) -> [T; 2] {
+        self.parts.size
+    }
+
+    /// Encode the snapshot into a byte sequence.
+    ///
+    /// ```
+    /// use zhang_hilbert::{HilbertScan32, ScanSnapshot};
+    /// let mut scan = HilbertScan32::new([11, 42]);
+    /// scan.nth(100);
+    ///
+    /// let bytes = scan.save_state().to_bytes();
+    /// let snapshot = ScanSnapshot::from_bytes(&bytes).unwrap();
+    /// let resumed = HilbertScan32::restore(&snapshot, Default::default());
+    /// assert!(resumed.eq(scan));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer::new(SNAPSHOT_MAGIC);
+        self.write(&mut w);
+        w.finish()
+    }
+
+    /// Decode a snapshot encoded by [`to_bytes`](ScanSnapshot::to_bytes).
+    ///
+    /// The checksum and the consistency of the decoded state are verified,
+    /// so a corrupted input is reported as an error.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut r = Reader::new(bytes, SNAPSHOT_MAGIC)?;
+        let this = Self::read(&mut r)?;
+        r.finish()?;
+        Ok(this)
+    }
+
+    pub(crate) fn write(&self, w: &mut Writer) {
+        let parts = &self.parts;
+        w.uint(parts.size[0]);
+        w.uint(parts.size[1]);
+        w.uint(parts.num_levels);
+        w.uint(parts.last_level);
+        w.uint(parts.origin[0]);
+        w.uint(parts.origin[1]);
+        w.uint(parts.position[0]);
+        w.uint(parts.position[1]);
+        w.uint(parts.bb_progress[0]);
+        w.uint(parts.bb_progress[1]);
+        w.bool(parts.bb_secondary_neg);
+        w.u8(parts.bb_curve_type);
+        w.u8(parts.bb_end);
+        w.bool(parts.bb_helper_row);
+        w.uint(parts.remaining);
+        w.bool(parts.done);
+        w.u8(parts.rules.variant as u8);
+        w.u8(parts.rules.division as u8);
+        w.bool(parts.rules.extra_subdivision);
+        w.u8(parts.rules.min_subdivision_size);
+        w.u8(parts.rules.primary_axis as u8);
+
+        for ls in self.level_states.iter() {
+            w.uint(ls.size[0]);
+            w.uint(ls.size[1]);
+            w.u8(ls.curve_type());
+            w.u8(ls.progress());
+        }
+    }
+
+    pub(crate) fn read(r: &mut Reader<'_>) -> Result<Self, SnapshotError> {
+        let parts = ScanParts {
+            size: [r.uint()?, r.uint()?],
+            num_levels: r.uint()?,
+            last_level: r.uint()?,
+            origin: [r.uint()?, r.uint()?],
+            position: [r.uint()?, r.uint()?],
+            bb_progress: [r.uint()?, r.uint()?],
+            bb_secondary_neg: r.bool()?,
+            bb_curve_type: r.u8()?,
+            bb_end: r.u8()?,
+            bb_helper_row: r.bool()?,
+            remaining: r.uint()?,
+            done: r.bool()?,
+            rules: Rules {
+                variant: match r.u8()? {
+                    0 => CurveVariant::Modified,
+                    1 => CurveVariant::Original,
+                    _ => return Err(SnapshotError::Invalid),
+                },
+                division: match r.u8()? {
+                    0 => DivisionRule::Fast,
+                    1 => DivisionRule::PaperExact,
+                    _ => return Err(SnapshotError::Invalid),
+                },
+                extra_subdivision: r.bool()?,
+                min_subdivision_size: match r.u8()? {
+                    x if x >= 3 => x,
+                    _ => return Err(SnapshotError::Invalid),
+                },
+                primary_axis: match r.u8()? {
+                    0 => PrimaryAxis::X,
+                    1 => PrimaryAxis::Y,
+                    _ => return Err(SnapshotError::Invalid),
+                },
+            },
+        };
+
+        // Validate `num_levels` before allocating anything
+        let size = parts.size;
+        let empty = size[0] == T::zero() || size[1] == T::zero();
+        ensure(parts.num_levels == num_levels_for_size(size))?;
+
+        let level_states = (0..parts.num_levels)
+            .map(|_| {
+                let size = [r.uint()?, r.uint()?];
+                let (curve_type, progress) = (r.u8()?, r.u8()?);
+                ensure(curve_type < 16 && progress < 16)?;
+                Ok(LevelState::new(size, curve_type, progress))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Check the invariants that the scan relies on to not panic
+        ensure(parts.remaining <= area(size))?;
+        ensure(parts.bb_curve_type < 8 && parts.bb_end < 4)?;
+        if empty {
+            ensure(parts.done && parts.remaining == 0)?;
+        } else {
+            ensure(check_origin(size, parts.origin).is_ok())?;
+            ensure((0..2).all(|i| {
+                parts.position[i] >= parts.origin[i]
+                    && parts.position[i] - parts.origin[i] < size[i]
+            }))?;
+        }
+        if parts.num_levels >= 2 {
+            let last_level = parts.last_level;
+            ensure(last_level + 2 == parts.num_levels || last_level + 1 == parts.num_levels)?;
+            // The levels after `last_level` may contain stale values
+            for ls in level_states[0..=last_level].iter() {
+                ensure(ls.size[0] <= size[0] && ls.size[1] <= size[1])?;
+                ensure(ls.curve_type() < 8 && ls.progress() <= 4)?;
+            }
+        } else {
+            ensure(parts.last_level == 0)?;
+        }
+
+        Ok(Self {
+            level_states,
+            parts,
+        })
+    }
+}
+
+impl<T, LevelSt> HilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    /// Construct a `HilbertScanCore` with a default-constructed `LevelSt`.
+    ///
+    /// **Warning**: As noted in the documentation of [`with_level_state_storage`],
+    /// the supplied `LevelSt` must have a certain number of elements. When
+    /// `LevelSt` is `Vec`, this function always panics because `Vec` is
     /// default-constructed to have zero elements. Rather, this function is
     /// useful when `LevelSt` has a predetermined number of elements like
-    /// `[LevelState<T>; 32]` does.
+    /// `[LevelState<T>; 32]` does. Use [`new_vec`] for `Vec`.
     ///
     /// [`with_level_state_storage`]: HilbertScanCore::with_level_state_storage
+    /// [`new_vec`]: HilbertScanCore::new_vec
     pub fn new(size: [T; 2]) -> Self
     where
         LevelSt: Default,
@@ -271,19 +1323,75 @@ where
         Self::with_level_state_storage(LevelSt::default(), size)
     }
 
+    /// Construct a `HilbertScanCore` for a rectangle whose dimensions are
+    /// known to be non-zero, with a default-constructed `LevelSt`.
+    ///
+    /// Unlike [`new`], this doesn't have to handle an empty rectangle. This is
+    /// the panic-free path for [`HilbertScan32`] and `NonZeroU32`: a `u32`
+    /// rectangle never needs more than 32 levels, and its area always fits in
+    /// `u64`.
+    ///
+    /// ```
+    /// use std::num::NonZeroU32;
+    /// use zhang_hilbert::HilbertScan32;
+    /// let size = [NonZeroU32::new(3).unwrap(), NonZeroU32::new(2).unwrap()];
+    /// let points: Vec<_> = HilbertScan32::new_nonzero(size).collect();
+    /// assert_eq!(points, HilbertScan32::new([3, 2]).collect::<Vec<_>>());
+    ///
+    /// // The plain constructor tolerates zero, yielding nothing
+    /// assert_eq!(HilbertScan32::new([0, 2]).next(), None);
+    /// ```
+    ///
+    /// `NonZeroU64` works with a 64-bit `LevelSt`:
+    ///
+    /// ```
+    /// use std::num::NonZeroU64;
+    /// use zhang_hilbert::{HilbertScanCore, LevelState};
+    /// let size = [NonZeroU64::new(1 << 40).unwrap(), NonZeroU64::new(5).unwrap()];
+    /// let mut scan = HilbertScanCore::<u64, [LevelState<u64>; 32]>::new_nonzero(size);
+    /// assert_eq!(scan.next(), Some([0, 0]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `LevelSt` is too small or the area of the rectangle doesn't
+    /// fit in `u64`.
+    ///
+    /// [`new`]: HilbertScanCore::new
+    /// [`HilbertScan32`]: crate::HilbertScan32
+    pub fn new_nonzero<N>(size: [N; 2]) -> Self
+    where
+        N: NonZeroCoord<Int = T>,
+        LevelSt: Default,
+    {
+        let size = size.map(N::get);
+        let level_states = LevelSt::default();
+        check_nonzero_scan_size(level_states.borrow(), size).unwrap_or_else(|e| panic!("{}", e));
+        let mut this = Self::empty(level_states, size);
+        this.reset();
+        this
+    }
+
     fn empty(level_states: LevelSt, size: [T; 2]) -> Self {
         Self {
             size,
             num_levels: 1,
             last_level: 0,
             level_states,
+            origin: [T::zero(), T::zero()],
             position: [T::zero(), T::zero()],
             bb_progress: [T::zero(), T::zero()],
             bb_secondary_neg: false,
             bb_curve_type: 0,
             bb_end: 0,
             bb_helper_row: false,
+            bb_sec_width: T::zero(),
+            bb_enter_remaining: 0,
+            bb_prev_curve_type: None,
+            remaining: 0,
             done: true,
+            rules: Rules::default(),
+            pow2: None,
         }
     }
 
@@ -294,57 +1402,456 @@ where
     /// to `size` and it can be calculated using `num_levels_for_size`.
     /// The elements do not have to be initialized as they are overwritten
     /// by this function.
-    pub fn with_level_state_storage(mut level_states: LevelSt, size: [T; 2]) -> Self {
+    ///
+    /// If either dimension of `size` is zero, the iterator yields nothing
+    /// and `level_states` may be empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_level_state_storage`]
+    /// returns an error.
+    ///
+    /// [`try_with_level_state_storage`]: HilbertScanCore::try_with_level_state_storage
+    pub fn with_level_state_storage(level_states: LevelSt, size: [T; 2]) -> Self {
+        Self::try_with_level_state_storage(level_states, size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt`, returning an
+    /// error instead of panicking if `level_states` is too small or `size`
+    /// is not supported.
+    ///
+    /// ```
+    /// use zhang_hilbert::{HilbertScanCore, LevelState, ScanError};
+    /// let result = HilbertScanCore::try_with_level_state_storage(
+    ///     vec![LevelState::default(); 3],
+    ///     [32u32, 32],
+    /// );
+    /// assert_eq!(
+    ///     result.unwrap_err(),
+    ///     ScanError::StorageTooSmall { required: 6, provided: 3 },
+    /// );
+    /// ```
+    pub fn try_with_level_state_storage(
+        level_states: LevelSt,
+        size: [T; 2],
+    ) -> Result<Self, ScanError> {
+        Self::try_with_rules(level_states, size, Rules::default(), [T::zero(); 2])
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt` and
+    /// [`CurveVariant`].
+    ///
+    /// ```
+    /// use zhang_hilbert::{CurveVariant, HilbertScanCore, LevelState};
+    /// let scan = HilbertScanCore::with_variant(
+    ///     [LevelState::default(); 32],
+    ///     [4u32, 3],
+    ///     CurveVariant::Original,
+    /// );
+    /// // The scan leaves the rectangle at the top-right corner as described
+    /// // in the paper
+    /// assert_eq!(scan.last(), Some([3, 2]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_variant`] returns an
+    /// error.
+    ///
+    /// [`try_with_variant`]: HilbertScanCore::try_with_variant
+    pub fn with_variant(level_states: LevelSt, size: [T; 2], variant: CurveVariant) -> Self {
+        Self::try_with_variant(level_states, size, variant).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt` and
+    /// [`CurveVariant`], returning an error instead of panicking if
+    /// `level_states` is too small or `size` is not supported.
+    pub fn try_with_variant(
+        level_states: LevelSt,
+        size: [T; 2],
+        variant: CurveVariant,
+    ) -> Result<Self, ScanError> {
+        let rules = Rules {
+            variant,
+            ..Rules::default()
+        };
+        Self::try_with_rules(level_states, size, rules, [T::zero(); 2])
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt` and
+    /// [`DivisionRule`].
+    ///
+    /// ```
+    /// use zhang_hilbert::{DivisionRule, HilbertScanCore, LevelState};
+    /// let scan = HilbertScanCore::with_division_rule(
+    ///     [LevelState::default(); 32],
+    ///     [24u32, 24],
+    ///     DivisionRule::PaperExact,
+    /// );
+    /// assert_eq!(scan.count(), 24 * 24);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_division_rule`] returns
+    /// an error.
+    ///
+    /// [`try_with_division_rule`]: HilbertScanCore::try_with_division_rule
+    pub fn with_division_rule(level_states: LevelSt, size: [T; 2], division: DivisionRule) -> Self {
+        Self::try_with_division_rule(level_states, size, division)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt` and
+    /// [`DivisionRule`], returning an error instead of panicking if
+    /// `level_states` is too small or `size` is not supported.
+    pub fn try_with_division_rule(
+        level_states: LevelSt,
+        size: [T; 2],
+        division: DivisionRule,
+    ) -> Result<Self, ScanError> {
+        let rules = Rules {
+            division,
+            ..Rules::default()
+        };
+        Self::try_with_rules(level_states, size, rules, [T::zero(); 2])
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt`, optionally
+    /// disabling the extra subdivision.
+    ///
+    /// By default, the smallest blocks produced by the algorithm are divided
+    /// once more if both of their sides are `3` or longer, which improves the
+    /// locality. If `extra_subdivision` is `false`, such blocks are scanned
+    /// by the basic scanning pattern (a zigzag) instead. The output is still
+    /// a valid scan.
+    ///
+    /// ```
+    /// use zhang_hilbert::{HilbertScanCore, LevelState};
+    /// let scan = HilbertScanCore::with_extra_subdivision(
+    ///     [LevelState::default(); 32],
+    ///     [6u32, 6],
+    ///     false,
+    /// );
+    /// assert_eq!(scan.count(), 36);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_extra_subdivision`]
+    /// returns an error.
+    ///
+    /// [`try_with_extra_subdivision`]: HilbertScanCore::try_with_extra_subdivision
+    pub fn with_extra_subdivision(
+        level_states: LevelSt,
+        size: [T; 2],
+        extra_subdivision: bool,
+    ) -> Self {
+        Self::try_with_extra_subdivision(level_states, size, extra_subdivision)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt`, optionally
+    /// disabling the extra subdivision, returning an error instead of
+    /// panicking if `level_states` is too small or `size` is not supported.
+    pub fn try_with_extra_subdivision(
+        level_states: LevelSt,
+        size: [T; 2],
+        extra_subdivision: bool,
+    ) -> Result<Self, ScanError> {
+        let rules = Rules {
+            extra_subdivision,
+            ..Rules::default()
+        };
+        Self::try_with_rules(level_states, size, rules, [T::zero(); 2])
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt` and the
+    /// minimum side length of the blocks that undergo the extra subdivision.
+    ///
+    /// The smallest blocks are divided once more only if both of their sides
+    /// are `min_subdivision_size` or longer. The default is `3`, which is
+    /// also the lower bound; smaller values are treated as `3`. Raising it
+    /// trades the locality for the iteration speed. The shorter side of the
+    /// smallest blocks is never longer than `4`, so `5` or a greater value has
+    /// the same effect as disabling the extra subdivision by
+    /// [`with_extra_subdivision`](HilbertScanCore::with_extra_subdivision).
+    ///
+    /// ```
+    /// use zhang_hilbert::{HilbertScan32, HilbertScanCore, LevelState};
+    /// let scan = HilbertScanCore::with_min_subdivision_size(
+    ///     [LevelState::default(); 32],
+    ///     [7u32, 7],
+    ///     4,
+    /// );
+    /// assert_eq!(scan.min_subdivision_size(), 4);
+    /// assert_eq!(scan.count(), 49);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_min_subdivision_size`]
+    /// returns an error.
+    ///
+    /// [`try_with_min_subdivision_size`]: HilbertScanCore::try_with_min_subdivision_size
+    pub fn with_min_subdivision_size(
+        level_states: LevelSt,
+        size: [T; 2],
+        min_subdivision_size: T,
+    ) -> Self {
+        Self::try_with_min_subdivision_size(level_states, size, min_subdivision_size)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt` and the
+    /// minimum side length of the blocks that undergo the extra subdivision,
+    /// returning an error instead of panicking if `level_states` is too small
+    /// or `size` is not supported.
+    pub fn try_with_min_subdivision_size(
+        level_states: LevelSt,
+        size: [T; 2],
+        min_subdivision_size: T,
+    ) -> Result<Self, ScanError> {
+        // Saturating doesn't change the output because the shorter side of
+        // the smallest blocks is never longer than `4`
+        let min_subdivision_size = min_subdivision_size
+            .max(T::from_u8(3))
+            .min(T::from_u8(u8::MAX))
+            .to_u128() as u8;
+        let rules = Rules {
+            min_subdivision_size,
+            ..Rules::default()
+        };
+        Self::try_with_rules(level_states, size, rules, [T::zero(); 2])
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt` and
+    /// [`PrimaryAxis`].
+    ///
+    /// ```
+    /// use zhang_hilbert::{HilbertScanCore, LevelState, PrimaryAxis};
+    /// let scan = HilbertScanCore::with_primary_axis(
+    ///     [LevelState::default(); 32],
+    ///     [6u32, 4],
+    ///     PrimaryAxis::Y,
+    /// );
+    /// // The scan leaves the rectangle at the top-left corner
+    /// assert_eq!(scan.last(), Some([0, 3]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_primary_axis`] returns
+    /// an error.
+    ///
+    /// [`try_with_primary_axis`]: HilbertScanCore::try_with_primary_axis
+    pub fn with_primary_axis(
+        level_states: LevelSt,
+        size: [T; 2],
+        primary_axis: PrimaryAxis,
+    ) -> Self {
+        Self::try_with_primary_axis(level_states, size, primary_axis)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt` and
+    /// [`PrimaryAxis`], returning an error instead of panicking if
+    /// `level_states` is too small or `size` is not supported.
+    pub fn try_with_primary_axis(
+        level_states: LevelSt,
+        size: [T; 2],
+        primary_axis: PrimaryAxis,
+    ) -> Result<Self, ScanError> {
+        let rules = Rules {
+            primary_axis,
+            ..Rules::default()
+        };
+        Self::try_with_rules(level_states, size, rules, [T::zero(); 2])
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt`, translating
+    /// the points by `origin`.
+    ///
+    /// The translation is applied to the cursor when the scan starts, so it
+    /// doesn't add any cost per point. [`rank`](HilbertScanCore::rank)
+    /// accepts translated points as well.
+    ///
+    /// ```
+    /// use zhang_hilbert::{HilbertScan32, HilbertScanCore, LevelState};
+    /// let scan = HilbertScanCore::with_origin([LevelState::default(); 32], [6u32, 7], [100, 20]);
+    /// assert!(scan.eq(HilbertScan32::new([6, 7]).map(|[x, y]| [x + 100, y + 20])));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_origin`] returns an
+    /// error.
+    ///
+    /// [`try_with_origin`]: HilbertScanCore::try_with_origin
+    pub fn with_origin(level_states: LevelSt, size: [T; 2], origin: [T; 2]) -> Self {
+        Self::try_with_origin(level_states, size, origin).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `HilbertScanCore` with an explicit `LevelSt`, translating
+    /// the points by `origin`, returning an error instead of panicking if
+    /// `level_states` is too small, `size` is not supported, or some
+    /// translated point doesn't fit in `T`
+    /// ([`ScanError::CoordinateOverflow`]).
+    pub fn try_with_origin(
+        level_states: LevelSt,
+        size: [T; 2],
+        origin: [T; 2],
+    ) -> Result<Self, ScanError> {
+        Self::try_with_rules(level_states, size, Rules::default(), origin)
+    }
+
+    /// Construct a `HilbertScanCore` with the given rules, translating the
+    /// points by `origin`. This is what [`ScanBuilder`] uses.
+    ///
+    /// [`ScanBuilder`]: crate::ScanBuilder
+    pub(crate) fn try_with_rules(
+        level_states: LevelSt,
+        size: [T; 2],
+        rules: Rules,
+        origin: [T; 2],
+    ) -> Result<Self, ScanError> {
+        check_scan_size(level_states.borrow(), size)?;
+        check_origin(size, origin)?;
+        let mut this = Self::empty(level_states, size);
+        this.rules = rules;
+        this.origin = origin;
+        this.reset();
+        Ok(this)
+    }
+
+    /// Get the rules the scan was constructed with.
+    pub(crate) fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// Get the [`CurveVariant`] used by the scan.
+    pub fn variant(&self) -> CurveVariant {
+        self.rules.variant
+    }
+
+    /// Get the [`DivisionRule`] used by the scan.
+    pub fn division_rule(&self) -> DivisionRule {
+        self.rules.division
+    }
+
+    /// Get the offset added to every point. See
+    /// [`with_origin`](HilbertScanCore::with_origin).
+    pub fn origin(&self) -> [T; 2] {
+        self.origin
+    }
+
+    /// Get the [`PrimaryAxis`] used by the scan.
+    pub fn primary_axis(&self) -> PrimaryAxis {
+        self.rules.primary_axis
+    }
+
+    /// Get whether the scan performs the extra subdivision. See
+    /// [`with_extra_subdivision`](HilbertScanCore::with_extra_subdivision).
+    pub fn extra_subdivision(&self) -> bool {
+        self.rules.extra_subdivision
+    }
+
+    /// Get the minimum side length of the blocks that undergo the extra
+    /// subdivision. See
+    /// [`with_min_subdivision_size`](HilbertScanCore::with_min_subdivision_size).
+    pub fn min_subdivision_size(&self) -> T {
+        T::from_u8(self.rules.min_subdivision_size)
+    }
+
+    /// Rewind the iterator to the beginning of the scan.
+    ///
+    /// The internal state is re-initialized in place, reusing the existing
+    /// `LevelSt`. This is cheaper than constructing a new iterator when the
+    /// same scan is performed repeatedly.
+    pub fn reset(&mut self) {
+        self.reset_generic();
+        if is_pow2_square(self.size) {
+            self.pow2 = Some(Pow2State::new(self.size[0].trailing_zeros(), 0));
+        }
+        self.mark_repositioned();
+    }
+
+    /// Record that the cursor was moved without producing a point.
+    fn mark_repositioned(&mut self) {
+        self.bb_enter_remaining = self.remaining;
+        self.bb_prev_curve_type = None;
+    }
+
+    /// The part of `reset` that sets up the state of the generic algorithm.
+    /// `pow2` is left `None`.
+    fn reset_generic(&mut self) {
+        let size = self.size;
+
+        self.num_levels = 1;
+        self.last_level = 0;
+        self.position = self.origin;
+        self.bb_progress = [T::zero(), T::zero()];
+        self.bb_secondary_neg = false;
+        self.bb_curve_type = 0;
+        self.bb_end = 0;
+        self.bb_helper_row = false;
+        self.bb_sec_width = T::zero();
+        self.remaining = 0;
+        self.done = true;
+        self.pow2 = None;
+
         if size[0] == T::zero() || size[1] == T::zero() {
-            return Self::empty(level_states, size);
+            return;
         }
 
+        self.remaining = area(size);
+        self.done = false;
+
         if size[0] == T::one() {
-            return Self {
-                done: false,
-                bb_progress: [T::one(), size[1]],
-                bb_curve_type: 0,
-                ..Self::empty(level_states, size)
-            };
+            self.bb_progress = [T::one(), size[1]];
+            self.bb_curve_type = 0;
+            self.bb_sec_width = size[1];
+            return;
         }
 
         if size[1] == T::one() {
-            return Self {
-                done: false,
-                bb_progress: [T::one(), size[0]],
-                bb_curve_type: 1,
-                ..Self::empty(level_states, size)
-            };
+            self.bb_progress = [T::one(), size[0]];
+            self.bb_curve_type = 1;
+            self.bb_sec_width = size[0];
+            return;
         }
 
         let num_levels = num_levels_for_size(size);
+        let division = self.rules.division;
+        let top = self.rules.top_curve_type();
+        let transposed = top != 0;
         let mut last_level;
         let (bb_curve_type, bb_helper_row, bb_progress);
         {
-            let level_states = &mut level_states.borrow_mut()[0..num_levels];
-            level_states[0] = LevelState {
-                size,
-                curve_type: 0, // γ(0) = 1
-                progress: 0,
-            };
+            let level_states = &mut self.level_states.borrow_mut()[0..num_levels];
+            level_states[0] = LevelState::new(size, top, 0); // γ(0) = 1
             for i in 1..=num_levels - 2 {
                 let prev = level_states[i - 1];
-                level_states[i] = LevelState {
-                    size: prev.size.map(|x| x - division_l1(x)),
-                    curve_type: (i % 2) as u8, // CURVE_INDUCTION_TABLE[prev.curve_type as usize][0],
-                    progress: 0,
-                };
+                level_states[i] = LevelState::new(
+                    prev.size.map(|x| x - division.l1(x)),
+                    (i % 2) as u8 ^ top, // CURVE_INDUCTION_TABLE[prev.curve_type() as usize][0],
+                    0,
+                );
             }
             last_level = num_levels - 2;
 
-            // Set up the scan of the first block
+            // Set up the scan of the first block. The parities are examined
+            // in the frame where X is primary, and the chosen curve type is
+            // transposed back.
             let last_curve_type = last_level % 2;
-            let (curve_type, helper) = match size.map(|x| (x & T::one()).to_u8().unwrap()) {
+            let parities = size.map(|x| (x & T::one()).to_u8().unwrap());
+            let (curve_type, helper) = match transpose_if(parities, transposed) {
                 // T_R(E, E)
                 [0, 0] => (last_curve_type as u8, false),
                 [0, 1] => {
-                    if num_levels == 2 {
+                    if num_levels == 2 && self.rules.variant == CurveVariant::Modified {
                         // T_R(E, O) (first & last) - Type-0 basic pattern
+                        // (This is a deviation from the original algorithm)
                         (0, false)
                     } else {
                         // T_R(E, O) - Type-1 basic pattern + helper row
@@ -361,6 +1868,7 @@ where
                 [1, 0] | [1, 1] => (0, true),
                 [_, _] => unreachable!(),
             };
+            let curve_type = curve_type ^ top;
 
             if helper {
                 // The helper row is specially handled, so exclude it from
@@ -372,23 +1880,37 @@ where
 
             let mut last_size = level_states[last_level].size;
 
-            level_states[last_level].curve_type = curve_type;
+            level_states[last_level].set_curve_type(curve_type);
+
+            trace!(
+                "entering block at [0, 0]: size={:?} curve_type={} entry=0b00 helper_row={}",
+                debug_point(last_size),
+                curve_type,
+                helper
+            );
 
             // Try the extra-subdivision on the first block.
-            let three = T::from(3u8).unwrap();
-            if last_size[0] >= three && last_size[1] >= three {
+            if self.rules.subdivides(last_size) {
                 // If the block is large enough, we can (and should) do the extra
                 // subdivision.
-                level_states[last_level].progress = 0;
+                level_states[last_level].set_progress(0);
 
-                last_size = extra_division_subblock_size(last_size, 0b00, curve_type);
+                last_size = extra_division_subblock_size(last_size, 0b00, curve_type, transposed);
                 bb_curve_type = CURVE_INDUCTION_TABLE[curve_type as usize][0];
 
+                trace!(
+                    "extra subdivision: first sub-block size={:?}",
+                    debug_point(last_size)
+                );
+
                 last_level += 1;
+                debug_assert!(last_level < storage_requirement_for_rules(size, self.rules).exact);
                 level_states[last_level].size = last_size;
             } else {
                 // Otherwise, apply the basic scanning pattern on this block.
                 bb_curve_type = curve_type;
+
+                trace!("no extra subdivision");
             }
 
             bb_helper_row = helper;
@@ -399,96 +1921,835 @@ where
             };
         }
 
-        let bb_secondary_neg = curve_secondary_negative_at_start(bb_curve_type) != 0;
-        let bb_end = curve_end_point(bb_curve_type);
+        self.num_levels = num_levels;
+        self.last_level = last_level;
+        self.bb_progress = bb_progress;
+        self.bb_secondary_neg = curve_secondary_negative_at_start(bb_curve_type) != 0;
+        self.bb_curve_type = bb_curve_type;
+        self.bb_end = curve_end_point(bb_curve_type);
+        self.bb_helper_row = bb_helper_row;
+        self.bb_sec_width = bb_progress[1];
+    }
 
-        Self {
-            size,
-            num_levels,
-            last_level,
-            level_states,
-            position: [T::zero(), T::zero()],
-            bb_progress,
-            bb_secondary_neg,
-            bb_curve_type,
-            bb_end,
-            bb_helper_row,
-            done: false,
+    /// Re-initialize the iterator to scan a rectangle of a different size,
+    /// reusing the existing `LevelSt`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice borrowed by `LevelSt` has fewer elements than
+    /// required by [`num_levels_for_size`]`(size)`, the rectangle has more
+    /// points than `u64` can count, or the translated points don't fit in `T`
+    /// (see [`with_origin`](HilbertScanCore::with_origin)).
+    pub fn set_size(&mut self, size: [T; 2]) {
+        check_scan_size(self.level_states.borrow(), size).unwrap_or_else(|e| panic!("{}", e));
+        check_origin(size, self.origin).unwrap_or_else(|e| panic!("{}", e));
+
+        self.size = size;
+        self.reset();
+    }
+
+    /// Map the points to the centers of the corresponding cells in the unit
+    /// square `[0, 1]²`, i.e., `[x, y]` to `[(x + 0.5) / width, (y + 0.5) /
+    /// height]`.
+    pub fn normalized<F: FloatCore>(self) -> Normalized<Self, F> {
+        let size = self.size;
+        Normalized::new(self, size)
+    }
+
+    /// Translate the points so that the rectangle is centered on the
+    /// origin. See [`Centered`] for how odd and even sizes are handled.
+    pub fn centered<S: PrimInt + Signed>(self) -> Centered<Self, S> {
+        let size = self.size;
+        Centered::new(self, size)
+    }
+
+    /// Translate the points by `origin`, producing signed coordinates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some coordinate, before or after the translation, doesn't
+    /// fit in `S`. See
+    /// [`Self::try_translated`] for a non-panicking version.
+    pub fn translated<S: PrimInt + Signed>(self, origin: [S; 2]) -> Translated<Self, S> {
+        self.try_translated(origin)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Translate the points by `origin`, producing signed coordinates.
+    /// Returns [`ScanError::CoordinateOverflow`] if some coordinate, before or
+    /// after the translation, doesn't fit in `S`.
+    pub fn try_translated<S: PrimInt + Signed>(
+        self,
+        origin: [S; 2],
+    ) -> Result<Translated<Self, S>, ScanError> {
+        let size = self.size;
+        Translated::new(self, size, origin)
+    }
+
+    /// Convert the coordinates to a wider type `O`. See [`Widened`].
+    pub fn widened<O>(self) -> Widened<Self, O>
+    where
+        T: Into<O>,
+    {
+        Widened::new(self)
+    }
+
+    /// Yield the points as `mint::Point2`s instead of arrays. Requires the
+    /// `mint` feature.
+    #[cfg(feature = "mint")]
+    pub fn points_mint(self) -> crate::adapter::MintPoints<Self> {
+        crate::adapter::MintPoints::new(self)
+    }
+
+    /// Wrap the scan in a `futures::Stream`. See
+    /// [`ScanStream`](crate::ScanStream). Requires the `futures` feature.
+    #[cfg(feature = "futures")]
+    pub fn to_stream(self) -> crate::adapter::ScanStream<Self> {
+        crate::adapter::ScanStream::new(self)
+    }
+
+    /// Apply a [`Transform`] to the points, e.g., to flip or rotate the
+    /// scan. See [`Transformed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the transformed rectangle, placed at the origin of the scan,
+    /// has a coordinate that doesn't fit in `T`.
+    pub fn transformed(self, transform: Transform) -> Transformed<Self, T> {
+        let (size, origin) = (self.size, self.origin);
+        Transformed::new(self, transform, size, origin)
+    }
+
+    /// Yield the points as tuples `(x, y)` instead of arrays.
+    pub fn tuples(self) -> Tuples<Self> {
+        Tuples::new(self)
+    }
+
+    /// Get the first point of the scan of the given size.
+    ///
+    /// The scan always starts at the origin. Returns `None` if the rectangle
+    /// is empty.
+    pub fn start_point(size: [T; 2]) -> Option<[T; 2]> {
+        if size[0] == T::zero() || size[1] == T::zero() {
+            None
+        } else {
+            Some([T::zero(), T::zero()])
+        }
+    }
+
+    /// Get the last point of the scan of the given size without performing
+    /// the scan. Returns `None` if the rectangle is empty.
+    ///
+    /// The result is for [`CurveVariant::Modified`].
+    ///
+    /// See the "Output properties" section of [`HilbertScanCore`].
+    pub fn end_point(size: [T; 2]) -> Option<[T; 2]> {
+        Self::end_point_with_variant(size, CurveVariant::Modified)
+    }
+
+    /// Get the last point of the scan of the given size and [`CurveVariant`]
+    /// without performing the scan. Returns `None` if the rectangle is empty.
+    pub fn end_point_with_variant(size: [T; 2], variant: CurveVariant) -> Option<[T; 2]> {
+        let rules = Rules {
+            variant,
+            ..Rules::default()
+        };
+        Self::end_point_with_rules(size, rules)
+    }
+
+    pub(crate) fn end_point_with_rules(size: [T; 2], rules: Rules) -> Option<[T; 2]> {
+        if rules.primary_axis == PrimaryAxis::Y {
+            let rules = Rules {
+                primary_axis: PrimaryAxis::X,
+                ..rules
+            };
+            return Self::end_point_with_rules([size[1], size[0]], rules).map(|[x, y]| [y, x]);
         }
+
+        let [w, h] = size;
+        if w == T::zero() || h == T::zero() {
+            return None;
+        }
+
+        let last_x = w - T::one();
+        if w == T::one() {
+            return Some([last_x, h - T::one()]);
+        }
+
+        let num_levels = num_levels_for_size(size);
+        let [w_odd, h_odd] = size.map(|x| (x & T::one()) != T::zero());
+
+        // The height of the bottom-right block
+        let last_h = || {
+            let mut last_h = h;
+            for _ in 1..num_levels.saturating_sub(1) {
+                last_h = last_h - rules.division.l1(last_h);
+            }
+            last_h
+        };
+
+        let y = if rules.variant == CurveVariant::Original && h_odd && (!w_odd || num_levels > 2) {
+            // The last block is `T_B(E, O)` (reversed type-2 as in the
+            // paper), which ends at the top-right corner
+            last_h() - T::one()
+        } else if !w_odd {
+            // The last block is `T_B(E, _)`, which ends at the bottom-right
+            // corner
+            T::zero()
+        } else if num_levels <= 2 {
+            // A single `T_B(O, _)` block + helper row, which ends at the
+            // top-right corner
+            h - T::one()
+        } else if h_odd {
+            // The last block is `T_B(E, O)` (reversed type-1 + helper row)
+            T::zero()
+        } else {
+            // The last block is the bottom-right block, which has an even
+            // width and height. The scan leaves it at either the bottom-right
+            // or the top-right corner, which have different checkerboard
+            // colors. Since every step alternates the color, the exit is the
+            // one with the same color as the `w * h - 1`-th point, which is
+            // the top-right corner because `w - 1` is even and `w * h - 1`
+            // is odd.
+            last_h() - T::one()
+        };
+
+        Some([last_x, y])
+    }
+
+    /// Get the number of points yet to be produced.
+    ///
+    /// Unlike [`Iterator::size_hint`] and [`Iterator::count`], this doesn't
+    /// depend on the width of `usize`.
+    ///
+    /// ```
+    /// use zhang_hilbert::HilbertScan32;
+    /// let mut scan = HilbertScan32::new([100_000, 100_000]);
+    /// scan.nth(41);
+    /// assert_eq!(scan.remaining_len(), 10_000_000_000 - 42);
+    /// ```
+    pub fn remaining_len(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Get the curve type of the basic block containing the point most
+    /// recently produced by the iterator.
+    ///
+    /// Basic blocks are the smallest blocks of the algorithm. Each of them is
+    /// scanned in one of the following manners (with the Y axis pointing
+    /// upward), which is identified by a curve type:
+    ///
+    /// ```text
+    ///   ,--,  ,--,   <----,   ^  ,--,  |   ,-----
+    ///   |  |  |  |   ,----'   |  |  |  |   '-----,
+    ///   |  |  |  |   '----,   |  |  |  |   ,-----'
+    ///   |  '--'  v   -----'   '--'  '--'   '---->
+    ///
+    ///     Type 0     Type 1     Type 2     Type 3
+    /// ```
+    ///
+    /// The curve types `4`–`7` represent the types `0`–`3` traversed in the
+    /// opposite direction. The lowest bit indicates the axis along which the
+    /// scan advances column by column (X = `0`, Y = `1`).
+    ///
+    /// If no point has been produced since the iterator was constructed or
+    /// repositioned by [`reset`], [`rank`], or [`restore`], this returns the
+    /// curve type of the basic block containing the next point.
+    ///
+    /// ```
+    /// use zhang_hilbert::HilbertScan32;
+    /// let mut scan = HilbertScan32::new([2, 2]);
+    /// assert_eq!(scan.next(), Some([0, 0]));
+    /// assert_eq!(scan.current_curve_type(), 0);
+    /// ```
+    ///
+    /// [`reset`]: HilbertScanCore::reset
+    /// [`rank`]: HilbertScanCore::rank
+    /// [`restore`]: HilbertScanCore::restore
+    pub fn current_curve_type(&self) -> u8 {
+        let entered = self.remaining == self.bb_enter_remaining;
+        if let Some(pow2) = &self.pow2 {
+            let next_index = area(self.size) - self.remaining;
+            let index = if entered { next_index } else { next_index - 1 };
+            return pow2_curve_type(pow2.order, index) ^ self.rules.top_curve_type();
+        }
+        match self.bb_prev_curve_type {
+            Some(curve_type) if entered => curve_type,
+            _ => self.bb_curve_type,
+        }
+    }
+
+    /// [`Iterator::nth`] taking a `u64`, which might not fit in `usize`.
+    pub(crate) fn nth_u64(&mut self, n: u64) -> Option<[T; 2]> {
+        if n >= self.remaining {
+            self.remaining = 0;
+            self.done = true;
+            return None;
+        }
+
+        if let Some(pow2) = &mut self.pow2 {
+            let index = area(self.size) - self.remaining + n;
+            *pow2 = Pow2State::new(pow2.order, index);
+            let point = pow2_point(pow2.order, index);
+            let transposed = self.rules.primary_axis == PrimaryAxis::Y;
+            self.position = translate(transpose_if(point, transposed), self.origin);
+            self.remaining -= n;
+        } else if n < self.basic_block_remaining_len() {
+            self.skip_in_basic_block(n);
+        } else {
+            self.seek(area(self.size) - self.remaining + n);
+        }
+
+        self.next()
     }
 
     /// Get the wrapped `LevelSt`, consuming `self`.
+    ///
+    /// This can be used to reuse the storage for another scan.
+    ///
+    /// ```
+    /// use zhang_hilbert::HilbertScanVec;
+    /// let scan = HilbertScanVec::<u32>::new_vec([64, 64]);
+    /// let level_states = scan.into_level_states();
+    /// let scan = HilbertScanVec::with_level_state_storage(level_states, [40, 50]);
+    /// assert_eq!(scan.count(), 40 * 50);
+    /// ```
     pub fn into_level_states(self) -> LevelSt {
         self.level_states
     }
-}
 
-impl<T, LevelSt> std::iter::FusedIterator for HilbertScanCore<T, LevelSt>
-where
-    LevelSt: BorrowMut<[LevelState<T>]>,
-    T: PrimInt + Unsigned + std::fmt::Debug,
-{
-}
+    /// Borrow the level states used by the current scan.
+    pub fn level_states(&self) -> &[LevelState<T>] {
+        &self.level_states.borrow()[0..self.num_levels]
+    }
 
-impl<T, LevelSt> Iterator for HilbertScanCore<T, LevelSt>
-where
-    LevelSt: BorrowMut<[LevelState<T>]>,
-    T: PrimInt + Unsigned + std::fmt::Debug,
-{
-    type Item = [T; 2];
+    /// Get the size of the rectangle being scanned.
+    ///
+    /// ```
+    /// use zhang_hilbert::HilbertScan32;
+    /// assert_eq!(HilbertScan32::new([11, 42]).size(), [11, 42]);
+    /// ```
+    pub fn size(&self) -> [T; 2] {
+        self.size
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.done {
-            return None;
+    /// Get the first point of the whole scan, including the origin. Returns
+    /// `None` if the rectangle is empty.
+    ///
+    /// Unlike [`start_point`](HilbertScanCore::start_point), this reflects
+    /// the options the scan was constructed with.
+    pub fn entry_point(&self) -> Option<[T; 2]> {
+        Self::start_point(self.size).map(|p| translate(p, self.origin))
+    }
+
+    /// Get the last point of the whole scan, including the origin, without
+    /// performing the scan. Returns `None` if the rectangle is empty.
+    ///
+    /// Unlike [`end_point`](HilbertScanCore::end_point), this reflects the
+    /// options the scan was constructed with.
+    ///
+    /// ```
+    /// use zhang_hilbert::{HilbertScanCore, LevelState, PrimaryAxis};
+    /// let scan = HilbertScanCore::with_primary_axis(
+    ///     [LevelState::default(); 32],
+    ///     [7u32, 6],
+    ///     PrimaryAxis::Y,
+    /// );
+    /// assert_eq!(scan.exit_point(), Some([0, 5]));
+    /// ```
+    pub fn exit_point(&self) -> Option<[T; 2]> {
+        Self::end_point_with_rules(self.size, self.rules).map(|p| translate(p, self.origin))
+    }
+
+    /// Get the number of levels used by the current scan, which is the
+    /// length of the slice returned by
+    /// [`level_states`](HilbertScanCore::level_states). This is equal to
+    /// [`num_levels_for_size`]`(self.size())` unless the rectangle is empty.
+    pub fn num_levels(&self) -> usize {
+        self.num_levels
+    }
+
+    /// Decompose `self` into the wrapped `LevelSt` and the rest of the state,
+    /// which can be reassembled by [`from_raw_parts`] later to resume the
+    /// scan.
+    ///
+    /// [`from_raw_parts`]: HilbertScanCore::from_raw_parts
+    pub fn into_raw_parts(self) -> (LevelSt, ScanParts<T>) {
+        let parts = self.raw_parts();
+        (self.level_states, parts)
+    }
+
+    fn raw_parts(&self) -> ScanParts<T> {
+        ScanParts {
+            size: self.size,
+            num_levels: self.num_levels,
+            last_level: self.last_level,
+            origin: self.origin,
+            position: self.position,
+            bb_progress: self.bb_progress,
+            bb_secondary_neg: self.bb_secondary_neg,
+            bb_curve_type: self.bb_curve_type,
+            bb_end: self.bb_end,
+            bb_helper_row: self.bb_helper_row,
+            remaining: self.remaining,
+            done: self.done,
+            rules: self.rules,
         }
+    }
 
-        let num_levels = self.num_levels;
-        let level_states = &mut self.level_states.borrow_mut()[0..num_levels];
+    /// Reassemble a `HilbertScanCore` from the parts produced by
+    /// [`into_raw_parts`]. The returned iterator continues the scan from where
+    /// the original one left off.
+    ///
+    /// `level_states` must borrow a slice whose first
+    /// `level_states().len()` elements are identical to those of the original
+    /// iterator at the point [`into_raw_parts`] was called. If this
+    /// requirement is violated, the iterator may panic or produce an invalid
+    /// sequence.
+    ///
+    /// [`into_raw_parts`]: HilbertScanCore::into_raw_parts
+    pub fn from_raw_parts(level_states: LevelSt, parts: ScanParts<T>) -> Self {
+        let sec_axis = curve_primary_axis(parts.bb_curve_type) as usize ^ 1;
+        let bb_sec_width = (level_states.borrow().get(parts.last_level))
+            .map_or(T::zero(), |level| level.size[sec_axis]);
 
-        // The output position
-        let position = self.position;
+        Self {
+            size: parts.size,
+            num_levels: parts.num_levels,
+            last_level: parts.last_level,
+            level_states,
+            origin: parts.origin,
+            position: parts.position,
+            bb_progress: parts.bb_progress,
+            bb_secondary_neg: parts.bb_secondary_neg,
+            bb_curve_type: parts.bb_curve_type,
+            bb_end: parts.bb_end,
+            bb_helper_row: parts.bb_helper_row,
+            bb_sec_width,
+            bb_enter_remaining: parts.remaining,
+            bb_prev_curve_type: None,
+            remaining: parts.remaining,
+            done: parts.done,
+            rules: parts.rules,
+            pow2: if is_pow2_square(parts.size) {
+                let index = area(parts.size) - parts.remaining;
+                Some(Pow2State::new(parts.size[0].trailing_zeros(), index))
+            } else {
+                None
+            },
+        }
+    }
 
-        // Update the basic block scan state
-        let [mut pri, mut sec] = self.bb_progress;
+    /// Get the index of `point` in the scan, i.e., the number of points
+    /// preceding it. This takes time proportional to the number of levels
+    /// and doesn't iterate through the points.
+    ///
+    /// The cursor is moved to `point`, so the next call to `next` returns
+    /// `point`.
+    ///
+    /// ```
+    /// use zhang_hilbert::HilbertScan32;
+    /// let mut scan = HilbertScan32::new([11, 42]);
+    /// let index = scan.rank([5, 20]);
+    /// assert_eq!(HilbertScan32::new([11, 42]).nth(index as usize), Some([5, 20]));
+    /// assert_eq!(scan.next(), Some([5, 20]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `point` is outside the rectangle.
+    pub fn rank(&mut self, point: [T; 2]) -> u64 {
+        let size = self.size;
+        let origin = self.origin;
+        let inside = (0..2).all(|i| point[i] >= origin[i] && point[i] - origin[i] < size[i]);
+        assert!(
+            inside,
+            "the point {:?} is outside the rectangle of the size {:?} at {:?}",
+            debug_point(point),
+            debug_point(size),
+            debug_point(origin)
+        );
+        // Relative to `origin`
+        let rel_point = [point[0] - origin[0], point[1] - origin[1]];
+
+        if let Some(pow2) = &mut self.pow2 {
+            let transposed = self.rules.primary_axis == PrimaryAxis::Y;
+            let index = pow2_index(pow2.order, transpose_if(rel_point, transposed));
+            *pow2 = Pow2State::new(pow2.order, index);
+            self.position = point;
+            self.remaining = area(size) - index;
+            self.done = false;
+            self.mark_repositioned();
+            return index;
+        }
+
+        // Find the bottom-level block containing `point`
+        let start = if self.num_levels > 2 {
+            self.locate_point(rel_point).0
+        } else {
+            0
+        };
+        self.seek(start);
+
+        // The bottom-level block consists of a handful of basic blocks
+        let mut index = start;
+        loop {
+            if let Some(offset) = self.offset_in_basic_block(point) {
+                self.skip_in_basic_block(offset);
+                self.mark_repositioned();
+                return index + offset;
+            }
+            let bb_len = self.basic_block_remaining_len();
+            self.skip_in_basic_block(bb_len - 1);
+            self.next();
+            index += bb_len;
+        }
+    }
+
+    /// Append the index ranges `[start, end)` of the points inside the
+    /// rectangle `[query_start, query_end)` to `out` in the ascending order,
+    /// merging adjacent ones. The cursor is rewound afterward.
+    pub(crate) fn cover_intervals(
+        &mut self,
+        query_start: [T; 2],
+        query_end: [T; 2],
+        out: &mut Vec<(u64, u64)>,
+    ) {
+        let size = self.size;
+        let query_end = [min(query_end[0], size[0]), min(query_end[1], size[1])];
+        if query_start[0] >= query_end[0] || query_start[1] >= query_end[1] {
+            return;
+        }
+
+        // The traversal relies on the state of the generic algorithm
+        self.pow2 = None;
+
+        let query = [query_start, query_end];
+        let top = self.rules.top_curve_type();
+        self.cover_block(0, [T::zero(); 2], size, top, 0, query, out);
+
+        self.reset();
+    }
+
+    /// The recursive part of `cover_intervals`. Visits the block at the level
+    /// `level` whose first point is the `start`-th one. The block must
+    /// intersect with `query`.
+    #[allow(clippy::too_many_arguments)]
+    fn cover_block(
+        &mut self,
+        level: usize,
+        origin: [T; 2],
+        size: [T; 2],
+        curve_type: u8,
+        start: u64,
+        query: [[T; 2]; 2],
+        out: &mut Vec<(u64, u64)>,
+    ) {
+        let [query_start, query_end] = query;
+        let inside =
+            (0..2).all(|i| origin[i] >= query_start[i] && origin[i] + size[i] <= query_end[i]);
+        if inside {
+            push_interval(out, start, start + area(size));
+            return;
+        }
+
+        if level + 2 >= self.num_levels {
+            self.cover_bottom_block(start, area(size), query, out);
+            return;
+        }
+
+        let division = self.rules.division;
+        let size_l1 = size.map(|x| division.l1(x));
+        let size_l0 = [size[0] - size_l1[0], size[1] - size_l1[1]];
+
+        let mut start = start;
+        let sub_curve_types = CURVE_INDUCTION_TABLE[curve_type as usize];
+        for (progress, &sub_curve_type) in sub_curve_types.iter().enumerate() {
+            let adr = CURVE_ADDRESS_TABLE[curve_type as usize] >> (progress * 2) as u32;
+            let adr0 = (adr & 0b10) != 0;
+            let adr1 = (adr & 0b01) != 0;
+            let sub_size = [
+                if adr0 { size_l1[0] } else { size_l0[0] },
+                if adr1 { size_l1[1] } else { size_l0[1] },
+            ];
+            let sub_origin = [
+                if adr0 {
+                    origin[0] + size_l0[0]
+                } else {
+                    origin[0]
+                },
+                if adr1 {
+                    origin[1] + size_l0[1]
+                } else {
+                    origin[1]
+                },
+            ];
+
+            let intersects = (0..2).all(|i| {
+                sub_origin[i] < query_end[i] && sub_origin[i] + sub_size[i] > query_start[i]
+            });
+            if intersects {
+                self.cover_block(
+                    level + 1,
+                    sub_origin,
+                    sub_size,
+                    sub_curve_type,
+                    start,
+                    query,
+                    out,
+                );
+            }
+
+            start += area(sub_size);
+        }
+    }
+
+    /// Visit the basic blocks of the bottom-level block consisting of the
+    /// points `start..start + len`.
+    fn cover_bottom_block(
+        &mut self,
+        start: u64,
+        len: u64,
+        query: [[T; 2]; 2],
+        out: &mut Vec<(u64, u64)>,
+    ) {
+        self.seek(start);
+
+        let end = start + len;
+        let mut index = start;
+        loop {
+            let bb_len = self.basic_block_remaining_len();
+            self.cover_basic_block(index, query, out);
+            index += bb_len;
+            if index >= end {
+                break;
+            }
+            self.skip_in_basic_block(bb_len - 1);
+            self.next();
+        }
+    }
+
+    /// Visit the current basic block, whose first point is the `start`-th
+    /// one. The cursor must be at the first point of the basic block.
+    fn cover_basic_block(&self, start: u64, query: [[T; 2]; 2], out: &mut Vec<(u64, u64)>) {
+        let [pri_len, sec_width] = self.bb_progress.map(|x| x.to_u64().unwrap());
         let pri_axis = curve_primary_axis(self.bb_curve_type) as usize;
         let sec_axis = pri_axis ^ 1;
-        let sec_width = level_states[self.last_level].size[sec_axis];
-        sec = sec - T::one();
+        let pri_neg = curve_primary_negative(self.bb_curve_type) != 0;
+        let sec_neg = self.bb_secondary_neg;
+        let position = [0, 1].map(|i| (self.position[i] - self.origin[i]).to_u64().unwrap());
+        let [query_start, query_end] = query.map(|p| p.map(|x| x.to_u64().unwrap()));
+
+        // Find the range of the offsets from `position` along `axis` (in the
+        // direction given by `neg`) inside the query
+        let offsets = |axis: usize, len: u64, neg: bool| {
+            let p = position[axis];
+            let (lo, hi) = if neg {
+                (p + 1 - len, p + 1)
+            } else {
+                (p, p + len)
+            };
+            let (lo, hi) = (max(lo, query_start[axis]), min(hi, query_end[axis]));
+            if lo >= hi {
+                None
+            } else if neg {
+                Some((p + 1 - hi, p - lo))
+            } else {
+                Some((lo - p, hi - 1 - p))
+            }
+        };
 
-        if sec == T::zero() {
-            pri = pri - T::one();
-            sec = sec_width;
-            // Zigzag
-            self.bb_secondary_neg = !self.bb_secondary_neg;
-        } else {
-            let sec_pos = &mut self.position[sec_axis];
-            if self.bb_secondary_neg {
-                *sec_pos = *sec_pos - T::one();
+        let (columns, (o0, o1)) = match (
+            offsets(pri_axis, pri_len, pri_neg),
+            offsets(sec_axis, sec_width, sec_neg),
+        ) {
+            (Some(columns), Some(sec)) => (columns, sec),
+            _ => return,
+        };
+
+        if o0 == 0 && o1 == sec_width - 1 {
+            // The columns are entirely inside
+            push_interval(
+                out,
+                start + columns.0 * sec_width,
+                start + (columns.1 + 1) * sec_width,
+            );
+            return;
+        }
+
+        for column in columns.0..=columns.1 {
+            let base = start + column * sec_width;
+            // Zigzag - the odd-numbered columns run backward
+            if column % 2 == 0 {
+                push_interval(out, base + o0, base + o1 + 1);
             } else {
-                *sec_pos = *sec_pos + T::one();
+                push_interval(out, base + sec_width - 1 - o1, base + sec_width - o0);
             }
-            self.bb_progress = [pri, sec];
-            return Some(position);
         }
+    }
 
-        if pri == T::zero() {
-            // This block is complete! Find the next block.
+    /// Take a snapshot of the scan, which can be used by [`restore`] later to
+    /// resume the scan.
+    ///
+    /// [`restore`]: HilbertScanCore::restore
+    pub fn save_state(&self) -> ScanSnapshot<T> {
+        ScanSnapshot {
+            level_states: self.level_states().to_vec(),
+            parts: self.raw_parts(),
+        }
+    }
+
+    /// Construct a `HilbertScanCore` that continues the scan from the point
+    /// where `snapshot` was taken by [`save_state`].
+    ///
+    /// The elements of the slice borrowed by `level_states` do not have to
+    /// be initialized as they are overwritten by this function.
+    ///
+    /// If `snapshot` was not produced by [`save_state`] or
+    /// [`ScanSnapshot::from_bytes`] (e.g., it was deserialized by `serde`
+    /// from corrupted data), the iterator may panic or produce an invalid
+    /// sequence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice borrowed by `level_states` is too small to hold
+    /// the level states in `snapshot`.
+    ///
+    /// [`save_state`]: HilbertScanCore::save_state
+    pub fn restore(snapshot: &ScanSnapshot<T>, mut level_states: LevelSt) -> Self {
+        let num_levels = snapshot.parts.num_levels;
+        assert_eq!(
+            snapshot.level_states.len(),
+            num_levels,
+            "inconsistent scan snapshot"
+        );
+        level_states.borrow_mut()[0..num_levels].copy_from_slice(&snapshot.level_states);
+        Self::from_raw_parts(level_states, snapshot.parts)
+    }
+
+    /// Produce the next point using the classic Hilbert curve algorithm.
+    /// Requires `self.pow2.is_some()` and `!self.done`.
+    fn next_pow2(&mut self) -> [T; 2] {
+        let position = self.position;
+        let pow2 = self.pow2.as_mut().unwrap();
+
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.done = true;
         } else {
-            let pri_pos = &mut self.position[pri_axis];
-            if curve_primary_negative(self.bb_curve_type) != 0 {
-                *pri_pos = *pri_pos - T::one();
+            let index = (1u64 << (pow2.order * 2)) - self.remaining - 1;
+            let (axis, neg) = pow2.step(index);
+            let axis = axis ^ self.rules.top_curve_type() as usize;
+            let p = &mut self.position[axis];
+            *p = if neg { *p - T::one() } else { *p + T::one() };
+        }
+
+        position
+    }
+
+    /// Produce up to `max_count` points in the current column, stopping
+    /// before the last one, and fold them using `f`. Returns the number of
+    /// points produced and the accumulated value. Requires
+    /// `self.pow2.is_none()` and `!self.done`.
+    #[inline]
+    fn fold_in_column<B>(
+        &mut self,
+        max_count: T,
+        mut acc: B,
+        mut f: impl FnMut(B, [T; 2]) -> B,
+    ) -> (T, B) {
+        let [pri, sec] = self.bb_progress;
+        let sec_axis = curve_primary_axis(self.bb_curve_type) as usize ^ 1;
+        let sec_neg = self.bb_secondary_neg;
+
+        let count = min(sec - T::one(), max_count);
+        let mut position = self.position;
+        let mut i = count;
+        while i != T::zero() {
+            acc = f(acc, position);
+            let sec_pos = &mut position[sec_axis];
+            *sec_pos = if sec_neg {
+                *sec_pos - T::one()
             } else {
-                *pri_pos = *pri_pos + T::one();
+                *sec_pos + T::one()
+            };
+            i = i - T::one();
+        }
+
+        self.remaining -= count.to_u64().unwrap();
+        self.position = position;
+        self.bb_progress = [pri, sec - count];
+        (count, acc)
+    }
+
+    /// Write as many of the next points as fit into `out` and return the
+    /// number of points written, which is `0` if the scan is complete.
+    ///
+    /// ```
+    /// use zhang_hilbert::HilbertScan32;
+    /// let mut scan = HilbertScan32::new([6, 7]);
+    /// let mut buffer = [[0; 2]; 16];
+    /// let mut points = Vec::new();
+    /// loop {
+    ///     let count = scan.fill_buffer(&mut buffer);
+    ///     if count == 0 {
+    ///         break;
+    ///     }
+    ///     points.extend_from_slice(&buffer[..count]);
+    /// }
+    /// assert_eq!(points, HilbertScan32::new([6, 7]).collect::<Vec<_>>());
+    /// ```
+    pub fn fill_buffer(&mut self, out: &mut [[T; 2]]) -> usize {
+        let mut written = 0;
+
+        if self.pow2.is_some() {
+            while written < out.len() && !self.done {
+                out[written] = self.next_pow2();
+                written += 1;
+            }
+            return written;
+        }
+
+        while written < out.len() && !self.done {
+            let space = T::from_usize(out.len() - written).unwrap_or_else(T::max_value);
+            let (count, _) =
+                self.fold_in_column(space, out[written..].iter_mut(), |mut slots, p| {
+                    *slots.next().unwrap() = p;
+                    slots
+                });
+            written += count.to_usize().unwrap();
+
+            if written < out.len() {
+                // `next` takes care of the last point and what follows it
+                if let Some(p) = self.next() {
+                    out[written] = p;
+                    written += 1;
+                }
             }
-            self.bb_progress = [pri, sec];
-            return Some(position);
         }
 
+        written
+    }
+
+    /// Move the cursor to the next block (or the helper row) after the current
+    /// basic block is complete. This is separated from `next` to keep the
+    /// common path of `next` small.
+    #[inline(never)]
+    fn next_block(&mut self) {
+        let pri_axis = curve_primary_axis(self.bb_curve_type) as usize;
+        let sec_axis = pri_axis ^ 1;
+
+        let num_levels = self.num_levels;
+        let division = self.rules.division;
+        let top = self.rules.top_curve_type();
+        let transposed = top != 0;
+        let level_states = &mut self.level_states.borrow_mut()[0..num_levels];
+
         if self.bb_helper_row {
             let block_done = if self.last_level == num_levels - 2 {
                 true
             } else {
-                level_states[num_levels - 2].progress == 3
+                level_states[num_levels - 2].progress() == 3
             };
 
             if block_done {
@@ -502,17 +2763,24 @@ where
                 //    | '---'  /
                 //
                 let level = &mut level_states[num_levels - 2];
-                let pri_axis = curve_primary_axis(level.curve_type) as usize;
+                let pri_axis = curve_primary_axis(level.curve_type()) as usize;
                 let sec_axis = pri_axis ^ 1;
                 let sec_width = level.size[sec_axis];
 
                 self.bb_end = 0b11;
-                self.bb_curve_type = level.curve_type;
+                self.bb_curve_type = level.curve_type();
                 self.bb_secondary_neg = false;
                 self.bb_progress = [T::one(), sec_width];
+                self.bb_sec_width = sec_width;
 
                 self.bb_helper_row = false;
 
+                trace!(
+                    "starting helper row at {:?}: width={:?}",
+                    debug_point(self.position),
+                    sec_width.to_u128()
+                );
+
                 let pri_pos = &mut self.position[pri_axis];
                 if curve_primary_negative(self.bb_curve_type) != 0 {
                     *pri_pos = *pri_pos - T::one();
@@ -521,33 +2789,34 @@ where
                 }
                 self.last_level = num_levels - 2;
 
-                return Some(position);
+                return;
             }
         }
 
         if self.last_level == 0 {
             self.done = true;
-            return Some(position);
+            return;
         }
 
         let mut i = self.last_level - 1;
         let next_bb_enter;
 
         loop {
-            level_states[i].progress += 1;
-            if level_states[i].progress == 4 {
+            let progress = level_states[i].progress() + 1;
+            level_states[i].set_progress(progress);
+            if progress == 4 {
                 if i == 0 {
                     // No left blocks
                     self.done = true;
-                    return Some(position);
+                    return;
                 } else {
                     i -= 1;
                 }
             } else {
                 // Get the relative position of the next block
                 let level = &level_states[i];
-                let adr = CURVE_ADDRESS_TABLE[level.curve_type as usize]
-                    >> (level.progress * 2 - 2) as u32;
+                let adr = CURVE_ADDRESS_TABLE[level.curve_type() as usize]
+                    >> (level.progress() * 2 - 2) as u32;
                 // adr[1:0] = current, adr[3:2] = next
                 let adr_rel = adr ^ (adr >> 2);
                 debug_assert!((adr_rel & 3) == 0b01 || (adr_rel & 3) == 0b10);
@@ -582,16 +2851,24 @@ where
         if i == num_levels - 2 {
             // We were and are still in the same basic block and we just moved
             // between extra-subdivided blocks.
-            let progress = level_states[i].progress;
-            let curve_type = level_states[i].curve_type;
+            let progress = level_states[i].progress();
+            let curve_type = level_states[i].curve_type();
 
             let adr = CURVE_ADDRESS_TABLE[curve_type as usize] >> (progress * 2) as u32;
             let bb_curve_type = CURVE_INDUCTION_TABLE[curve_type as usize][progress as usize];
 
             let prev_size = level_states[i].size;
-            let size = extra_division_subblock_size(prev_size, adr, curve_type);
+            let size = extra_division_subblock_size(prev_size, adr, curve_type, transposed);
             level_states[i + 1].size = size;
 
+            trace!(
+                "entering sub-block {} at {:?}: size={:?} curve_type={}",
+                progress,
+                debug_point(self.position),
+                debug_point(size),
+                bb_curve_type
+            );
+
             self.bb_secondary_neg = curve_secondary_negative_at_start(bb_curve_type) != 0;
             self.bb_curve_type = bb_curve_type;
             self.bb_end = curve_end_point(bb_curve_type);
@@ -600,19 +2877,20 @@ where
             } else {
                 [size[0], size[1]]
             };
+            self.bb_sec_width = self.bb_progress[1];
 
-            debug_assert_eq!(self.bb_progress[0] & T::one(), T::zero());
-            debug_assert_ne!(self.bb_progress[0], T::zero());
-            debug_assert_ne!(self.bb_progress[1], T::zero());
+            debug_assert!(self.bb_progress[0] & T::one() == T::zero());
+            debug_assert!(self.bb_progress[0] != T::zero());
+            debug_assert!(self.bb_progress[1] != T::zero());
 
             debug_assert_eq!(self.last_level, num_levels - 1);
 
-            return Some(position);
+            return;
         }
 
         while i < num_levels - 2 {
-            let progress = level_states[i].progress;
-            let curve_type = level_states[i].curve_type;
+            let progress = level_states[i].progress();
+            let curve_type = level_states[i].curve_type();
 
             let adr = CURVE_ADDRESS_TABLE[curve_type as usize] >> (progress * 2) as u32;
             let adr0 = (adr & 0b10) != 0;
@@ -620,7 +2898,7 @@ where
             let ind = CURVE_INDUCTION_TABLE[curve_type as usize][progress as usize];
 
             let prev_size = level_states[i].size;
-            let size_l1 = prev_size.map(division_l1);
+            let size_l1 = prev_size.map(|x| division.l1(x));
             let size_l0 = [prev_size[0] - size_l1[0], prev_size[1] - size_l1[1]];
 
             let size = [
@@ -628,9 +2906,7 @@ where
                 if adr1 { size_l1[1] } else { size_l0[1] },
             ];
 
-            level_states[i + 1].size = size;
-            level_states[i + 1].curve_type = ind;
-            level_states[i + 1].progress = 0;
+            level_states[i + 1] = LevelState::new(size, ind, 0);
 
             i += 1;
         }
@@ -643,8 +2919,10 @@ where
         // > block. Then we can decide the scanning manner of this T_B(E, E)
         // > block.
         //
+        // The parities are examined in the frame where X is primary
         let mut size = level_states[i].size;
-        let even_flags = (((size[0] & T::one()) << 1) | (size[1] & T::one()))
+        let [pri_size, sec_size] = transpose_if(size, transposed);
+        let even_flags = (((pri_size & T::one()) << 1) | (sec_size & T::one()))
             .to_u8()
             .unwrap();
 
@@ -688,8 +2966,8 @@ where
                 ],
                 [
                     // bottom-right to ...
-                    4 | 0, // 0b10 → 0b00 - Reversed type-0 basic pattern
-                    4 | 0, // 0b10 → 0b00 - Reversed type-0 basic pattern
+                    4, // 0b10 → 0b00 - Reversed type-0 basic pattern
+                    4, // 0b10 → 0b00 - Reversed type-0 basic pattern
                 ],
                 [
                     // top-right to ...
@@ -708,17 +2986,17 @@ where
                 let mut i = i - 1;
                 loop {
                     let level = &level_states[i];
-                    if level.progress == 3 {
+                    if level.progress() == 3 {
                         if i == 0 {
-                            next_dir = 0; // Default to X
-                            next_dir_sign = 0; // Positive X (move right)
+                            next_dir = top; // Default to the primary axis
+                            next_dir_sign = 0; // Positive direction
                             break;
                         } else {
                             i -= 1;
                         }
                     } else {
-                        let adr = CURVE_ADDRESS_TABLE[level.curve_type as usize]
-                            >> (level.progress * 2) as u32;
+                        let adr = CURVE_ADDRESS_TABLE[level.curve_type() as usize]
+                            >> (level.progress() * 2) as u32;
                         // adr[1:0] = current, adr[3:2] = next
                         let adr_rel = adr ^ (adr >> 2);
                         debug_assert!((adr_rel & 3) == 0b01 || (adr_rel & 3) == 0b10);
@@ -733,21 +3011,27 @@ where
                 )
             }
             0b01 => {
-                let is_last_block = [
-                    self.position[0] + size[0],
-                    self.position[1] + T::one() - size[1],
-                ] == [self.size[0], T::zero()];
-                if is_last_block {
+                // Relative to `origin`
+                let position = transpose_if(
+                    [
+                        self.position[0] - self.origin[0],
+                        self.position[1] - self.origin[1],
+                    ],
+                    transposed,
+                );
+                let is_last_block = [position[0] + pri_size, position[1] + T::one() - sec_size]
+                    == [transpose_if(self.size, transposed)[0], T::zero()];
+                if is_last_block && self.rules.variant == CurveVariant::Modified {
                     // T_B(E, O) - Reversed Type-1 basic pattern + helper row
                     // (This is a deviation from the original algorithm)
-                    (4 | 1, true)
+                    ((4 | 1) ^ top, true)
                 } else {
                     // T_B(E, O) - Reversed Type-2 basic pattern
-                    (4 | 2, false)
+                    ((4 | 2) ^ top, false)
                 }
             }
             // T_B(O, E) - Reversed type-3 basic pattern
-            0b10 => (4 | 3, false),
+            0b10 => ((4 | 3) ^ top, false),
             // T_B(O, O) - Unreachable because there can be only one T_B(O, O)
             // a rectangle!
             0b11 => unreachable!(),
@@ -755,29 +3039,45 @@ where
         };
 
         if helper {
-            debug_assert_eq!(bb_curve_type, 4 | 1);
-            debug_assert_eq!(curve_primary_axis(bb_curve_type), 1);
+            debug_assert_eq!(bb_curve_type, (4 | 1) ^ top);
+            debug_assert_eq!(curve_primary_axis(bb_curve_type), 1 ^ top);
             // Exclude the helper row from the block size
-            size[1] = size[1] - T::one();
+            let sec_axis = 1 ^ top as usize;
+            size[sec_axis] = size[sec_axis] - T::one();
             level_states[i].size = size;
         }
-        level_states[i].curve_type = bb_curve_type;
+        level_states[i].set_curve_type(bb_curve_type);
+
+        trace!(
+            "entering block at {:?}: size={:?} curve_type={} entry={:#04b} helper_row={}",
+            debug_point(self.position),
+            debug_point(size),
+            bb_curve_type,
+            next_bb_enter,
+            helper
+        );
 
-        let three = T::from(3u8).unwrap();
-        if size[0] >= three && size[1] >= three {
+        if self.rules.subdivides(size) {
             // If the block is large enough, we can (and should) do the extra
             // subdivision (i.e., dividing the smallest blocks defined by the
             // top level of the algorithm in the paper)
-            level_states[i].progress = 0;
+            level_states[i].set_progress(0);
 
-            size = extra_division_subblock_size(size, next_bb_enter, bb_curve_type);
+            size = extra_division_subblock_size(size, next_bb_enter, bb_curve_type, transposed);
             bb_curve_type = CURVE_INDUCTION_TABLE[bb_curve_type as usize][0];
 
+            trace!(
+                "extra subdivision: first sub-block size={:?}",
+                debug_point(size)
+            );
+
             i += 1;
             debug_assert_eq!(i, num_levels - 1);
+            debug_assert!(i < storage_requirement_for_rules(self.size, self.rules).exact);
             level_states[i].size = size;
         } else {
             // Otherwise, apply the basic scanning pattern on this block.
+            trace!("no extra subdivision");
         }
         self.bb_secondary_neg = curve_secondary_negative_at_start(bb_curve_type) != 0;
         self.bb_curve_type = bb_curve_type;
@@ -788,14 +3088,646 @@ where
             [size[0], size[1]]
         };
         self.bb_helper_row = helper;
+        self.bb_sec_width = self.bb_progress[1];
 
-        debug_assert_eq!(self.bb_progress[0] & T::one(), T::zero());
-        debug_assert_ne!(self.bb_progress[0], T::zero());
-        debug_assert_ne!(self.bb_progress[1], T::zero());
+        debug_assert!(self.bb_progress[0] & T::one() == T::zero());
+        debug_assert!(self.bb_progress[0] != T::zero());
+        debug_assert!(self.bb_progress[1] != T::zero());
 
         self.last_level = i;
+    }
+
+    /// Get the number of points yet to be produced in the current basic
+    /// block, including the one at `position`.
+    fn basic_block_remaining_len(&self) -> u64 {
+        let [pri, sec] = self.bb_progress.map(|x| x.to_u64().unwrap());
+        (pri - 1) * self.bb_sec_width.to_u64().unwrap() + sec
+    }
+
+    /// Skip `count` points without leaving the current basic block.
+    ///
+    /// `count` must be less than `basic_block_remaining_len()`.
+    fn skip_in_basic_block(&mut self, count: u64) {
+        if count == 0 {
+            return;
+        }
+
+        let step = |x: T, delta: T, neg: bool| if neg { x - delta } else { x + delta };
+
+        let [pri, sec] = self.bb_progress;
+        let pri_axis = curve_primary_axis(self.bb_curve_type) as usize;
+        let sec_axis = pri_axis ^ 1;
+        let sec_neg = self.bb_secondary_neg;
+        self.remaining -= count;
+
+        if count < sec.to_u64().unwrap() {
+            // Stay in the current column
+            let count = T::from_u64(count).unwrap();
+            self.position[sec_axis] = step(self.position[sec_axis], count, sec_neg);
+            self.bb_progress = [pri, sec - count];
+            return;
+        }
+
+        // Move to one of the subsequent columns
+        let sec_width = self.bb_sec_width;
+        let count = count - sec.to_u64().unwrap();
+        let sec_width_u64 = sec_width.to_u64().unwrap();
+        let columns = T::from_u64(count / sec_width_u64 + 1).unwrap();
+        let sec_offset = T::from_u64(count % sec_width_u64).unwrap();
+
+        // Zigzag - the odd-numbered columns start where the current column
+        // ends
+        let end = step(self.position[sec_axis], sec - T::one(), sec_neg);
+        let (start, neg) = if (columns & T::one()) != T::zero() {
+            (end, !sec_neg)
+        } else {
+            (step(end, sec_width - T::one(), !sec_neg), sec_neg)
+        };
+
+        self.position[sec_axis] = step(start, sec_offset, neg);
+        self.position[pri_axis] = step(
+            self.position[pri_axis],
+            columns,
+            curve_primary_negative(self.bb_curve_type) != 0,
+        );
+        self.bb_secondary_neg = neg;
+        self.bb_progress = [pri - columns, sec_width - sec_offset];
+    }
+
+    /// Skip `count` points by stepping through basic blocks.
+    fn skip_by_basic_blocks(&mut self, mut count: u64) {
+        while count > 0 {
+            let bb_len = self.basic_block_remaining_len();
+            if count < bb_len {
+                self.skip_in_basic_block(count);
+                return;
+            }
+            self.skip_in_basic_block(bb_len - 1);
+            self.next();
+            count -= bb_len;
+        }
+    }
+
+    /// Find the offset of `point` from the start of the current basic block.
+    /// Returns `None` if the basic block doesn't contain `point`.
+    ///
+    /// The cursor must be at the first point of the basic block.
+    fn offset_in_basic_block(&self, point: [T; 2]) -> Option<u64> {
+        // The distance from `from` to `to` in the direction given by `neg`
+        let dist = |from: T, to: T, neg: bool| {
+            if neg {
+                (to <= from).then(|| from - to)
+            } else {
+                (to >= from).then(|| to - from)
+            }
+        };
+
+        let [pri_len, sec_width] = self.bb_progress;
+        let pri_axis = curve_primary_axis(self.bb_curve_type) as usize;
+        let sec_axis = pri_axis ^ 1;
+        let pri_neg = curve_primary_negative(self.bb_curve_type) != 0;
+
+        let column =
+            dist(self.position[pri_axis], point[pri_axis], pri_neg).filter(|&c| c < pri_len)?;
+        let offset = dist(
+            self.position[sec_axis],
+            point[sec_axis],
+            self.bb_secondary_neg,
+        )
+        .filter(|&s| s < sec_width)?;
+
+        // Zigzag - the odd-numbered columns run backward
+        let offset = if (column & T::one()) != T::zero() {
+            sec_width - T::one() - offset
+        } else {
+            offset
+        };
+
+        let [column, offset, sec_width] = [column, offset, sec_width].map(|x| x.to_u64().unwrap());
+        Some(column * sec_width + offset)
+    }
+
+    /// Update `level_states[0..=num_levels - 2]` to describe the path to the
+    /// bottom-level block containing the `index`-th point.
+    ///
+    /// Returns the index of the first point in the block, the block's origin,
+    /// and its size.
+    fn locate(&mut self, index: u64) -> (u64, [T; 2], [T; 2]) {
+        self.locate_by(|start, _, sub_size| index - start < area(sub_size))
+    }
+
+    /// Update `level_states[0..=num_levels - 2]` to describe the path to the
+    /// bottom-level block containing `point`.
+    ///
+    /// Returns the index of the first point in the block, the block's origin,
+    /// and its size.
+    fn locate_point(&mut self, point: [T; 2]) -> (u64, [T; 2], [T; 2]) {
+        self.locate_by(|_, sub_origin, sub_size| {
+            (0..2).all(|i| point[i] >= sub_origin[i] && point[i] - sub_origin[i] < sub_size[i])
+        })
+    }
+
+    /// The implementation of `locate` and `locate_point`. `is_target` is
+    /// called with the index of the first point, the origin, and the size of
+    /// each candidate block.
+    fn locate_by(
+        &mut self,
+        mut is_target: impl FnMut(u64, [T; 2], [T; 2]) -> bool,
+    ) -> (u64, [T; 2], [T; 2]) {
+        let num_levels = self.num_levels;
+        let division = self.rules.division;
+        let level_states = &mut self.level_states.borrow_mut()[0..num_levels];
+
+        level_states[0] = LevelState::new(self.size, self.rules.top_curve_type(), 0);
+
+        let mut start = 0;
+        let mut origin = [T::zero(), T::zero()];
+
+        for i in 0..num_levels - 2 {
+            let size = level_states[i].size;
+            let curve_type = level_states[i].curve_type();
+            let size_l1 = size.map(|x| division.l1(x));
+            let size_l0 = [size[0] - size_l1[0], size[1] - size_l1[1]];
+
+            let mut progress = 0;
+            loop {
+                let adr = CURVE_ADDRESS_TABLE[curve_type as usize] >> (progress * 2) as u32;
+                let adr0 = (adr & 0b10) != 0;
+                let adr1 = (adr & 0b01) != 0;
+                let sub_size = [
+                    if adr0 { size_l1[0] } else { size_l0[0] },
+                    if adr1 { size_l1[1] } else { size_l0[1] },
+                ];
+                let sub_origin = [
+                    if adr0 {
+                        origin[0] + size_l0[0]
+                    } else {
+                        origin[0]
+                    },
+                    if adr1 {
+                        origin[1] + size_l0[1]
+                    } else {
+                        origin[1]
+                    },
+                ];
+
+                if progress == 3 || is_target(start, sub_origin, sub_size) {
+                    origin = sub_origin;
+                    level_states[i].set_progress(progress);
+                    level_states[i + 1] = LevelState::new(
+                        sub_size,
+                        CURVE_INDUCTION_TABLE[curve_type as usize][progress as usize],
+                        0,
+                    );
+                    break;
+                }
+
+                start += area(sub_size);
+                progress += 1;
+            }
+        }
+
+        (start, origin, level_states[num_levels - 2].size)
+    }
+
+    /// Move the cursor to the `index`-th point. `index` must be less than the
+    /// total number of points.
+    fn seek(&mut self, index: u64) {
+        let num_levels = self.num_levels;
+        let start = if num_levels > 2 {
+            self.locate(index).0
+        } else {
+            0
+        };
+
+        if start == 0 {
+            // `index` is in the first bottom-level block, which is set up by
+            // `reset`
+            self.reset_generic();
+            self.skip_by_basic_blocks(index);
+            self.mark_repositioned();
+            return;
+        }
+
+        // Set up the state as if we are just about to produce the last point
+        // of the previous bottom-level block. `next` will take care of the
+        // initialization of the block containing `index`.
+        let last_index = start - 1;
+        let (_, origin, size) = self.locate(last_index);
+        let level_states = &self.level_states.borrow()[0..num_levels];
+
+        // Find the direction to the next block
+        let mut i = num_levels - 3;
+        let adr = loop {
+            let level = &level_states[i];
+            if level.progress() == 3 {
+                // This doesn't underflow because `last_index` isn't the last
+                // point
+                i -= 1;
+            } else {
+                break CURVE_ADDRESS_TABLE[level.curve_type() as usize]
+                    >> (level.progress() * 2) as u32;
+            }
+        };
+        let adr_rel = (adr ^ (adr >> 2)) & 0b11;
+        let axis = (adr_rel & 0b01) as usize;
+        let negative = (adr & adr_rel) != 0;
+
+        // Find the exit point of the block. It's one of the corners facing
+        // the next block.
+        let last = [
+            origin[0] + size[0] - T::one(),
+            origin[1] + size[1] - T::one(),
+        ];
+        let mut exit = last;
+        if (size[axis ^ 1] & T::one()) == T::zero() {
+            // The two candidates have different checkerboard colors. Every
+            // step alternates the color, so the point's color is determined
+            // by the parity of its index.
+            if negative {
+                exit[axis] = origin[axis];
+            }
+            exit[axis ^ 1] = origin[axis ^ 1];
+            // The parity of `exit[0] + exit[1]`, which might overflow
+            let color = (exit[0].to_u128() ^ exit[1].to_u128()) as u64 & 1;
+            if color != (last_index & 1) {
+                exit[axis ^ 1] = last[axis ^ 1];
+            }
+        } else {
+            // `T_B(O, E)`, `T_B(E, O)`, and the first block with a helper row
+            // all end at the top-right corner
+        }
+
+        self.last_level = num_levels - 2;
+        self.position = translate(exit, self.origin);
+        self.bb_progress = [T::one(), T::one()];
+        // Choose a curve type whose primary direction points to the next
+        // block so that `next` moves the cursor in that direction
+        self.bb_curve_type = [[0, 2], [1, 3]][axis][negative as usize];
+        self.bb_secondary_neg = false;
+        self.bb_end = (((exit[0] != origin[0]) as u8) << 1) | (exit[1] != origin[1]) as u8;
+        self.bb_helper_row = false;
+        self.bb_sec_width = size[axis ^ 1];
+        self.remaining = area(self.size) - last_index;
+        self.done = false;
+
+        self.next();
+        self.skip_by_basic_blocks(index - start);
+        self.mark_repositioned();
+    }
+}
+
+impl<LevelSt> HilbertScanCore<u32, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<u32>]>,
+{
+    /// Pack each point `[x, y]` into a `u32` as `(y << 16) | x`. See
+    /// [`PackedU16x2`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if some coordinate doesn't fit in 16 bits. See
+    /// [`Self::try_packed_u16x2`] for a non-panicking version.
+    pub fn packed_u16x2(self) -> PackedU16x2<Self> {
+        self.try_packed_u16x2().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Pack each point `[x, y]` into a `u32` as `(y << 16) | x`. Returns
+    /// [`ScanError::CoordinateOverflow`] if some coordinate doesn't fit in
+    /// 16 bits, i.e., a side of the rectangle (including the origin) exceeds
+    /// `65536`.
+    pub fn try_packed_u16x2(self) -> Result<PackedU16x2<Self>, ScanError> {
+        let (size, origin) = (self.size, self.origin);
+        PackedU16x2::new(self, size, origin)
+    }
+}
+
+impl<T> HilbertScanCore<T, Vec<LevelState<T>>>
+where
+    T: ScanCoord,
+{
+    /// Construct a `HilbertScanCore` with a `Vec` having exactly as many
+    /// elements as required by [`num_levels_for_size`]`(size)`.
+    ///
+    /// This allocates no more memory than needed and supports any coordinate
+    /// type.
+    ///
+    /// ```
+    /// use zhang_hilbert::HilbertScanVec;
+    /// let mut scan = HilbertScanVec::<u64>::new_vec([1 << 40, 3 << 20]);
+    /// assert_eq!(scan.next(), Some([0, 0]));
+    /// ```
+    pub fn new_vec(size: [T; 2]) -> Self {
+        let level_states = vec![LevelState::new([T::zero(); 2], 0, 0); num_levels_for_size(size)];
+        Self::with_level_state_storage(level_states, size)
+    }
+}
+
+impl<T, const LEVELS: usize> HilbertScanCore<T, [LevelState<T>; LEVELS]>
+where
+    T: ScanCoord,
+{
+    /// Construct a `HilbertScanCore` with an array of `LEVELS` elements.
+    /// Unlike [`new`](HilbertScanCore::new), this supports any `LEVELS`.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_new_array`] returns an error.
+    ///
+    /// [`try_new_array`]: HilbertScanCore::try_new_array
+    pub fn new_array(size: [T; 2]) -> Self {
+        Self::try_new_array(size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `HilbertScanCore` with an array of `LEVELS` elements,
+    /// returning an error if `LEVELS` is too small for `size` or `size` is
+    /// not supported.
+    pub fn try_new_array(size: [T; 2]) -> Result<Self, ScanError> {
+        Self::try_with_level_state_storage([LevelState::new([T::zero(); 2], 0, 0); LEVELS], size)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<T, const N: usize> HilbertScanCore<T, heapless::Vec<LevelState<T>, N>>
+where
+    T: ScanCoord,
+{
+    /// Construct a `HilbertScanCore` with a `heapless::Vec` having exactly as
+    /// many elements as required by [`num_levels_for_size`]`(size)`.
+    ///
+    /// Requires the `heapless` feature.
+    ///
+    /// ```
+    /// use zhang_hilbert::HilbertScanHeapless;
+    /// let mut scan = HilbertScanHeapless::<u32, 8>::new_heapless([100, 30]);
+    /// assert_eq!(scan.level_states().len(), 5);
+    /// assert_eq!(scan.next(), Some([0, 0]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_new_heapless`] returns an
+    /// error.
+    ///
+    /// [`try_new_heapless`]: HilbertScanCore::try_new_heapless
+    pub fn new_heapless(size: [T; 2]) -> Self {
+        Self::try_new_heapless(size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `HilbertScanCore` with a `heapless::Vec`, returning an
+    /// error if the capacity `N` is too small for `size` or `size` is not
+    /// supported.
+    pub fn try_new_heapless(size: [T; 2]) -> Result<Self, ScanError> {
+        Self::try_with_level_state_storage(new_heapless_level_states(size), size)
+    }
+}
+
+/// Create a `heapless::Vec` with as many elements as required by
+/// [`num_levels_for_size`]`(size)`, or as many as it can hold if it's
+/// insufficient.
+#[cfg(feature = "heapless")]
+pub(crate) fn new_heapless_level_states<T: ScanCoord, const N: usize>(
+    size: [T; 2],
+) -> heapless::Vec<LevelState<T>, N> {
+    let mut level_states = heapless::Vec::new();
+    let len = num_levels_for_size(size).min(N);
+    level_states
+        .resize(len, LevelState::new([T::zero(); 2], 0, 0))
+        .unwrap();
+    level_states
+}
+
+impl<'a, T> HilbertScanCore<T, &'a mut [LevelState<T>]>
+where
+    T: ScanCoord,
+{
+    /// Construct a `HilbertScanCore` with uninitialized storage.
+    ///
+    /// The first [`num_levels_for_size`]`(size)` elements of `storage` are
+    /// initialized and borrowed by the iterator. The rest is left untouched,
+    /// so [`set_size`](HilbertScanCore::set_size) can't enlarge the
+    /// rectangle beyond `size`.
+    ///
+    /// ```
+    /// use std::mem::MaybeUninit;
+    /// use zhang_hilbert::{HilbertScanCore, LevelState};
+    /// let mut storage = [MaybeUninit::<LevelState<u32>>::uninit(); 8];
+    /// let scan = HilbertScanCore::with_uninit_storage(&mut storage, [11, 42]);
+    /// assert_eq!(scan.count(), 11 * 42);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_with_uninit_storage`]
+    /// returns an error.
+    ///
+    /// [`try_with_uninit_storage`]: HilbertScanCore::try_with_uninit_storage
+    pub fn with_uninit_storage(
+        storage: &'a mut [MaybeUninit<LevelState<T>>],
+        size: [T; 2],
+    ) -> Self {
+        Self::try_with_uninit_storage(storage, size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `HilbertScanCore` with uninitialized storage, returning an
+    /// error instead of panicking if `storage` is too small or `size` is not
+    /// supported.
+    pub fn try_with_uninit_storage(
+        storage: &'a mut [MaybeUninit<LevelState<T>>],
+        size: [T; 2],
+    ) -> Result<Self, ScanError> {
+        Self::try_with_level_state_storage(init_level_states(storage, size), size)
+    }
+}
+
+/// Initialize the elements of `storage` used for a rectangle of the size
+/// `size` (or all of them if there are fewer) and return them as a slice of
+/// initialized `LevelState`s.
+pub(crate) fn init_level_states<T: ScanCoord>(
+    storage: &mut [MaybeUninit<LevelState<T>>],
+    size: [T; 2],
+) -> &mut [LevelState<T>] {
+    let len = min(num_levels_for_size(size), storage.len());
+    let storage = &mut storage[..len];
+    for slot in storage.iter_mut() {
+        *slot = MaybeUninit::new(LevelState::new([T::zero(); 2], 0, 0));
+    }
+
+    // Safety: `MaybeUninit<LevelState<T>>` has the same layout as
+    // `LevelState<T>`, and every element of `storage` was initialized above.
+    // The returned slice inherits the exclusive borrow of `storage`.
+    unsafe { &mut *(storage as *mut [MaybeUninit<LevelState<T>>] as *mut [LevelState<T>]) }
+}
+
+/// Prints only the active level states, one per line, instead of the whole
+/// `LevelSt`.
+impl<T, LevelSt> fmt::Debug for HilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "HilbertScanCore {{ size: {:?}, origin: {:?}, position: {:?}, remaining: {}, \
+             done: {}, last_level: {}, bb: progress={:?} type={} secondary_neg={} end={} \
+             helper_row={}, rules: {:?}, pow2: {} }}",
+            self.size,
+            self.origin,
+            self.position,
+            self.remaining,
+            self.done,
+            self.last_level,
+            self.bb_progress,
+            self.bb_curve_type,
+            self.bb_secondary_neg,
+            self.bb_end,
+            self.bb_helper_row,
+            self.rules,
+            self.pow2.is_some(),
+        )?;
+        for (i, state) in self.level_states().iter().enumerate() {
+            write!(
+                f,
+                "\n  L{}: size=[{:?},{:?}] type={} prog={:?}",
+                i,
+                state.size[0],
+                state.size[1],
+                state.curve_type(),
+                state.progress()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, LevelSt> std::iter::FusedIterator for HilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+}
+
+// Safety: `size_hint` is derived from `remaining`, which is the exact number
+// of points yet to be produced. `size_hint_for_len` returns `None` as the
+// upper bound only when it doesn't fit in `usize`.
+#[cfg(feature = "nightly")]
+unsafe impl<T, LevelSt> std::iter::TrustedLen for HilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+}
+
+impl<T, LevelSt> Iterator for HilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    type Item = [T; 2];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if self.pow2.is_some() {
+            return Some(self.next_pow2());
+        }
+
+        self.remaining -= 1;
+
+        // The output position
+        let position = self.position;
+
+        // Update the basic block scan state
+        let [mut pri, mut sec] = self.bb_progress;
+        let pri_axis = curve_primary_axis(self.bb_curve_type) as usize;
+        let sec_axis = pri_axis ^ 1;
+        sec = sec - T::one();
+
+        if sec == T::zero() {
+            pri = pri - T::one();
+            sec = self.bb_sec_width;
+            // Zigzag
+            self.bb_secondary_neg = !self.bb_secondary_neg;
+        } else {
+            let sec_pos = &mut self.position[sec_axis];
+            if self.bb_secondary_neg {
+                *sec_pos = *sec_pos - T::one();
+            } else {
+                *sec_pos = *sec_pos + T::one();
+            }
+            self.bb_progress = [pri, sec];
+            return Some(position);
+        }
+
+        if pri == T::zero() {
+            // This block is complete! Find the next block.
+        } else {
+            let pri_pos = &mut self.position[pri_axis];
+            if curve_primary_negative(self.bb_curve_type) != 0 {
+                *pri_pos = *pri_pos - T::one();
+            } else {
+                *pri_pos = *pri_pos + T::one();
+            }
+            self.bb_progress = [pri, sec];
+            return Some(position);
+        }
+
+        self.bb_prev_curve_type = Some(self.bb_curve_type);
+        self.next_block();
+        self.bb_enter_remaining = self.remaining;
         Some(position)
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.nth_u64(n as u64)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint_for_len(self.remaining)
+    }
+
+    fn fold<B, F>(mut self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        let mut acc = init;
+
+        if self.pow2.is_some() {
+            while !self.done {
+                acc = f(acc, self.next_pow2());
+            }
+            return acc;
+        }
+
+        while !self.done {
+            // Produce the points in the current column except the last one
+            // in a tight loop
+            acc = self.fold_in_column(T::max_value(), acc, &mut f).1;
+
+            // `next` takes care of the last point and what follows it
+            if let Some(p) = self.next() {
+                acc = f(acc, p);
+            }
+        }
+
+        acc
+    }
+
+    fn count(self) -> usize {
+        usize::try_from(self.remaining).expect("count overflow")
+    }
+
+    fn last(self) -> Option<Self::Item> {
+        if self.done {
+            None
+        } else {
+            self.exit_point()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -804,13 +3736,64 @@ mod tests {
 
     #[test]
     fn log2_sanity() {
-        assert_eq!(log2_floor(1), 0);
-        assert_eq!(log2_floor(2), 1);
-        assert_eq!(log2_floor(3), 1);
-        assert_eq!(log2_floor(256), 8);
-        assert_eq!(log2_floor(300), 8);
-        assert_eq!(log2_floor(511), 8);
-        assert_eq!(log2_floor(512), 9);
+        assert_eq!(log2_floor(1u32), 0);
+        assert_eq!(log2_floor(2u32), 1);
+        assert_eq!(log2_floor(3u32), 1);
+        assert_eq!(log2_floor(256u32), 8);
+        assert_eq!(log2_floor(300u32), 8);
+        assert_eq!(log2_floor(511u32), 8);
+        assert_eq!(log2_floor(512u32), 9);
+    }
+
+    #[test]
+    fn log2_sanity_u64() {
+        assert_eq!(log2_floor(1u64), 0);
+        assert_eq!(log2_floor(u64::from(u32::MAX)), 31);
+        assert_eq!(log2_floor(1u64 << 32), 32);
+        assert_eq!(log2_floor((1u64 << 40) + 5), 40);
+        assert_eq!(log2_floor(u64::MAX), 63);
+    }
+
+    #[test]
+    fn log2_sanity_u128() {
+        assert_eq!(log2_floor(1u128), 0);
+        assert_eq!(log2_floor(u128::from(u64::MAX)), 63);
+        assert_eq!(log2_floor(1u128 << 64), 64);
+        assert_eq!(log2_floor((1u128 << 100) + 5), 100);
+        assert_eq!(log2_floor(u128::MAX), 127);
+    }
+
+    #[test]
+    fn num_levels_u128() {
+        assert_eq!(num_levels_for_size([1u128 << 100, 1 << 100]), 101);
+        assert_eq!(num_levels_for_size([u128::MAX, u128::MAX]), 128);
+        assert_eq!(num_levels_for_size([u128::MAX, 3]), 2);
+        assert_eq!(division_l1(u128::MAX), 1 << 127);
+        assert_eq!(division_l1(3u128 << 120), 1 << 121);
+        assert_eq!(division_l1_paper(3u128 << 120), 1 << 120);
+        assert_eq!(
+            storage_requirement([u128::MAX, u128::MAX]),
+            StorageReq {
+                sufficient: 128,
+                exact: 128
+            }
+        );
+        // Doesn't overflow
+        let sub_size = extra_division_subblock_size([u128::MAX - 1, u128::MAX], 0, 0, false);
+        assert!(sub_size.iter().all(|&x| x > 0 && x < u128::MAX));
+    }
+
+    #[test]
+    fn num_levels_u64() {
+        assert_eq!(num_levels_for_size([1u64 << 40, 1 << 40]), 41);
+        assert_eq!(num_levels_for_size([u64::MAX, u64::MAX]), 64);
+        assert_eq!(num_levels_for_size([u64::MAX, 2]), 2);
+        assert_eq!(
+            num_levels_for_size([(1u64 << 33) + 1, (1 << 33) + 1]),
+            num_levels_for_size_u64([(1 << 33) + 1, (1 << 33) + 1])
+        );
+        assert_eq!(division_l1(u64::MAX), 1 << 63);
+        assert_eq!(division_l1((1u64 << 40) + 1), 1 << 39);
     }
 
     #[test]
@@ -820,7 +3803,74 @@ mod tests {
         // the definition on the paper. This deviation improves the performance but
         // it's probable (not drastic, I believe) that it could slightly affect
         // the output quality
-        // assert_eq!(division_l1(24u32), 8);
+        assert_eq!(division_l1(24u32), 16);
         assert_eq!(division_l1(32u32), 16);
+
+        assert_eq!(division_l1_paper(18u32), 8);
+        assert_eq!(division_l1_paper(24u32), 8);
+        assert_eq!(division_l1_paper(32u32), 16);
+        assert_eq!(DivisionRule::Fast.l1(24u32), 16);
+        assert_eq!(DivisionRule::PaperExact.l1(24u32), 8);
+    }
+
+    #[test]
+    fn division_rules_differ_only_on_3_pow2() {
+        for size in 2u32..4096 {
+            let is_3_pow2 = size % 3 == 0 && (size / 3).is_power_of_two();
+            assert_eq!(
+                DivisionRule::Fast.l1(size) != DivisionRule::PaperExact.l1(size),
+                is_3_pow2,
+                "{}",
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn pow2_matches_generic() {
+        for (order, axis) in (1..=8).flat_map(|o| [(o, PrimaryAxis::X), (o, PrimaryAxis::Y)]) {
+            let size = [1u32 << order; 2];
+            let fast = HilbertScanCore::<u32, [LevelState<u32>; 32]>::with_primary_axis(
+                Default::default(),
+                size,
+                axis,
+            );
+            assert!(fast.pow2.is_some());
+            let mut generic = fast.clone();
+            generic.pow2 = None;
+
+            let expected: Vec<_> = generic.clone().collect();
+            assert_eq!(fast.clone().collect::<Vec<_>>(), expected, "{:?}", size);
+
+            for k in (0..expected.len() + 1).step_by(13) {
+                assert_eq!(fast.clone().nth(k), expected.get(k).cloned(), "{}", k);
+            }
+        }
+    }
+
+    #[test]
+    fn pow2_curve_type_matches_generic() {
+        for (order, axis) in (0..=6).flat_map(|o| [(o, PrimaryAxis::X), (o, PrimaryAxis::Y)]) {
+            let size = [1u32 << order; 2];
+            let mut fast = HilbertScanCore::<u32, [LevelState<u32>; 32]>::with_primary_axis(
+                Default::default(),
+                size,
+                axis,
+            );
+            let mut generic = fast.clone();
+            generic.pow2 = None;
+
+            assert_eq!(fast.current_curve_type(), generic.current_curve_type());
+            while let Some(p) = fast.next() {
+                assert_eq!(generic.next(), Some(p));
+                assert_eq!(
+                    fast.current_curve_type(),
+                    generic.current_curve_type(),
+                    "{:?} {:?}",
+                    size,
+                    p
+                );
+            }
+        }
     }
 }
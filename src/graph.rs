@@ -0,0 +1,33 @@
+//! Exporting the scan as a graph
+use crate::{reorder::num_cells, ArbHilbertScan32};
+
+/// Get the edges of the path graph formed by the scan of [`ArbHilbertScan32`],
+/// i.e., the pairs of consecutive points in the scan order.
+///
+/// Every edge connects two adjacent cells. A scan of `n` cells produces
+/// `n - 1` edges, which form a Hamiltonian path of the grid graph. This can
+/// be used as a spanning tree for generating a maze or as an initial tour
+/// for the traveling salesman problem.
+///
+/// ```
+/// let edges = zhang_hilbert::edge_list([2, 2]);
+/// assert_eq!(
+///     edges,
+///     [([0, 0], [0, 1]), ([0, 1], [1, 1]), ([1, 1], [1, 0])],
+/// );
+/// ```
+///
+/// # Panics
+///
+/// Panics if the number of cells does not fit in `u32`.
+pub fn edge_list(size: [u32; 2]) -> Vec<([u32; 2], [u32; 2])> {
+    let mut edges = Vec::with_capacity(num_cells(size).saturating_sub(1));
+    let mut scan = ArbHilbertScan32::new(size);
+    if let Some(mut last) = scan.next() {
+        for p in scan {
+            edges.push((last, p));
+            last = p;
+        }
+    }
+    edges
+}
@@ -0,0 +1,87 @@
+//! Sort keys following the scan order
+use std::cmp::Ordering;
+
+use crate::{
+    coord::ScanCoord,
+    core::{num_levels_for_size, HilbertScanCore, LevelState},
+};
+
+/// A point in a rectangle, ordered by its position in the scan produced by
+/// [`HilbertScanCore`].
+///
+/// This can be used as the key of a `BTreeMap` to make the points close to
+/// each other in the rectangle likely close in the map:
+///
+/// ```
+/// use std::collections::BTreeSet;
+/// use zhang_hilbert::{HilbertKey, HilbertScan32};
+/// let size = [11, 42];
+/// let set: BTreeSet<_> = [[3, 9], [10, 41], [0, 0], [5, 20]]
+///     .iter()
+///     .map(|&p| HilbertKey::new(size, p))
+///     .collect();
+/// let points: Vec<_> = set.iter().map(HilbertKey::point).collect();
+/// let expected: Vec<_> = HilbertScan32::new(size)
+///     .filter(|p| points.contains(p))
+///     .collect();
+/// assert_eq!(points, expected);
+/// ```
+///
+/// Keys are compared by their indices in the scan. Keys for different
+/// rectangle sizes are ordered by their sizes if their indices are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HilbertKey<T> {
+    rank: u64,
+    size: [T; 2],
+    point: [T; 2],
+}
+
+impl<T: ScanCoord> HilbertKey<T> {
+    /// Construct a `HilbertKey` for `point` in a rectangle of the size `size`.
+    ///
+    /// This computes the index of `point` by [`HilbertScanCore::rank`],
+    /// allocating the working area on the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `point` is outside the rectangle.
+    pub fn new(size: [T; 2], point: [T; 2]) -> Self {
+        let level_states = vec![LevelState::new([T::zero(); 2], 0, 0); num_levels_for_size(size)];
+        let mut scan = HilbertScanCore::with_level_state_storage(level_states, size);
+        Self {
+            rank: scan.rank(point),
+            size,
+            point,
+        }
+    }
+}
+
+impl<T: Copy> HilbertKey<T> {
+    /// Get the size of the rectangle.
+    pub fn size(&self) -> [T; 2] {
+        self.size
+    }
+
+    /// Get the point.
+    pub fn point(&self) -> [T; 2] {
+        self.point
+    }
+
+    /// Get the index of the point in the scan.
+    pub fn rank(&self) -> u64 {
+        self.rank
+    }
+}
+
+impl<T: Ord> Ord for HilbertKey<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // `point` is determined by `rank` and `size`
+        (self.rank, &self.size).cmp(&(other.rank, &other.size))
+    }
+}
+
+impl<T: Ord> PartialOrd for HilbertKey<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
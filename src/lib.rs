@@ -4,6 +4,18 @@
 //!
 //! ![](https://ipfs.io/ipfs/QmUbNnFkcyHQrg3CpNf3ykVq6dm7vG7CGzU8tryzWvXrEf/thecurve.svg)
 //!
+//! Scans are configured and constructed by [`ScanBuilder`]:
+//!
+//! ```
+//! use zhang_hilbert::ScanBuilder;
+//! for [x, y] in ScanBuilder::new([11u32, 42]).build() {
+//!     assert!(x >= 0 && y >= 0 && x < 11 && y < 42);
+//!     println!("{:?}", [x, y]);
+//! }
+//! ```
+//!
+//! The iterator types can also be constructed directly:
+//!
 //! ```
 //! use zhang_hilbert::ArbHilbertScan32;
 //! for [x, y] in ArbHilbertScan32::new([11, 42]) {
@@ -19,7 +31,7 @@
 //! This implementation uses a different curve-type selection rule for the
 //! last `E_B(E, O)` block in a `E_R(E, O)` rectangle. This makes the leaving
 //! point fixed at a known point in more cases, making the output suitable for
-//! tiling.
+//! tiling. The original rule can be selected by [`CurveVariant::Original`].
 //!
 //! ```text
 //! cargo run --example hilbertgen -- -a zhang 6 7
@@ -71,17 +83,155 @@
 //!
 //! The `division` internal function was modified for efficient implementation.
 //! As a result, the function produces an different output for the input `3⋅2ⁿ`.
+//! The original definition can be selected by [`DivisionRule::PaperExact`].
 //!
+//! # Cargo features
+//!
+//! - `serde` implements `Serialize` and `Deserialize` for [`ScanSnapshot`]
+//!   and [`ArbScanSnapshot`], allowing in-progress scans to be serialized.
+//! - `log` emits `trace!` events at block transitions and part switches,
+//!   which is useful for diagnosing the decisions made by the algorithm.
+//! - `heapless` adds `HilbertScanHeapless` and `ArbHilbertScanHeapless`,
+//!   which store the level states in a `heapless::Vec` of a fixed capacity
+//!   without using the heap.
+//! - `mint` adds `points_mint`, which yields the points as
+//!   `mint::Point2`s for interoperability with other math libraries.
+//! - `futures` adds `to_stream`, which wraps a scan in a
+//!   `futures::Stream`.
+//! - `nightly` implements `TrustedLen` for [`HilbertScanCore`] and
+//!   [`ArbHilbertScanCore`]. Requires a nightly compiler.
+//!
+#![cfg_attr(feature = "nightly", feature(trusted_len))]
+
+/// Emit a `trace!` event if the `log` feature is enabled. Expands to nothing
+/// otherwise.
+macro_rules! trace {
+    ($($args:tt)*) => {
+        #[cfg(feature = "log")]
+        log::trace!($($args)*);
+    };
+}
+
+mod adapter;
 mod arb;
+mod builder;
+mod checkpoint;
+mod closed;
+mod coord;
 mod core;
+mod graph;
+mod key;
+mod masked;
+mod query;
+mod rect;
+mod reorder;
+mod scan;
+mod sink;
+mod snapshot;
+mod stats;
+mod tiling;
+
+pub use self::{
+    adapter::*, arb::*, builder::*, checkpoint::*, closed::*, coord::*, core::*, graph::*, key::*,
+    masked::*, query::*, rect::*, reorder::*, scan::*, sink::*, snapshot::SnapshotError, stats::*,
+    tiling::*,
+};
 
-pub use self::{arb::*, core::*};
+/// `HilbertScanCore` with an array-based working area of `LEVELS` elements.
+/// Use [`HilbertScanCore::new_array`] to construct it.
+///
+/// [`max_levels_for_bits`] and [`levels_for_max_dim`] can be used to find
+/// `LEVELS` at compile time.
+pub type HilbertScan<T, const LEVELS: usize> = HilbertScanCore<T, [LevelState<T>; LEVELS]>;
+
+/// `ArbHilbertScanCore` with an array-based working area of `LEVELS`
+/// elements. Use [`ArbHilbertScanCore::new_array`] to construct it.
+pub type ArbHilbertScan<T, const LEVELS: usize> = ArbHilbertScanCore<T, [LevelState<T>; LEVELS]>;
 
 /// `HilbertScanCore` with an array-based working area.
-pub type HilbertScan32 = HilbertScanCore<u32, [LevelState<u32>; 32]>;
+pub type HilbertScan32 = HilbertScan<u32, 32>;
 
 /// `ArbHilbertScan32` with an array-based working area.
-pub type ArbHilbertScan32 = ArbHilbertScanCore<u32, [LevelState<u32>; 32]>;
+pub type ArbHilbertScan32 = ArbHilbertScan<u32, 32>;
+
+/// `HilbertScanCore` with an array-based working area for `u64` coordinates.
+/// Use [`HilbertScanCore::new_array`] to construct it.
+pub type HilbertScan64 = HilbertScan<u64, 64>;
+
+/// `ArbHilbertScanCore` with an array-based working area for `u64`
+/// coordinates. Use [`ArbHilbertScanCore::new_array`] to construct it.
+pub type ArbHilbertScan64 = ArbHilbertScan<u64, 64>;
+
+/// `HilbertScanCore` with an array-based working area for `u128`
+/// coordinates. Use [`HilbertScanCore::new_array`] to construct it.
+///
+/// The number of points must still fit in `u64`, so the scans of rectangles
+/// having a side longer than `u64::MAX` can't be constructed. Functions that
+/// don't perform scans, such as [`HilbertScanCore::end_point`], accept any
+/// size.
+pub type HilbertScan128 = HilbertScan<u128, 128>;
+
+/// `ArbHilbertScanCore` with an array-based working area for `u128`
+/// coordinates. Use [`ArbHilbertScanCore::new_array`] to construct it.
+///
+/// See [`HilbertScan128`] for the limitation on the size.
+pub type ArbHilbertScan128 = ArbHilbertScan<u128, 128>;
+
+/// `HilbertScanCore` with an array-based working area for `usize`
+/// coordinates, which is large enough for any size on the target. Use
+/// [`HilbertScanCore::new_array`] to construct it.
+pub type HilbertScanUsize = HilbertScan<usize, { usize::BITS as usize }>;
+
+/// `ArbHilbertScanCore` with an array-based working area for `usize`
+/// coordinates, which is large enough for any size on the target. Use
+/// [`ArbHilbertScanCore::new_array`] to construct it.
+pub type ArbHilbertScanUsize = ArbHilbertScan<usize, { usize::BITS as usize }>;
+
+/// `HilbertScanCore` with an array-based working area for `u16` coordinates.
+pub type HilbertScan16 = HilbertScan<u16, 16>;
+
+/// `ArbHilbertScanCore` with an array-based working area for `u16`
+/// coordinates.
+pub type ArbHilbertScan16 = ArbHilbertScan<u16, 16>;
+
+/// `HilbertScanCore` with an array-based working area for `u8` coordinates.
+pub type HilbertScan8 = HilbertScan<u8, 8>;
+
+/// `ArbHilbertScanCore` with an array-based working area for `u8`
+/// coordinates.
+pub type ArbHilbertScan8 = ArbHilbertScan<u8, 8>;
+
+/// `ScanAny` with an array-based working area.
+pub type ScanAny32 = ScanAny<u32, [LevelState<u32>; 32]>;
+
+/// `ScanAny` with a `Vec`-based working area, which is constructed by
+/// [`ScanBuilder::build`].
+pub type ScanAnyVec<T> = ScanAny<T, Vec<LevelState<T>>>;
+
+/// `ClosedHilbertScan` with array-based working areas.
+pub type ClosedHilbertScan32 = ClosedHilbertScan<u32, [LevelState<u32>; 32]>;
+
+/// `HilbertScanCore` with a `Vec`-based working area. Use
+/// [`HilbertScanCore::new_vec`] to construct it.
+pub type HilbertScanVec<T> = HilbertScanCore<T, Vec<LevelState<T>>>;
+
+/// `ArbHilbertScanCore` with a `Vec`-based working area. Use
+/// [`ArbHilbertScanCore::new_vec`] to construct it.
+pub type ArbHilbertScanVec<T> = ArbHilbertScanCore<T, Vec<LevelState<T>>>;
+
+/// `HilbertScanCore` with a `heapless::Vec`-based working area of capacity
+/// `N`. Use [`HilbertScanCore::new_heapless`] to construct it. Requires the
+/// `heapless` feature.
+#[cfg(feature = "heapless")]
+pub type HilbertScanHeapless<T, const N: usize> =
+    HilbertScanCore<T, heapless::Vec<LevelState<T>, N>>;
+
+/// `ArbHilbertScanCore` with a `heapless::Vec`-based working area of
+/// capacity `N`. Use [`ArbHilbertScanCore::new_heapless`] to construct it.
+/// Requires the `heapless` feature.
+#[cfg(feature = "heapless")]
+pub type ArbHilbertScanHeapless<T, const N: usize> =
+    ArbHilbertScanCore<T, heapless::Vec<LevelState<T>, N>>;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,197 @@
+//! Scanning the cells selected by a mask
+use std::{borrow::BorrowMut, iter::FusedIterator};
+
+use crate::{
+    adapter::Transformed,
+    arb::ArbHilbertScanCore,
+    builder::ScanAny,
+    coord::ScanCoord,
+    core::{area, HilbertScanCore, LevelState, ScanError},
+};
+
+/// A scan of a rectangle whose size is known.
+pub trait ScanSize {
+    /// The coordinate type.
+    type Coord;
+
+    /// Get the size of the rectangle being scanned.
+    fn size(&self) -> [Self::Coord; 2];
+}
+
+impl<T, LevelSt> ScanSize for HilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    type Coord = T;
+
+    fn size(&self) -> [T; 2] {
+        HilbertScanCore::size(self)
+    }
+}
+
+impl<T, LevelSt> ScanSize for ArbHilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    type Coord = T;
+
+    fn size(&self) -> [T; 2] {
+        ArbHilbertScanCore::size(self)
+    }
+}
+
+impl<T, LevelSt> ScanSize for ScanAny<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    type Coord = T;
+
+    fn size(&self) -> [T; 2] {
+        ScanAny::size(self)
+    }
+}
+
+impl<I, T: ScanCoord> ScanSize for Transformed<I, T> {
+    type Coord = T;
+
+    fn size(&self) -> [T; 2] {
+        Transformed::size(self)
+    }
+}
+
+/// An iterator adapter that yields only the cells selected by a mask, along
+/// with their indices among the selected cells.
+///
+/// The mask is a slice of `bool`s in the row-major order, i.e.,
+/// `mask[y * w + x]` selects the cell `[x, y]`. The indices ("compacted
+/// ranks") are consecutive from `0`, and [`len`](ExactSizeIterator::len)
+/// initially equals the number of selected cells.
+///
+/// The underlying scan still visits every cell of the rectangle, including
+/// the runs of cells not selected by the mask, because the mask doesn't tell
+/// which of the upcoming cells in the scan order are selected.
+///
+/// ```
+/// use zhang_hilbert::{HilbertScan32, MaskedScan};
+/// // Exclude the cell `[1, 0]`
+/// let mask = [true, false, true, true];
+/// let scan = MaskedScan::new(HilbertScan32::new([2, 2]), &mask).unwrap();
+/// assert_eq!(scan.len(), 3);
+/// let points: Vec<_> = scan.collect();
+/// assert_eq!(points, [(0, [0, 0]), (1, [0, 1]), (2, [1, 1])]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MaskedScan<'a, I> {
+    inner: I,
+    mask: &'a [bool],
+    width: usize,
+    /// The compacted rank of the next selected cell
+    rank: u64,
+    /// The number of selected cells yet to be produced
+    remaining: usize,
+}
+
+impl<'a, I> MaskedScan<'a, I>
+where
+    I: Iterator + ScanSize,
+    I::Coord: ScanCoord,
+{
+    /// Construct a `MaskedScan`. `mask` must have one element for each cell
+    /// of the rectangle being scanned by `inner`. Returns
+    /// [`ScanError::MaskSizeMismatch`] otherwise.
+    ///
+    /// `inner` must be at its start, i.e., its `size_hint` must report every
+    /// cell of the rectangle. Returns [`ScanError::ScanInProgress`]
+    /// otherwise.
+    ///
+    /// ```
+    /// use zhang_hilbert::{HilbertScan32, MaskedScan, ScanError};
+    /// let mut scan = HilbertScan32::new([2, 2]);
+    /// scan.next();
+    /// assert_eq!(
+    ///     MaskedScan::new(scan, &[true; 4]).unwrap_err(),
+    ///     ScanError::ScanInProgress,
+    /// );
+    /// ```
+    pub fn new(inner: I, mask: &'a [bool]) -> Result<Self, ScanError> {
+        let size = inner.size();
+        let num_cells = area(size);
+        if num_cells != mask.len() as u64 {
+            return Err(ScanError::MaskSizeMismatch {
+                required: num_cells,
+                provided: mask.len(),
+            });
+        }
+        // `remaining` counts the selected cells of the whole rectangle
+        if inner.size_hint() != (mask.len(), Some(mask.len())) {
+            return Err(ScanError::ScanInProgress);
+        }
+
+        Ok(Self {
+            inner,
+            mask,
+            // `num_cells` fits in `usize`, and so does the width unless the
+            // rectangle is empty
+            width: size[0].to_usize().unwrap_or(0),
+            rank: 0,
+            remaining: mask.iter().filter(|&&x| x).count(),
+        })
+    }
+
+    /// Get a reference to the wrapped iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<'a, I, T> Iterator for MaskedScan<'a, I>
+where
+    I: Iterator<Item = [T; 2]> + ScanSize<Coord = T>,
+    T: ScanCoord,
+{
+    type Item = (u64, [T; 2]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        for p in &mut self.inner {
+            let [x, y] = p.map(|x| x.to_usize().unwrap());
+            if self.mask[y * self.width + x] {
+                let rank = self.rank;
+                self.rank += 1;
+                self.remaining -= 1;
+                return Some((rank, p));
+            }
+        }
+        // `inner` produced fewer points than its `size_hint` reported
+        self.remaining = 0;
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, I, T> ExactSizeIterator for MaskedScan<'a, I>
+where
+    I: Iterator<Item = [T; 2]> + ScanSize<Coord = T>,
+    T: ScanCoord,
+{
+}
+
+impl<'a, I, T> FusedIterator for MaskedScan<'a, I>
+where
+    I: Iterator<Item = [T; 2]> + ScanSize<Coord = T>,
+    T: ScanCoord,
+{
+}
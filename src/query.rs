@@ -0,0 +1,227 @@
+//! Queries on the scan order
+use std::{convert::TryFrom, fmt, iter::FusedIterator};
+
+use crate::{
+    coord::ScanCoord,
+    core::{size_hint_for_len, HilbertScanCore},
+    HilbertScanVec,
+};
+
+/// Find the ranges of the indices of the points inside a query rectangle,
+/// which is located at `query_origin` and has the size `query_size`.
+///
+/// Returns sorted, non-overlapping, and non-adjacent ranges `[start, end)`
+/// of the indices in the scan produced by [`HilbertScanCore`]. Their union
+/// is exactly the set of the points inside the query rectangle. The part of
+/// the query rectangle outside the scanned rectangle is ignored.
+///
+/// The blocks entirely inside or outside the query rectangle are not
+/// visited, so this doesn't iterate through the points.
+///
+/// ```
+/// use zhang_hilbert::cover_intervals;
+/// // The scan of a 4×4 square starts with the 2×2 square at the origin
+/// assert_eq!(cover_intervals([4u32, 4], [0, 0], [2, 2]), [(0, 4)]);
+/// assert_eq!(cover_intervals([4u32, 4], [0, 0], [1, 4]), [(0, 1), (3, 6)]);
+/// ```
+pub fn cover_intervals<T>(size: [T; 2], query_origin: [T; 2], query_size: [T; 2]) -> Vec<(u64, u64)>
+where
+    T: ScanCoord,
+{
+    let mut out = Vec::new();
+    if size[0] == T::zero() || size[1] == T::zero() {
+        return out;
+    }
+
+    let query_end = [0, 1].map(|i| {
+        query_origin[i]
+            .checked_add(query_size[i])
+            .unwrap_or_else(T::max_value)
+    });
+    HilbertScanCore::new_vec(size).cover_intervals(query_origin, query_end, &mut out);
+    out
+}
+
+/// Produce the points inside a window in the order of the scan produced by
+/// [`HilbertScanCore`]. The window is located at `window_origin` and has the
+/// size `window_size`. The part of the window outside the scanned rectangle
+/// is ignored.
+///
+/// Unlike filtering the full scan, this skips the blocks outside the window
+/// (see [`cover_intervals`]), so the cost depends on the window rather than
+/// the scanned rectangle.
+///
+/// ```
+/// use zhang_hilbert::{windowed, HilbertScan32};
+/// let points: Vec<_> = windowed([11u32, 42], [3, 5], [4, 4]).collect();
+/// let expected: Vec<_> = HilbertScan32::new([11, 42])
+///     .filter(|&[x, y]| (3..7).contains(&x) && (5..9).contains(&y))
+///     .collect();
+/// assert_eq!(points, expected);
+/// ```
+pub fn windowed<T>(scan_size: [T; 2], window_origin: [T; 2], window_size: [T; 2]) -> Windowed<T>
+where
+    T: ScanCoord,
+{
+    let intervals = cover_intervals(scan_size, window_origin, window_size);
+    let remaining = intervals.iter().map(|&(start, end)| end - start).sum();
+    Windowed {
+        scan: HilbertScanVec::new_vec(scan_size),
+        intervals: intervals.into_iter(),
+        index: 0,
+        end: 0,
+        remaining,
+    }
+}
+
+/// An iterator that produces the points inside a window in the scan order.
+///
+/// This `struct` is created by [`windowed`].
+#[derive(Clone)]
+pub struct Windowed<T> {
+    scan: HilbertScanVec<T>,
+    /// The index ranges yet to be entered
+    intervals: std::vec::IntoIter<(u64, u64)>,
+    /// The index of the point to be produced by `scan.next()`
+    index: u64,
+    /// The end of the current index range
+    end: u64,
+    remaining: u64,
+}
+
+impl<T: ScanCoord> Windowed<T> {
+    /// Produce each point along with its index in the full scan.
+    ///
+    /// ```
+    /// use zhang_hilbert::windowed;
+    /// let points: Vec<_> = windowed([4u32, 4], [0, 0], [1, 4]).ranked().collect();
+    /// assert_eq!(points, [(0, [0, 0]), (3, [0, 1]), (4, [0, 2]), (5, [0, 3])]);
+    /// ```
+    pub fn ranked(self) -> RankedWindowed<T> {
+        RankedWindowed { inner: self }
+    }
+
+    fn next_ranked(&mut self) -> Option<(u64, [T; 2])> {
+        let skip = if self.index == self.end {
+            let (start, end) = self.intervals.next()?;
+            let skip = start - self.index;
+            self.index = start;
+            self.end = end;
+            skip
+        } else {
+            0
+        };
+
+        let point = self.scan.nth_u64(skip)?;
+        let index = self.index;
+        self.index += 1;
+        self.remaining -= 1;
+        Some((index, point))
+    }
+}
+
+impl<T: ScanCoord + fmt::Debug> fmt::Debug for Windowed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Windowed")
+            .field("scan", &self.scan)
+            .field("intervals", &self.intervals)
+            .field("index", &self.index)
+            .field("end", &self.end)
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
+
+impl<T: ScanCoord> Iterator for Windowed<T> {
+    type Item = [T; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_ranked().map(|(_, point)| point)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint_for_len(self.remaining)
+    }
+}
+
+impl<T: ScanCoord> FusedIterator for Windowed<T> {}
+
+/// An iterator that produces the points inside a window in the scan order,
+/// along with their indices in the full scan.
+///
+/// This `struct` is created by [`Windowed::ranked`].
+#[derive(Clone)]
+pub struct RankedWindowed<T> {
+    inner: Windowed<T>,
+}
+
+impl<T: ScanCoord + fmt::Debug> fmt::Debug for RankedWindowed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RankedWindowed")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<T: ScanCoord> Iterator for RankedWindowed<T> {
+    type Item = (u64, [T; 2]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_ranked()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T: ScanCoord> FusedIterator for RankedWindowed<T> {}
+
+/// Get the point following `point` in the scan produced by
+/// [`HilbertScanCore`]. Returns `None` if `point` is the last one.
+///
+/// This takes time proportional to the number of levels.
+///
+/// ```
+/// use zhang_hilbert::{successor, HilbertScan32};
+/// let mut scan = HilbertScan32::new([11, 42]);
+/// let p = scan.nth(100).unwrap();
+/// assert_eq!(successor([11, 42], p), scan.next());
+/// ```
+///
+/// # Panics
+///
+/// Panics if `point` is outside the rectangle.
+pub fn successor<T>(size: [T; 2], point: [T; 2]) -> Option<[T; 2]>
+where
+    T: ScanCoord,
+{
+    let mut scan = HilbertScanCore::new_vec(size);
+    scan.rank(point);
+    scan.nth(1)
+}
+
+/// Get the point preceding `point` in the scan produced by
+/// [`HilbertScanCore`]. Returns `None` if `point` is the first one.
+///
+/// This takes time proportional to the number of levels.
+///
+/// ```
+/// use zhang_hilbert::{predecessor, HilbertScan32};
+/// let mut scan = HilbertScan32::new([11, 42]);
+/// let p = scan.nth(100).unwrap();
+/// assert_eq!(predecessor([11, 42], scan.next().unwrap()), Some(p));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `point` is outside the rectangle.
+pub fn predecessor<T>(size: [T; 2], point: [T; 2]) -> Option<[T; 2]>
+where
+    T: ScanCoord,
+{
+    let mut scan = HilbertScanCore::new_vec(size);
+    let index = scan.rank(point).checked_sub(1)?;
+    scan.reset();
+    scan.nth(usize::try_from(index).ok()?)
+}
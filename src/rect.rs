@@ -0,0 +1,103 @@
+//! Rectangle type
+use crate::{coord::ScanCoord, core::area, ArbHilbertScan32, HilbertScan32};
+
+/// The size of a rectangle to be scanned.
+///
+/// This is a convenience type for constructing scans without naming the
+/// iterator types:
+///
+/// ```
+/// use zhang_hilbert::Rect;
+/// for [x, y] in Rect::new(11, 42) {
+///     assert!(x < 11 && y < 42);
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect<T> {
+    pub width: T,
+    pub height: T,
+}
+
+impl<T: ScanCoord> Rect<T> {
+    /// Construct a `Rect`.
+    pub fn new(width: T, height: T) -> Self {
+        Self { width, height }
+    }
+
+    /// Get the size as an array `[width, height]`.
+    pub fn size(&self) -> [T; 2] {
+        [self.width, self.height]
+    }
+
+    /// Get the number of cells in the rectangle, saturating at `u64::MAX`.
+    pub fn area(&self) -> u64 {
+        area(self.size())
+    }
+}
+
+impl Rect<u32> {
+    /// Construct a [`HilbertScan32`] for this rectangle.
+    pub fn hilbert(self) -> HilbertScan32 {
+        HilbertScan32::new(self.size())
+    }
+
+    /// Construct an [`ArbHilbertScan32`] for this rectangle.
+    ///
+    /// ```
+    /// use zhang_hilbert::{ArbHilbertScan32, Rect};
+    /// assert!(Rect::new(40, 7).arb_hilbert().eq(ArbHilbertScan32::new([40, 7])));
+    /// ```
+    pub fn arb_hilbert(self) -> ArbHilbertScan32 {
+        ArbHilbertScan32::new(self.size())
+    }
+}
+
+/// Produces [`ArbHilbertScan32`].
+impl IntoIterator for Rect<u32> {
+    type Item = [u32; 2];
+    type IntoIter = ArbHilbertScan32;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.arb_hilbert()
+    }
+}
+
+impl<T> From<[T; 2]> for Rect<T> {
+    fn from([width, height]: [T; 2]) -> Self {
+        Self { width, height }
+    }
+}
+
+impl<T> From<Rect<T>> for [T; 2] {
+    fn from(rect: Rect<T>) -> Self {
+        [rect.width, rect.height]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degenerate_sizes() {
+        for &size in &[[0, 0], [0, 5], [5, 0]] {
+            let rect = Rect::from(size);
+            assert_eq!(rect.area(), 0);
+            assert_eq!(rect.hilbert().count(), 0);
+            assert_eq!(rect.into_iter().count(), 0);
+        }
+
+        for &size in &[[1, 1], [1, 5], [5, 1]] {
+            let rect = Rect::from(size);
+            assert_eq!(rect.area(), (size[0] * size[1]) as u64);
+            assert!(rect.hilbert().eq(HilbertScan32::new(size)));
+            assert!(rect.into_iter().eq(ArbHilbertScan32::new(size)));
+        }
+    }
+
+    #[test]
+    fn area_saturates() {
+        assert_eq!(Rect::new(u64::MAX, 2).area(), u64::MAX);
+        assert_eq!(Rect::new(100_000u32, 100_000).area(), 10_000_000_000);
+    }
+}
@@ -0,0 +1,190 @@
+//! Reordering row-major data into the curve order
+use crate::{core::area, HilbertScan32};
+
+/// Get the number of cells in a rectangle of the size `size` as `usize`.
+///
+/// # Panics
+///
+/// Panics if the number does not fit in `usize` or `u32`.
+pub(crate) fn num_cells(size: [u32; 2]) -> usize {
+    let len = area(size);
+    assert!(len <= u64::from(u32::MAX), "the rectangle is too large");
+    len as usize
+}
+
+/// Fill `out` with the index of each cell in the order in which
+/// [`HilbertScan32`] visits them. `out` is interpreted in the row-major
+/// order, i.e., `out[y * size[0] + x]` receives the index of the cell
+/// `[x, y]`.
+///
+/// ```
+/// use zhang_hilbert::fill_index_map;
+/// let mut map = [0; 4];
+/// fill_index_map([2, 2], &mut map);
+/// assert_eq!(map, [0, 3, 1, 2]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `out.len()` is not equal to `size[0] * size[1]` or the number
+/// of cells does not fit in `u32`.
+pub fn fill_index_map(size: [u32; 2], out: &mut [u32]) {
+    assert_eq!(out.len(), num_cells(size), "the buffer size is incorrect");
+
+    let width = size[0] as usize;
+    for (i, [x, y]) in HilbertScan32::new(size).enumerate() {
+        out[y as usize * width + x as usize] = i as u32;
+    }
+}
+
+/// Rearrange `data`, which is interpreted in the row-major order, into the
+/// order in which [`HilbertScan32`] visits the cells. After the call,
+/// `data[i]` holds the element of the `i`-th visited cell.
+///
+/// The permutation is applied in place by following its cycles. The only
+/// extra memory used is a bit set with one bit per element.
+///
+/// ```
+/// use zhang_hilbert::reorder_to_curve;
+/// let mut data = ['a', 'b', 'c', 'd'];
+/// reorder_to_curve([2, 2], &mut data);
+/// assert_eq!(data, ['a', 'c', 'd', 'b']);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `data.len()` is not equal to `size[0] * size[1]` or the number
+/// of cells does not fit in `u32`.
+pub fn reorder_to_curve<D: Copy>(size: [u32; 2], data: &mut [D]) {
+    let len = num_cells(size);
+    assert_eq!(data.len(), len, "the buffer size is incorrect");
+
+    let mut cells = CellLookup::new(size);
+    let mut visited = BitSet::new(len);
+
+    for start in 0..len {
+        if visited.contains(start) {
+            continue;
+        }
+
+        // `data[i]` receives the element of the `i`-th visited cell
+        let first = data[start];
+        let mut i = start;
+        loop {
+            visited.insert(i);
+            let cell = cells.get(i);
+            if cell == start {
+                data[i] = first;
+                break;
+            }
+            data[i] = data[cell];
+            i = cell;
+        }
+    }
+}
+
+/// Undo [`reorder_to_curve`]. `data` is interpreted in the order in which
+/// [`HilbertScan32`] visits the cells and is rearranged into the row-major
+/// order.
+///
+/// ```
+/// use zhang_hilbert::reorder_from_curve;
+/// let mut data = ['a', 'c', 'd', 'b'];
+/// reorder_from_curve([2, 2], &mut data);
+/// assert_eq!(data, ['a', 'b', 'c', 'd']);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `data.len()` is not equal to `size[0] * size[1]` or the number
+/// of cells does not fit in `u32`.
+pub fn reorder_from_curve<D: Copy>(size: [u32; 2], data: &mut [D]) {
+    let len = num_cells(size);
+    assert_eq!(data.len(), len, "the buffer size is incorrect");
+
+    let mut cells = CellLookup::new(size);
+    let mut visited = BitSet::new(len);
+
+    for start in 0..len {
+        if visited.contains(start) {
+            continue;
+        }
+
+        // The element `data[i]` is moved to the `i`-th visited cell
+        let mut carried = data[start];
+        let mut i = start;
+        loop {
+            visited.insert(i);
+            i = cells.get(i);
+            std::mem::swap(&mut carried, &mut data[i]);
+            if i == start {
+                break;
+            }
+        }
+    }
+}
+
+/// Finds the row-major index of the `i`-th visited cell by seeking a scan.
+struct CellLookup {
+    scan: HilbertScan32,
+    width: usize,
+}
+
+impl CellLookup {
+    fn new(size: [u32; 2]) -> Self {
+        Self {
+            scan: HilbertScan32::new(size),
+            width: size[0] as usize,
+        }
+    }
+
+    fn get(&mut self, i: usize) -> usize {
+        self.scan.reset();
+        let [x, y] = self.scan.nth(i).unwrap();
+        y as usize * self.width + x as usize
+    }
+}
+
+struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    fn contains(&self, i: usize) -> bool {
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    fn insert(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+}
+
+/// Sort `points` in the order in which [`HilbertScan32`] visits them.
+///
+/// The indices of the points are found by [`HilbertScanCore::rank`]
+/// without performing the whole scan, so this is suitable for sparse sets of
+/// points.
+///
+/// ```
+/// use zhang_hilbert::sort_by_curve;
+/// let mut points = [[1, 1], [0, 0], [1, 0], [0, 1]];
+/// sort_by_curve([2, 2], &mut points);
+/// assert_eq!(points, [[0, 0], [0, 1], [1, 1], [1, 0]]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if any of `points` is outside the rectangle.
+///
+/// [`HilbertScanCore::rank`]: crate::HilbertScanCore::rank
+pub fn sort_by_curve(size: [u32; 2], points: &mut [[u32; 2]]) {
+    let mut scan = HilbertScan32::new(size);
+    // `rank` isn't cheap, so compute it only once for each point
+    points.sort_by_cached_key(|&p| scan.rank(p));
+}
@@ -0,0 +1,321 @@
+//! The `Scan` trait and simple scan orders
+use std::{borrow::BorrowMut, iter::FusedIterator};
+
+use crate::{
+    arb::ArbHilbertScanCore,
+    builder::ScanAny,
+    coord::ScanCoord,
+    core::{area, size_hint_for_len, HilbertScanCore, LevelState},
+    masked::ScanSize,
+};
+
+/// An ordering of all cells of a rectangle.
+///
+/// This makes it possible to write code generic over the scan order, e.g.,
+/// to compare the pseudo-Hilbert scan with [`RowMajorScan`] or
+/// [`BoustrophedonScan`].
+///
+/// ```
+/// use zhang_hilbert::{ArbHilbertScan32, BoustrophedonScan, RowMajorScan, Scan};
+/// // Count the moves between non-adjacent cells
+/// fn num_jumps(scan: impl Scan<Coord = u32>) -> usize {
+///     let mut last = scan.entry_point();
+///     scan.skip(1)
+///         .filter(|&[x, y]| {
+///             let [lx, ly] = last.replace([x, y]).unwrap();
+///             x.max(lx) - x.min(lx) + y.max(ly) - y.min(ly) != 1
+///         })
+///         .count()
+/// }
+/// assert_eq!(num_jumps(RowMajorScan::new([4, 4])), 3);
+/// assert_eq!(num_jumps(BoustrophedonScan::new([4, 4])), 0);
+/// assert_eq!(num_jumps(ArbHilbertScan32::new([4, 4])), 0);
+/// ```
+pub trait Scan: ScanSize + Iterator<Item = [<Self as ScanSize>::Coord; 2]> {
+    /// Get the first point of the whole scan. Returns `None` if the
+    /// rectangle is empty.
+    fn entry_point(&self) -> Option<[Self::Coord; 2]>;
+
+    /// Get the last point of the whole scan without performing the scan.
+    /// Returns `None` if the rectangle is empty.
+    fn exit_point(&self) -> Option<[Self::Coord; 2]>;
+}
+
+impl<T, LevelSt> Scan for HilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    fn entry_point(&self) -> Option<[T; 2]> {
+        HilbertScanCore::entry_point(self)
+    }
+
+    fn exit_point(&self) -> Option<[T; 2]> {
+        HilbertScanCore::exit_point(self)
+    }
+}
+
+impl<T, LevelSt> Scan for ArbHilbertScanCore<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    fn entry_point(&self) -> Option<[T; 2]> {
+        ArbHilbertScanCore::entry_point(self)
+    }
+
+    fn exit_point(&self) -> Option<[T; 2]> {
+        ArbHilbertScanCore::exit_point(self)
+    }
+}
+
+impl<T, LevelSt> Scan for ScanAny<T, LevelSt>
+where
+    LevelSt: BorrowMut<[LevelState<T>]>,
+    T: ScanCoord,
+{
+    fn entry_point(&self) -> Option<[T; 2]> {
+        ScanAny::entry_point(self)
+    }
+
+    fn exit_point(&self) -> Option<[T; 2]> {
+        ScanAny::exit_point(self)
+    }
+}
+
+/// The state shared by [`RowMajorScan`] and [`BoustrophedonScan`], which
+/// visit the rows from bottom to top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rows<T> {
+    size: [T; 2],
+    /// The index of the next point.
+    index: u64,
+    len: u64,
+}
+
+impl<T: ScanCoord> Rows<T> {
+    fn new(size: [T; 2]) -> Self {
+        Self {
+            size,
+            index: 0,
+            len: area(size),
+        }
+    }
+
+    /// Advance by `n + 1` points, returning the position of the last one as
+    /// `[column, row]`, or `None` if the scan has ended.
+    #[inline]
+    fn advance_by(&mut self, n: u64) -> Option<[T; 2]> {
+        let index = self.index.saturating_add(n);
+        if index >= self.len {
+            self.index = self.len;
+            return None;
+        }
+        self.index = index + 1;
+
+        let [index, w] = [u128::from(index), self.size[0].to_u128()];
+        Some([index % w, index / w].map(|x| T::from_u128(x).unwrap()))
+    }
+
+    fn remaining_len(&self) -> u64 {
+        self.len - self.index
+    }
+}
+
+/// An iterator producing the cells of a rectangle in the row-major order,
+/// i.e., each row from left to right, from the bottom row to the top row.
+///
+/// This is the simplest scan order, mainly useful as a baseline for
+/// comparing the pseudo-Hilbert scan against.
+///
+/// ```
+/// use zhang_hilbert::RowMajorScan;
+/// let points: Vec<[u32; 2]> = RowMajorScan::new([2, 2]).collect();
+/// assert_eq!(points, [[0, 0], [1, 0], [0, 1], [1, 1]]);
+/// ```
+///
+/// Unlike the other scans, consecutive points are not adjacent when the
+/// scan moves to the next row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowMajorScan<T> {
+    rows: Rows<T>,
+}
+
+impl<T: ScanCoord> RowMajorScan<T> {
+    /// Construct a `RowMajorScan` for a rectangle of the size `size`.
+    pub fn new(size: [T; 2]) -> Self {
+        Self {
+            rows: Rows::new(size),
+        }
+    }
+
+    /// Get the size of the rectangle being scanned.
+    pub fn size(&self) -> [T; 2] {
+        self.rows.size
+    }
+
+    /// Get the first point of the scan, which is always `[0, 0]`. Returns
+    /// `None` if the rectangle is empty.
+    pub fn entry_point(&self) -> Option<[T; 2]> {
+        top_right(self.rows.size).map(|_| [T::zero(); 2])
+    }
+
+    /// Get the last point of the scan, which is always the top-right corner.
+    /// Returns `None` if the rectangle is empty.
+    pub fn exit_point(&self) -> Option<[T; 2]> {
+        top_right(self.rows.size)
+    }
+
+    /// Get the number of remaining points as `u64`.
+    pub fn remaining_len(&self) -> u64 {
+        self.rows.remaining_len()
+    }
+}
+
+impl<T: ScanCoord> Iterator for RowMajorScan<T> {
+    type Item = [T; 2];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.advance_by(0)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.rows.advance_by(n as u64)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint_for_len(self.remaining_len())
+    }
+}
+
+impl<T: ScanCoord> FusedIterator for RowMajorScan<T> {}
+
+impl<T: ScanCoord> ScanSize for RowMajorScan<T> {
+    type Coord = T;
+
+    fn size(&self) -> [T; 2] {
+        RowMajorScan::size(self)
+    }
+}
+
+impl<T: ScanCoord> Scan for RowMajorScan<T> {
+    fn entry_point(&self) -> Option<[T; 2]> {
+        RowMajorScan::entry_point(self)
+    }
+
+    fn exit_point(&self) -> Option<[T; 2]> {
+        RowMajorScan::exit_point(self)
+    }
+}
+
+/// An iterator producing the cells of a rectangle in the boustrophedon
+/// (serpentine) order, i.e., from the bottom row to the top row, reversing
+/// the direction in every other row.
+///
+/// Every two consecutive points are adjacent. The scan leaves at the
+/// top-right corner if the height is odd, or the top-left corner otherwise.
+///
+/// ```
+/// use zhang_hilbert::BoustrophedonScan;
+/// let points: Vec<[u32; 2]> = BoustrophedonScan::new([2, 2]).collect();
+/// assert_eq!(points, [[0, 0], [1, 0], [1, 1], [0, 1]]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoustrophedonScan<T> {
+    rows: Rows<T>,
+}
+
+impl<T: ScanCoord> BoustrophedonScan<T> {
+    /// Construct a `BoustrophedonScan` for a rectangle of the size `size`.
+    pub fn new(size: [T; 2]) -> Self {
+        Self {
+            rows: Rows::new(size),
+        }
+    }
+
+    /// Get the size of the rectangle being scanned.
+    pub fn size(&self) -> [T; 2] {
+        self.rows.size
+    }
+
+    /// Get the first point of the scan, which is always `[0, 0]`. Returns
+    /// `None` if the rectangle is empty.
+    pub fn entry_point(&self) -> Option<[T; 2]> {
+        top_right(self.rows.size).map(|_| [T::zero(); 2])
+    }
+
+    /// Get the last point of the scan. Returns `None` if the rectangle is
+    /// empty.
+    pub fn exit_point(&self) -> Option<[T; 2]> {
+        top_right(self.rows.size).map(|[x, y]| {
+            if (y & T::one()) == T::zero() {
+                [x, y]
+            } else {
+                [T::zero(), y]
+            }
+        })
+    }
+
+    /// Get the number of remaining points as `u64`.
+    pub fn remaining_len(&self) -> u64 {
+        self.rows.remaining_len()
+    }
+
+    #[inline]
+    fn to_serpentine(&self, [x, y]: [T; 2]) -> [T; 2] {
+        if (y & T::one()) == T::zero() {
+            [x, y]
+        } else {
+            [self.rows.size[0] - T::one() - x, y]
+        }
+    }
+}
+
+impl<T: ScanCoord> Iterator for BoustrophedonScan<T> {
+    type Item = [T; 2];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.advance_by(0).map(|p| self.to_serpentine(p))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.rows
+            .advance_by(n as u64)
+            .map(|p| self.to_serpentine(p))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint_for_len(self.remaining_len())
+    }
+}
+
+impl<T: ScanCoord> FusedIterator for BoustrophedonScan<T> {}
+
+impl<T: ScanCoord> ScanSize for BoustrophedonScan<T> {
+    type Coord = T;
+
+    fn size(&self) -> [T; 2] {
+        BoustrophedonScan::size(self)
+    }
+}
+
+impl<T: ScanCoord> Scan for BoustrophedonScan<T> {
+    fn entry_point(&self) -> Option<[T; 2]> {
+        BoustrophedonScan::entry_point(self)
+    }
+
+    fn exit_point(&self) -> Option<[T; 2]> {
+        BoustrophedonScan::exit_point(self)
+    }
+}
+
+/// Get the top-right corner of a rectangle, or `None` if it's empty.
+fn top_right<T: ScanCoord>([w, h]: [T; 2]) -> Option<[T; 2]> {
+    if w == T::zero() || h == T::zero() {
+        None
+    } else {
+        Some([w - T::one(), h - T::one()])
+    }
+}
@@ -0,0 +1,82 @@
+//! Streaming the points to a `std::io::Write`
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+use crate::ArbHilbertScan32;
+
+/// Write the points of [`ArbHilbertScan32`] to `w` as CSV, one point per
+/// line.
+///
+/// The points are written as they are produced, without collecting them
+/// first. See [`write_points_csv`] for the format.
+///
+/// ```
+/// let mut out = Vec::new();
+/// zhang_hilbert::write_csv(&mut out, [2, 1]).unwrap();
+/// assert_eq!(out, b"0, 0\n1, 0\n");
+/// ```
+pub fn write_csv(w: impl Write, size: [u32; 2]) -> io::Result<()> {
+    write_points_csv(w, ArbHilbertScan32::new(size))
+}
+
+/// Write the points of [`ArbHilbertScan32`] to `w` as TSV, one point per
+/// line. See [`write_points_tsv`] for the format.
+pub fn write_tsv(w: impl Write, size: [u32; 2]) -> io::Result<()> {
+    write_points_tsv(w, ArbHilbertScan32::new(size))
+}
+
+/// Write the points of [`ArbHilbertScan32`] to `w` as a JSON array. See
+/// [`write_points_json`] for the format.
+pub fn write_json(w: impl Write, size: [u32; 2]) -> io::Result<()> {
+    write_points_json(w, ArbHilbertScan32::new(size))
+}
+
+/// Write `points` to `w` as CSV. Each line contains a point in the form
+/// `x, y`.
+pub fn write_points_csv<T: Display>(
+    mut w: impl Write,
+    points: impl IntoIterator<Item = [T; 2]>,
+) -> io::Result<()> {
+    for [x, y] in points {
+        writeln!(w, "{}, {}", x, y)?;
+    }
+    Ok(())
+}
+
+/// Write `points` to `w` as TSV. Each line contains a point in the form
+/// `x<TAB>y`.
+pub fn write_points_tsv<T: Display>(
+    mut w: impl Write,
+    points: impl IntoIterator<Item = [T; 2]>,
+) -> io::Result<()> {
+    for [x, y] in points {
+        writeln!(w, "{}\t{}", x, y)?;
+    }
+    Ok(())
+}
+
+/// Write `points` to `w` as a JSON array of `[x, y]` arrays. Each point is
+/// written on its own line.
+///
+/// ```
+/// let mut out = Vec::new();
+/// zhang_hilbert::write_points_json(&mut out, vec![[0, 0], [0, 1]]).unwrap();
+/// assert_eq!(out, b"[\n  [0, 0],\n  [0, 1]\n]\n" as &[u8]);
+/// ```
+pub fn write_points_json<T: Display>(
+    mut w: impl Write,
+    points: impl IntoIterator<Item = [T; 2]>,
+) -> io::Result<()> {
+    writeln!(w, "[")?;
+    let mut points = points.into_iter().peekable();
+    while let Some([x, y]) = points.next() {
+        if points.peek().is_some() {
+            writeln!(w, "  [{}, {}],", x, y)?;
+        } else {
+            writeln!(w, "  [{}, {}]", x, y)?;
+        }
+    }
+    writeln!(w, "]")
+}
@@ -0,0 +1,223 @@
+//! Compact binary encoding of scan snapshots
+//!
+//! The encoded form of a snapshot is laid out as follows:
+//!
+//! ```text
+//! magic: u8, version: u8, payload: [u8], checksum: u32le
+//! ```
+//!
+//! The magic byte identifies the snapshot type. All integers in the payload
+//! are encoded in the unsigned LEB128 format. The checksum is the Adler-32
+//! checksum of everything preceding it.
+use std::fmt;
+
+use crate::coord::ScanCoord;
+
+/// The current version of the snapshot encoding.
+const FORMAT_VERSION: u8 = 4;
+
+/// An error returned when decoding a snapshot fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapshotError {
+    /// The input ended unexpectedly or has extra bytes.
+    Truncated,
+    /// The magic byte does not match the requested snapshot type.
+    BadMagic,
+    /// The snapshot was encoded in an unsupported version of the format.
+    UnsupportedVersion(u8),
+    /// The checksum does not match the contents.
+    ChecksumMismatch,
+    /// The snapshot is well-formed but does not describe a valid scan state.
+    Invalid,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Truncated => write!(f, "the snapshot has an incorrect length"),
+            SnapshotError::BadMagic => write!(f, "the snapshot has an unknown magic byte"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "the snapshot format version {} is not supported", v)
+            }
+            SnapshotError::ChecksumMismatch => write!(f, "the snapshot checksum is incorrect"),
+            SnapshotError::Invalid => write!(f, "the snapshot describes an invalid state"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+pub(crate) struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub(crate) fn new(magic: u8) -> Self {
+        Self {
+            bytes: vec![magic, FORMAT_VERSION],
+        }
+    }
+
+    pub(crate) fn u8(&mut self, x: u8) {
+        self.bytes.push(x);
+    }
+
+    pub(crate) fn bool(&mut self, x: bool) {
+        self.bytes.push(x as u8);
+    }
+
+    pub(crate) fn uint<T: ScanCoord>(&mut self, mut x: T) {
+        let mask = T::from_u8(0x7f);
+        loop {
+            let byte = (x & mask).to_u8().unwrap();
+            x = x >> 7;
+            if x == T::zero() {
+                self.bytes.push(byte);
+                break;
+            }
+            self.bytes.push(byte | 0x80);
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        let checksum = adler32(&self.bytes);
+        self.bytes.extend_from_slice(&checksum.to_le_bytes());
+        self.bytes
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Validate the header and the checksum of `bytes` and construct a
+    /// `Reader` for its payload.
+    pub(crate) fn new(bytes: &'a [u8], magic: u8) -> Result<Self, SnapshotError> {
+        if bytes.len() < 6 {
+            return Err(SnapshotError::Truncated);
+        }
+        if bytes[0] != magic {
+            return Err(SnapshotError::BadMagic);
+        }
+        if bytes[1] != FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(bytes[1]));
+        }
+
+        let (body, checksum) = bytes.split_at(bytes.len() - 4);
+        let checksum = u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+        if adler32(body) != checksum {
+            return Err(SnapshotError::ChecksumMismatch);
+        }
+
+        Ok(Self { bytes: &body[2..] })
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, SnapshotError> {
+        let (&x, rest) = self.bytes.split_first().ok_or(SnapshotError::Truncated)?;
+        self.bytes = rest;
+        Ok(x)
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool, SnapshotError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SnapshotError::Invalid),
+        }
+    }
+
+    pub(crate) fn uint<T: ScanCoord>(&mut self) -> Result<T, SnapshotError> {
+        let bits = T::zero().count_zeros() as usize;
+        let mut x = T::zero();
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            let digit = T::from_u8(byte & 0x7f);
+            if shift >= bits || (digit << shift) >> shift != digit {
+                return Err(SnapshotError::Invalid);
+            }
+            x = x | (digit << shift);
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(x);
+            }
+        }
+    }
+
+    /// Ensure the payload was consumed entirely.
+    pub(crate) fn finish(self) -> Result<(), SnapshotError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(SnapshotError::Truncated)
+        }
+    }
+}
+
+/// Return `Ok(())` if `cond` holds, `Err(SnapshotError::Invalid)` otherwise.
+pub(crate) fn ensure(cond: bool) -> Result<(), SnapshotError> {
+    if cond {
+        Ok(())
+    } else {
+        Err(SnapshotError::Invalid)
+    }
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for &x in chunk {
+            a += x as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adler32_known_value() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn uint_round_trip() {
+        let values = [
+            0u64,
+            1,
+            0x7f,
+            0x80,
+            0x3fff,
+            0x4000,
+            u64::from(u32::MAX),
+            u64::MAX,
+        ];
+        let mut w = Writer::new(0);
+        for &x in &values {
+            w.uint(x);
+        }
+        let bytes = w.finish();
+
+        let mut r = Reader::new(&bytes, 0).unwrap();
+        for &x in &values {
+            assert_eq!(r.uint::<u64>(), Ok(x));
+        }
+        r.finish().unwrap();
+
+        // Overflow
+        let mut w = Writer::new(0);
+        w.uint(0x100u32);
+        let bytes = w.finish();
+        assert_eq!(
+            Reader::new(&bytes, 0).unwrap().uint::<u8>(),
+            Err(SnapshotError::Invalid)
+        );
+    }
+}
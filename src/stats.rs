@@ -0,0 +1,188 @@
+//! Measuring the quality of scans
+use crate::{
+    builder::{Algorithm, ScanBuilder},
+    coord::ScanCoord,
+    reorder::num_cells,
+};
+
+/// Statistics about the spatial locality of a scan. Returned by
+/// [`locality_stats`].
+///
+/// Every step of a scan moves to an adjacent cell, so the distances between
+/// consecutive points are always `1`. Instead, the locality is measured by
+/// how far apart in the scan order the spatially adjacent cells are. Lower
+/// values mean that nearby cells tend to be visited close together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalityStats {
+    /// The mean difference between the indices of two horizontally or
+    /// vertically adjacent cells, or `0` if there are no such pairs
+    pub mean_step: f64,
+    /// The maximum difference between the indices of two horizontally or
+    /// vertically adjacent cells, or `0` if there are no such pairs
+    pub max_step: u64,
+    /// The number of times the scan changes its direction
+    pub turns: u64,
+}
+
+/// Measure the spatial locality of the scan of the given size produced by
+/// [`Algorithm::ZhangArb`].
+///
+/// ```
+/// use zhang_hilbert::locality_stats;
+/// // 0 → 1
+/// //     ↓
+/// // 3 ← 2
+/// let stats = locality_stats([2, 2]);
+/// assert_eq!(stats.mean_step, 1.5);
+/// assert_eq!(stats.max_step, 3);
+/// assert_eq!(stats.turns, 2);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the number of cells does not fit in `u32`.
+pub fn locality_stats(size: [u32; 2]) -> LocalityStats {
+    locality_stats_with_algorithm(Algorithm::ZhangArb, size)
+}
+
+/// Measure the spatial locality of the scan of the given size produced by
+/// `algorithm`. See [`locality_stats`].
+///
+/// # Panics
+///
+/// Panics if the number of cells does not fit in `u32`.
+pub fn locality_stats_with_algorithm(algorithm: Algorithm, size: [u32; 2]) -> LocalityStats {
+    let [w, h] = [size[0] as usize, size[1] as usize];
+    let mut indices = vec![0u32; num_cells(size)];
+    let mut turns = TurnCounter::default();
+    for (i, p) in ScanBuilder::new(size)
+        .algorithm(algorithm)
+        .build()
+        .enumerate()
+    {
+        indices[p[1] as usize * w + p[0] as usize] = i as u32;
+        turns.push(p);
+    }
+
+    let (mut sum, mut num_pairs, mut max_step) = (0u64, 0u64, 0u64);
+    let mut add_pair = |a: u32, b: u32| {
+        let step = u64::from(a.max(b) - a.min(b));
+        sum += step;
+        num_pairs += 1;
+        max_step = max_step.max(step);
+    };
+    for y in 0..h {
+        let row = &indices[y * w..][..w];
+        for x in 0..w {
+            if x + 1 < w {
+                add_pair(row[x], row[x + 1]);
+            }
+            if y + 1 < h {
+                add_pair(row[x], indices[(y + 1) * w + x]);
+            }
+        }
+    }
+
+    LocalityStats {
+        mean_step: if num_pairs == 0 {
+            0.0
+        } else {
+            sum as f64 / num_pairs as f64
+        },
+        max_step,
+        turns: turns.stats.num_turns,
+    }
+}
+
+/// Statistics about the turns of a scan. Returned by [`turn_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TurnStats {
+    /// The number of pairs of consecutive moves in different directions
+    pub num_turns: u64,
+    /// The number of pairs of consecutive moves in the same direction
+    pub num_straight: u64,
+    /// The maximum number of consecutive moves in the same direction
+    pub longest_run: u64,
+}
+
+/// Count the turns and straight segments of the path formed by `points`,
+/// which is usually a scan. Every two consecutive points must be adjacent.
+///
+/// ```
+/// use zhang_hilbert::{turn_stats, CurveVariant, HilbertScanCore, LevelState};
+/// // ,-----,        ,------
+/// // '-, ,-'        '-----,
+/// // --' '--        ------'
+/// // Modified       Original
+/// let modified = HilbertScanCore::with_variant(
+///     [LevelState::default(); 32],
+///     [4u32, 3],
+///     CurveVariant::Modified,
+/// );
+/// let original = HilbertScanCore::with_variant(
+///     [LevelState::default(); 32],
+///     [4u32, 3],
+///     CurveVariant::Original,
+/// );
+/// assert_eq!(turn_stats(modified).num_turns, 8);
+/// assert_eq!(turn_stats(original).num_turns, 4);
+/// ```
+pub fn turn_stats<T: ScanCoord>(points: impl IntoIterator<Item = [T; 2]>) -> TurnStats {
+    let mut turns = TurnCounter::default();
+    for p in points {
+        turns.push(p);
+    }
+    turns.stats
+}
+
+/// Accumulates [`TurnStats`] from a sequence of points.
+struct TurnCounter<T> {
+    last_point: Option<[T; 2]>,
+    last_dir: Option<(usize, bool)>,
+    /// The number of moves since the last turn
+    run: u64,
+    stats: TurnStats,
+}
+
+impl<T> Default for TurnCounter<T> {
+    fn default() -> Self {
+        Self {
+            last_point: None,
+            last_dir: None,
+            run: 0,
+            stats: TurnStats::default(),
+        }
+    }
+}
+
+impl<T: ScanCoord> TurnCounter<T> {
+    fn push(&mut self, p: [T; 2]) {
+        if let Some(q) = self.last_point {
+            let dir = direction(q, p);
+            match self.last_dir {
+                Some(last_dir) if last_dir == dir => {
+                    self.stats.num_straight += 1;
+                    self.run += 1;
+                }
+                Some(_) => {
+                    self.stats.num_turns += 1;
+                    self.run = 1;
+                }
+                None => self.run = 1,
+            }
+            self.stats.longest_run = self.stats.longest_run.max(self.run);
+            self.last_dir = Some(dir);
+        }
+        self.last_point = Some(p);
+    }
+}
+
+/// Get the direction of the unit step from `p` to `q` as the axis and
+/// whether it's the positive direction.
+fn direction<T: ScanCoord>(p: [T; 2], q: [T; 2]) -> (usize, bool) {
+    if p[0] != q[0] {
+        (0, q[0] > p[0])
+    } else {
+        (1, q[1] > p[1])
+    }
+}
@@ -0,0 +1,698 @@
+//! Chaining tiles into a continuous path
+use std::iter::FusedIterator;
+
+use crate::{
+    adapter::{Transform, Transformed},
+    arb::ArbHilbertScanCore,
+    builder::Algorithm,
+    coord::ScanCoord,
+    core::{HilbertScanCore, LevelState, ScanError},
+};
+
+/// Describes how a scan enters and leaves a rectangle. Returned by
+/// [`tiling_contract`].
+///
+/// ```
+/// use zhang_hilbert::{tiling_contract_with_algorithm, Algorithm, ExitGuarantee};
+/// let contract = tiling_contract_with_algorithm(Algorithm::Zhang, [6u32, 7]).unwrap();
+/// assert_eq!(contract.entry, [0, 0]);
+/// assert_eq!(contract.exit, [5, 0]);
+/// assert_eq!(contract.guarantee, ExitGuarantee::Guaranteed);
+///
+/// let contract = tiling_contract_with_algorithm(Algorithm::Zhang, [5u32, 6]).unwrap();
+/// assert_eq!(contract.exit, [4, 1]);
+/// assert_eq!(contract.guarantee, ExitGuarantee::Observed);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TilingContract<T> {
+    /// The first point of the scan, which is always `[0, 0]`
+    pub entry: [T; 2],
+    /// The last point of the scan
+    pub exit: [T; 2],
+    /// Whether `exit` belongs to a class of sizes for which it is fixed
+    pub guarantee: ExitGuarantee,
+}
+
+/// How the exit point of a [`TilingContract`] is determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExitGuarantee {
+    /// The exit point is fixed by construction for the class of sizes
+    /// documented by [`tiling_contract`], and will remain so in future
+    /// versions.
+    Guaranteed,
+    /// The exit point is what the current implementation produces for this
+    /// particular size. It may change if the curve-type selection rules are
+    /// modified.
+    Observed,
+}
+
+impl<T> TilingContract<T> {
+    /// Check if the exit point is [`ExitGuarantee::Guaranteed`].
+    pub fn is_guaranteed(&self) -> bool {
+        self.guarantee == ExitGuarantee::Guaranteed
+    }
+}
+
+/// Get the entry and exit points of the scan of the given size produced by
+/// [`Algorithm::ZhangArb`]. Returns `None` if the rectangle is empty.
+///
+/// The exit point is [`ExitGuarantee::Guaranteed`] in these cases:
+///
+///  - The rectangle is a single row or column. The scan leaves at the
+///    opposite end.
+///  - [`Algorithm::Zhang`]: The width is even. The scan leaves at the
+///    bottom-right corner `[w - 1, 0]`.
+///  - [`Algorithm::ZhangArb`]: The longer side is even, or the width is even
+///    for a square. The scan leaves at the far end of the longer side:
+///    `[w - 1, 0]` if `w >= h`, or `[0, h - 1]` otherwise.
+///
+/// The tiling helpers such as [`TileOrientation`] and [`tile_sequence`] are
+/// built on this function.
+pub fn tiling_contract<T: ScanCoord>(size: [T; 2]) -> Option<TilingContract<T>> {
+    tiling_contract_with_algorithm(Algorithm::ZhangArb, size)
+}
+
+/// Get the entry and exit points of the scan of the given size produced by
+/// `algorithm`. See [`tiling_contract`].
+pub fn tiling_contract_with_algorithm<T: ScanCoord>(
+    algorithm: Algorithm,
+    size: [T; 2],
+) -> Option<TilingContract<T>> {
+    let [w, h] = size;
+    let (entry, exit) = match algorithm {
+        Algorithm::Zhang => (
+            HilbertScanCore::<T, Vec<LevelState<T>>>::start_point(size)?,
+            HilbertScanCore::<T, Vec<LevelState<T>>>::end_point(size)?,
+        ),
+        Algorithm::ZhangArb => (
+            ArbHilbertScanCore::<T, Vec<LevelState<T>>>::start_point(size)?,
+            ArbHilbertScanCore::<T, Vec<LevelState<T>>>::end_point(size)?,
+        ),
+    };
+
+    let is_even = |x: T| (x & T::one()) == T::zero();
+    let is_line = w == T::one() || h == T::one();
+    let is_guaranteed = is_line
+        || match algorithm {
+            Algorithm::Zhang => is_even(w),
+            // Every part but the last one has an even width, so the last one
+            // does too if the longer side is even
+            Algorithm::ZhangArb if h > w => is_even(h),
+            Algorithm::ZhangArb => is_even(w),
+        };
+
+    Some(TilingContract {
+        entry,
+        exit,
+        guarantee: if is_guaranteed {
+            ExitGuarantee::Guaranteed
+        } else {
+            ExitGuarantee::Observed
+        },
+    })
+}
+
+/// The orientation of a tile in a row of tiles chained by [`tile_sequence`].
+///
+/// A scan enters a tile at a corner on its left side and leaves it on its
+/// right side. Laying the same scan side by side only forms a continuous
+/// path if the scan leaves at the same row as it enters, which isn't the
+/// case for many sizes. Reflecting every other tile fixes that: a
+/// `Mirrored` tile enters next to where a `Normal` tile leaves and leaves
+/// next to where the following `Normal` tile enters.
+///
+/// If the `Normal` tile already leaves at the same row as it enters, the
+/// `Mirrored` tile is oriented the same as the `Normal` tile.
+///
+/// ```
+/// use zhang_hilbert::{Algorithm, TileOrientation, Transform};
+/// // Ends at `[2, 2]` and needs to be reflected in every other tile
+/// let size = [3u32, 3];
+/// let normal = TileOrientation::Normal.transform(Algorithm::Zhang, size).unwrap();
+/// let mirrored = TileOrientation::Mirrored.transform(Algorithm::Zhang, size).unwrap();
+/// assert_eq!(normal, Transform::Identity);
+/// assert_eq!(mirrored, Transform::FlipY);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TileOrientation {
+    /// The orientation of the even-numbered tiles
+    #[default]
+    Normal,
+    /// The orientation of the odd-numbered tiles
+    Mirrored,
+}
+
+impl TileOrientation {
+    /// Get the orientation of the `index`-th tile of a row.
+    pub fn of_tile(index: u64) -> Self {
+        if index & 1 == 0 {
+            TileOrientation::Normal
+        } else {
+            TileOrientation::Mirrored
+        }
+    }
+
+    /// Find the transformation that orients the scan of `algorithm` for this
+    /// orientation of a tile of the size `tile_size`.
+    ///
+    /// The scan is transformed as by the `transformed` method of
+    /// [`HilbertScanCore`] after being constructed for the size
+    /// `transform.size(tile_size)`. Returns [`ScanError::NoSeamlessTiling`] if
+    /// no pair of transformations chains the tiles.
+    ///
+    /// The transformations are found from the [`tiling_contract`] of the
+    /// transformed sizes. They are stable across versions only if the exit
+    /// points are [`ExitGuarantee::Guaranteed`].
+    pub fn transform<T: ScanCoord>(
+        self,
+        algorithm: Algorithm,
+        tile_size: [T; 2],
+    ) -> Result<Transform, ScanError> {
+        let [normal, mirrored] = tile_transforms(algorithm, tile_size)?;
+        Ok(match self {
+            TileOrientation::Normal => normal,
+            TileOrientation::Mirrored => mirrored,
+        })
+    }
+}
+
+/// Find the transformations for [`TileOrientation::Normal`] and
+/// [`TileOrientation::Mirrored`].
+fn tile_transforms<T: ScanCoord>(
+    algorithm: Algorithm,
+    tile_size: [T; 2],
+) -> Result<[Transform; 2], ScanError> {
+    tile_transforms_by(tile_size, |size| {
+        tiling_contract_with_algorithm(algorithm, size).map(|contract| contract.exit)
+    })
+}
+
+/// Find the transformations for [`TileOrientation::Normal`] and
+/// [`TileOrientation::Mirrored`], given the function computing the last point
+/// of the scan of a given size.
+pub(crate) fn tile_transforms_by<T: ScanCoord>(
+    tile_size: [T; 2],
+    end_point: impl Fn([T; 2]) -> Option<[T; 2]>,
+) -> Result<[Transform; 2], ScanError> {
+    if tile_size[0] == T::zero() || tile_size[1] == T::zero() {
+        return Err(ScanError::NoSeamlessTiling);
+    }
+
+    // The first and last points of the scan oriented by `transform`
+    let end_points = |transform: Transform| {
+        // The scan before the transformation
+        let size = transform.size(tile_size);
+        let exit = end_point(size).unwrap();
+        [
+            transform.apply([T::zero(); 2], size),
+            transform.apply(exit, size),
+        ]
+    };
+
+    let last_x = tile_size[0] - T::one();
+    let ends: Vec<_> = Transform::ALL
+        .iter()
+        .map(|&transform| (transform, end_points(transform)))
+        // Enter on the left side and leave on the right side
+        .filter(|&(_, [start, end])| start[0] == T::zero() && end[0] == last_x)
+        .collect();
+
+    for (i, &(normal, [start, end])) in ends.iter().enumerate() {
+        // Prefer not reflecting the scan at all
+        let mirrored = std::iter::once(&ends[i])
+            .chain(&ends)
+            .find(|&&(_, [m_start, m_end])| m_start[1] == end[1] && m_end[1] == start[1]);
+        if let Some(&(mirrored, _)) = mirrored {
+            return Ok([normal, mirrored]);
+        }
+    }
+
+    Err(ScanError::NoSeamlessTiling)
+}
+
+/// A tile produced by [`TileSequence`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TilePlacement<T> {
+    /// The index of the tile in the row
+    pub index: u64,
+    /// The position of the bottom-left corner of the tile
+    pub origin: [T; 2],
+    /// The orientation of the tile
+    pub orientation: TileOrientation,
+    /// The transformation to apply to the scan of the tile. See
+    /// [`TileOrientation::transform`].
+    pub transform: Transform,
+}
+
+impl<T: ScanCoord> TilePlacement<T> {
+    /// Map a point of the transformed scan of the tile to the row of tiles.
+    pub fn to_global(&self, [x, y]: [T; 2]) -> [T; 2] {
+        [x + self.origin[0], y + self.origin[1]]
+    }
+}
+
+/// Chain `n` tiles of the size `tile_size` in a row along the X axis, using
+/// [`Algorithm::ZhangArb`].
+///
+/// The scans of the tiles, oriented by the yielded transformations and
+/// concatenated, form a single continuous scan of the rectangle
+/// `[tile_size[0] * n, tile_size[1]]`. A column of tiles can be obtained by
+/// chaining transposed tiles and transposing the result.
+///
+/// Tiles taller than wide often can't be chained because their scans leave
+/// at the top side instead of the right side. Neither can many tiles having
+/// an odd width and an even height, whose scans leave in the middle of the
+/// right side.
+///
+/// ```
+/// use zhang_hilbert::{tile_sequence, ScanBuilder};
+/// let mut points: Vec<[u32; 2]> = Vec::new();
+/// for tile in tile_sequence([3u32, 5], 3).unwrap() {
+///     let scan = ScanBuilder::new([3, 5])
+///         .tile_orientation(tile.orientation)
+///         .unwrap();
+///     points.extend(scan.map(|p| tile.to_global(p)));
+/// }
+/// assert_eq!(points.len(), 45);
+/// assert!(points.windows(2).all(|w| {
+///     let [[x0, y0], [x1, y1]] = [w[0], w[1]];
+///     x0.max(x1) - x0.min(x1) + y0.max(y1) - y0.min(y1) == 1
+/// }));
+/// ```
+///
+/// Returns [`ScanError::NoSeamlessTiling`] if the tiles can't be chained and
+/// [`ScanError::CoordinateOverflow`] if the row is too wide for `T`.
+pub fn tile_sequence<T: ScanCoord>(
+    tile_size: [T; 2],
+    n: u64,
+) -> Result<TileSequence<T>, ScanError> {
+    tile_sequence_with_algorithm(Algorithm::ZhangArb, tile_size, n)
+}
+
+/// Chain `n` tiles of the size `tile_size` in a row along the X axis, using
+/// the given [`Algorithm`]. See [`tile_sequence`].
+pub fn tile_sequence_with_algorithm<T: ScanCoord>(
+    algorithm: Algorithm,
+    tile_size: [T; 2],
+    n: u64,
+) -> Result<TileSequence<T>, ScanError> {
+    let transforms = tile_transforms(algorithm, tile_size)?;
+
+    // The rightmost tile must have a representable origin
+    if n > 0 {
+        T::from_u64(n - 1)
+            .and_then(|i| tile_size[0].checked_mul(i))
+            .and_then(|x| x.checked_add(tile_size[0] - T::one()))
+            .ok_or(ScanError::CoordinateOverflow)?;
+    }
+
+    Ok(TileSequence {
+        tile_size,
+        transforms,
+        next_index: 0,
+        next_x: T::zero(),
+        len: n,
+    })
+}
+
+/// An iterator producing the tiles of a row chained into a continuous path.
+///
+/// This `struct` is created by [`tile_sequence`] and
+/// [`tile_sequence_with_algorithm`].
+#[derive(Debug, Clone)]
+pub struct TileSequence<T> {
+    tile_size: [T; 2],
+    /// The transformations for `Normal` and `Mirrored`
+    transforms: [Transform; 2],
+    next_index: u64,
+    /// The X coordinate of the origin of the next tile
+    next_x: T,
+    len: u64,
+}
+
+impl<T: ScanCoord> TileSequence<T> {
+    /// Get the size of each tile.
+    pub fn tile_size(&self) -> [T; 2] {
+        self.tile_size
+    }
+}
+
+impl<T: ScanCoord> Iterator for TileSequence<T> {
+    type Item = TilePlacement<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_index >= self.len {
+            return None;
+        }
+
+        let index = self.next_index;
+        let orientation = TileOrientation::of_tile(index);
+        let tile = TilePlacement {
+            index,
+            origin: [self.next_x, T::zero()],
+            orientation,
+            transform: self.transforms[orientation as usize],
+        };
+
+        self.next_index += 1;
+        if self.next_index < self.len {
+            self.next_x = self.next_x + self.tile_size[0];
+        }
+        Some(tile)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        crate::core::size_hint_for_len(self.len - self.next_index)
+    }
+}
+
+impl<T: ScanCoord> FusedIterator for TileSequence<T> {}
+
+/// The order in which [`TiledScan`] visits the rows of tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TileOrder {
+    /// Every row is visited from left to right.
+    #[default]
+    RowMajor,
+    /// The rows are visited from left to right and from right to left
+    /// alternately.
+    Boustrophedon,
+}
+
+/// An endless iterator scanning the plane in tiles of a fixed size.
+///
+/// Each tile is scanned by [`ArbHilbertScan32`](crate::ArbHilbertScan32),
+/// and the points are translated by the origin of the tile. By default,
+/// the tiles are laid out in a single row extending to the right. Use
+/// [`columns`](TiledScan::columns) to visit rows of a given number of tiles
+/// instead.
+///
+/// ```
+/// use zhang_hilbert::TiledScan;
+/// let points: Vec<[u64; 2]> = TiledScan::new([2, 2]).take(8).collect();
+/// assert_eq!(
+///     points,
+///     [[0, 0], [0, 1], [1, 1], [1, 0], [2, 0], [2, 1], [3, 1], [3, 0]],
+/// );
+/// ```
+///
+/// The iteration ends only when a tile origin doesn't fit in `u64`.
+#[derive(Debug, Clone)]
+pub struct TiledScan {
+    tile_size: [u32; 2],
+    /// The number of tiles in a row, or `None` for a single endless row
+    columns: Option<u64>,
+    order: TileOrder,
+    /// The transformations for `Normal` and `Mirrored` tiles, or `None` if
+    /// mirroring is disabled
+    transforms: Option<[Transform; 2]>,
+    /// The shift of the odd-numbered rows. See [`TiledScan::brick_offset`].
+    brick_offset: u32,
+    /// The position of the current tile in the visiting order of its row
+    column: u64,
+    row: u64,
+    origin: [u64; 2],
+    /// The size of the current tile, which is narrower than `tile_size` for
+    /// a partial tile
+    size: [u32; 2],
+    /// The current row is visited from right to left
+    reversed: bool,
+    /// The scan of the current tile, or `None` if the iteration has ended
+    scan: Option<Transformed<crate::ArbHilbertScan32, u32>>,
+}
+
+impl TiledScan {
+    /// Construct a `TiledScan` with tiles of the size `tile_size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_new`] returns an error.
+    ///
+    /// [`try_new`]: TiledScan::try_new
+    pub fn new(tile_size: [u32; 2]) -> Self {
+        Self::try_new(tile_size).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Construct a `TiledScan` with tiles of the size `tile_size`, returning
+    /// [`ScanError::UnsupportedSize`] if the tiles are empty.
+    pub fn try_new(tile_size: [u32; 2]) -> Result<Self, ScanError> {
+        if tile_size[0] == 0 || tile_size[1] == 0 {
+            return Err(ScanError::UnsupportedSize);
+        }
+        let mut this = Self {
+            tile_size,
+            columns: None,
+            order: TileOrder::RowMajor,
+            transforms: None,
+            brick_offset: 0,
+            column: 0,
+            row: 0,
+            origin: [0; 2],
+            size: tile_size,
+            reversed: false,
+            scan: None,
+        };
+        this.start_tile();
+        Ok(this)
+    }
+
+    /// Lay out the tiles in rows of `columns` tiles, restarting at the first
+    /// tile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `columns` is zero.
+    pub fn columns(mut self, columns: u64) -> Self {
+        assert_ne!(columns, 0, "the number of columns must be non-zero");
+        self.columns = Some(columns);
+        self.skip_to_tile([0, 0]);
+        self
+    }
+
+    /// Set the order in which the rows are visited, restarting at the first
+    /// tile. This has no effect unless [`columns`](TiledScan::columns) is
+    /// set.
+    pub fn order(mut self, order: TileOrder) -> Self {
+        self.order = order;
+        self.skip_to_tile([0, 0]);
+        self
+    }
+
+    /// Shift the odd-numbered rows of tiles to the right by `offset`,
+    /// forming a brick pattern, and restart at the first tile. This has no
+    /// effect unless [`columns`](TiledScan::columns) is set.
+    ///
+    /// A shifted row starts with a partial tile of the width `offset` and
+    /// ends with a partial tile of the width `tile_size[0] - offset`, so it
+    /// has one more tile than the other rows. The partial tiles are scanned
+    /// by scans of their own sizes.
+    ///
+    /// ```
+    /// use zhang_hilbert::TiledScan;
+    /// let mut scan = TiledScan::new([4, 4]).columns(2).brick_offset(2);
+    /// scan.skip_to_tile([1, 1]);
+    /// assert_eq!(scan.next(), Some([2, 4]));
+    /// scan.skip_to_tile([2, 1]);
+    /// assert_eq!(scan.next(), Some([6, 4]));
+    /// assert_eq!(scan.take(7).count(), 7);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` isn't less than `tile_size[0]`.
+    pub fn brick_offset(mut self, offset: u32) -> Self {
+        assert!(
+            offset < self.tile_size[0],
+            "the brick offset must be less than the tile width"
+        );
+        self.brick_offset = offset;
+        self.skip_to_tile([0, 0]);
+        self
+    }
+
+    /// Reflect every other tile of each row so that the path is continuous
+    /// across the seams between the tiles in a row. See
+    /// [`TileOrientation`]. The seams between rows remain discontinuous, and
+    /// so do the seams next to the partial tiles of
+    /// [`brick_offset`](TiledScan::brick_offset), which aren't reflected.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the conditions where [`try_mirrored`] returns an error.
+    ///
+    /// [`try_mirrored`]: TiledScan::try_mirrored
+    pub fn mirrored(self) -> Self {
+        self.try_mirrored().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Reflect every other tile of each row, restarting at the first tile.
+    /// Returns [`ScanError::NoSeamlessTiling`] if the tiles can't be chained.
+    pub fn try_mirrored(mut self) -> Result<Self, ScanError> {
+        self.transforms = Some(tile_transforms(Algorithm::ZhangArb, self.tile_size)?);
+        self.skip_to_tile([0, 0]);
+        Ok(self)
+    }
+
+    /// Get the size of each tile.
+    pub fn tile_size(&self) -> [u32; 2] {
+        self.tile_size
+    }
+
+    /// Get the column and row of the tile being scanned.
+    pub fn tile(&self) -> [u64; 2] {
+        [self.tile_column(), self.row]
+    }
+
+    /// Restart the scan at the first point of the tile at column `tx` and
+    /// row `ty`.
+    ///
+    /// ```
+    /// use zhang_hilbert::TiledScan;
+    /// let mut scan = TiledScan::new([4, 4]).columns(8);
+    /// scan.skip_to_tile([2, 1]);
+    /// assert_eq!(scan.next(), Some([8, 4]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile is never visited, i.e., `tx` isn't less than the
+    /// number of tiles in the row or `ty` is non-zero without
+    /// [`columns`](TiledScan::columns).
+    pub fn skip_to_tile(&mut self, [tx, ty]: [u64; 2]) {
+        assert!(
+            tx < self.row_len(ty) && (self.columns.is_some() || ty == 0),
+            "the tile {:?} is never visited",
+            [tx, ty]
+        );
+        self.row = ty;
+        self.reversed = self.order == TileOrder::Boustrophedon && ty % 2 == 1;
+        self.column = if self.reversed {
+            self.row_len(ty) - 1 - tx
+        } else {
+            tx
+        };
+        self.start_tile();
+    }
+
+    /// Check if the row `row` is shifted by `brick_offset`.
+    fn is_shifted(&self, row: u64) -> bool {
+        self.brick_offset != 0 && row % 2 == 1
+    }
+
+    /// Get the number of tiles in the row `row`.
+    fn row_len(&self, row: u64) -> u64 {
+        match self.columns {
+            Some(columns) => columns + self.is_shifted(row) as u64,
+            None => u64::MAX,
+        }
+    }
+
+    /// Get the column of the current tile.
+    fn tile_column(&self) -> u64 {
+        if self.reversed {
+            self.row_len(self.row) - 1 - self.column
+        } else {
+            self.column
+        }
+    }
+
+    /// Get the X coordinate and the width of the current tile.
+    fn tile_span(&self) -> Option<(u64, u32)> {
+        let w = self.tile_size[0];
+        let tx = self.tile_column();
+        if !self.is_shifted(self.row) {
+            return Some((tx.checked_mul(u64::from(w))?, w));
+        }
+
+        let offset = self.brick_offset;
+        if tx == 0 {
+            return Some((0, offset));
+        }
+        let x = (tx - 1).checked_mul(u64::from(w))? + u64::from(offset);
+        if tx == self.row_len(self.row) - 1 {
+            // The partial tile at the right edge
+            Some((x, w - offset))
+        } else {
+            Some((x, w))
+        }
+    }
+
+    /// Construct the scan of the current tile. Ends the iteration if the
+    /// tile origin doesn't fit in `u64`.
+    fn start_tile(&mut self) {
+        let h = self.tile_size[1];
+        let span = self.tile_span();
+        let origin_y = self.row.checked_mul(u64::from(h));
+        let (origin, size) = match (span, origin_y) {
+            (Some((x, w)), Some(y))
+                if x.checked_add(u64::from(w)).is_some()
+                    && y.checked_add(u64::from(h)).is_some() =>
+            {
+                ([x, y], [w, h])
+            }
+            _ => {
+                self.scan = None;
+                return;
+            }
+        };
+        self.origin = origin;
+        self.size = size;
+
+        let transform = match self.transforms {
+            Some(transforms) if size == self.tile_size => {
+                transforms[TileOrientation::of_tile(self.column) as usize]
+            }
+            _ => Transform::Identity,
+        };
+        let size = transform.size(size);
+        self.scan = Some(crate::ArbHilbertScan32::new(size).transformed(transform));
+    }
+
+    /// Move to the next tile in the visiting order.
+    fn next_tile(&mut self) {
+        self.column += 1;
+        if self.column == self.row_len(self.row) {
+            self.column = 0;
+            self.row = match self.row.checked_add(1) {
+                Some(row) => row,
+                None => {
+                    self.scan = None;
+                    return;
+                }
+            };
+            self.reversed = self.order == TileOrder::Boustrophedon && !self.reversed;
+        }
+        self.start_tile();
+    }
+}
+
+impl Iterator for TiledScan {
+    type Item = [u64; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let p = self.scan.as_mut()?.next();
+            if let Some(mut p) = p {
+                if self.reversed && self.transforms.is_some() {
+                    // Chain the tiles from right to left
+                    p = Transform::FlipX.apply(p, self.size);
+                }
+                return Some([
+                    self.origin[0] + u64::from(p[0]),
+                    self.origin[1] + u64::from(p[1]),
+                ]);
+            }
+            self.next_tile();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.scan {
+            // The next tile might not fit in `u64`
+            Some(scan) => (scan.size_hint().0, None),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+impl FusedIterator for TiledScan {}
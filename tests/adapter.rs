@@ -0,0 +1,372 @@
+use zhang_hilbert::{
+    ArbHilbertScan16, ArbHilbertScan32, HilbertScan16, HilbertScan32, HilbertScanCore, LevelState,
+    ScanError, Transform,
+};
+
+#[test]
+fn normalized_range() {
+    for &size in &[[1, 1], [1, 9], [6, 7], [11, 42], [40, 7]] {
+        let points: Vec<[f64; 2]> = HilbertScan32::new(size).normalized().collect();
+        let expected: Vec<_> = HilbertScan32::new(size)
+            .map(|[x, y]| {
+                [
+                    (x as f64 + 0.5) / size[0] as f64,
+                    (y as f64 + 0.5) / size[1] as f64,
+                ]
+            })
+            .collect();
+        assert_eq!(points, expected);
+
+        let points: Vec<[f32; 2]> = ArbHilbertScan32::new(size).normalized().collect();
+        assert_eq!(points.len(), (size[0] * size[1]) as usize);
+        for p in points {
+            assert!(p.iter().all(|&v| v > 0.0 && v < 1.0), "{:?}", p);
+        }
+    }
+}
+
+#[test]
+fn normalized_2x2() {
+    let points: Vec<[f32; 2]> = HilbertScan32::new([2, 2]).normalized().collect();
+    let expected: Vec<_> = HilbertScan32::new([2, 2])
+        .map(|[x, y]| [[0.25, 0.75][x as usize], [0.25, 0.75][y as usize]])
+        .collect();
+    assert_eq!(points, expected);
+}
+
+#[test]
+fn centered_is_symmetric() {
+    for w in 1..20 {
+        for h in 1..20 {
+            let points: Vec<[i64; 2]> = HilbertScan32::new([w, h]).centered().collect();
+            assert_eq!(points.len(), (w * h) as usize);
+            for axis in 0..2 {
+                let min = points.iter().map(|p| p[axis]).min().unwrap();
+                let max = points.iter().map(|p| p[axis]).max().unwrap();
+                // The extra cell of an even length goes to the negative side
+                assert!(
+                    min + max == 0 || min + max == -1,
+                    "{:?} {} {}",
+                    [w, h],
+                    min,
+                    max
+                );
+                assert_eq!(max - min + 1, i64::from([w, h][axis]));
+            }
+
+            let points: Vec<[i16; 2]> = ArbHilbertScan32::new([w, h]).centered().collect();
+            let expected: Vec<_> = ArbHilbertScan32::new([w, h])
+                .map(|[x, y]| [x as i16 - (w / 2) as i16, y as i16 - (h / 2) as i16])
+                .collect();
+            assert_eq!(points, expected);
+        }
+    }
+}
+
+#[test]
+fn tuples_match_arrays() {
+    for &size in &[[0, 3], [1, 7], [4, 4], [13, 9]] {
+        let expected: Vec<_> = HilbertScan32::new(size).map(|[x, y]| (x, y)).collect();
+        assert_eq!(
+            HilbertScan32::new(size).tuples().collect::<Vec<_>>(),
+            expected
+        );
+
+        let expected: Vec<_> = ArbHilbertScan32::new(size).map(|[x, y]| (x, y)).collect();
+        assert_eq!(
+            ArbHilbertScan32::new(size).tuples().collect::<Vec<_>>(),
+            expected
+        );
+
+        let mut tuples = ArbHilbertScan32::new(size).tuples();
+        assert_eq!(tuples.nth(2), expected.get(2).cloned());
+        assert_eq!(tuples.size_hint().0, expected.len().saturating_sub(3));
+    }
+}
+
+#[test]
+fn translated_negative_origin() {
+    for &size in &[[0, 3], [1, 7], [4, 4], [13, 9]] {
+        for &origin in &[[0, 0], [-3, 5], [-100, -7], [i32::MIN, i32::MIN]] {
+            let expected: Vec<_> = HilbertScan32::new(size)
+                .map(|[x, y]| [x as i32 + origin[0], y as i32 + origin[1]])
+                .collect();
+            let points: Vec<[i32; 2]> = HilbertScan32::new(size).translated(origin).collect();
+            assert_eq!(points, expected);
+
+            let expected: Vec<_> = ArbHilbertScan32::new(size)
+                .map(|[x, y]| [x as i32 + origin[0], y as i32 + origin[1]])
+                .collect();
+            let points: Vec<[i32; 2]> = ArbHilbertScan32::new(size).translated(origin).collect();
+            assert_eq!(points, expected);
+        }
+    }
+
+    let points: Vec<[i64; 2]> = HilbertScan32::new([2, 1]).translated([-1, -1]).collect();
+    assert_eq!(points, [[-1, -1], [0, -1]]);
+}
+
+#[test]
+fn translated_near_max() {
+    // The last row and column end exactly at `i32::MAX`
+    let origin = [i32::MAX - 12, i32::MAX - 8];
+    let points: Vec<_> = HilbertScan32::new([13, 9])
+        .try_translated(origin)
+        .unwrap()
+        .collect();
+    assert_eq!(points.len(), 13 * 9);
+    assert!(points.contains(&[i32::MAX, i32::MAX]));
+
+    assert_eq!(
+        HilbertScan32::new([14, 9])
+            .try_translated(origin)
+            .unwrap_err(),
+        ScanError::CoordinateOverflow
+    );
+    assert_eq!(
+        ArbHilbertScan32::new([13, 10])
+            .try_translated(origin)
+            .unwrap_err(),
+        ScanError::CoordinateOverflow
+    );
+
+    // The untranslated coordinates must fit in the output type as well
+    assert!(HilbertScan32::new([1 << 31, 1])
+        .try_translated([0i32, 0])
+        .is_ok());
+    assert_eq!(
+        HilbertScan32::new([(1 << 31) + 1, 1])
+            .try_translated([i32::MIN, 0])
+            .unwrap_err(),
+        ScanError::CoordinateOverflow
+    );
+
+    // Nothing to translate in an empty rectangle
+    assert!(HilbertScan32::new([0, 5])
+        .try_translated([i32::MAX, i32::MAX])
+        .is_ok());
+}
+
+#[test]
+#[should_panic(expected = "the coordinates don't fit in the output type")]
+fn translated_overflow_panics() {
+    let _ = ArbHilbertScan32::new([3, 3]).translated([i8::MAX - 1, 0]);
+}
+
+#[test]
+fn widened_matches_wide_scan() {
+    for &size in &[
+        [0, 3],
+        [1, 7],
+        [4, 4],
+        [13, 9],
+        [256, 256],
+        [300, 200],
+        [1000, 3],
+    ] {
+        let size16 = [size[0] as u16, size[1] as u16];
+
+        let points: Vec<[u32; 2]> = HilbertScan16::new(size16).widened().collect();
+        assert!(
+            points.iter().cloned().eq(HilbertScan32::new(size)),
+            "{:?}",
+            size
+        );
+
+        let points: Vec<[u32; 2]> = ArbHilbertScan16::new(size16).widened().collect();
+        assert!(
+            points.iter().cloned().eq(ArbHilbertScan32::new(size)),
+            "{:?}",
+            size
+        );
+
+        let mut widened = HilbertScan16::new(size16).widened::<u64>();
+        let n = points.len() / 2;
+        assert_eq!(
+            widened.nth(n),
+            HilbertScan32::new(size).nth(n).map(|p| p.map(u64::from))
+        );
+        assert_eq!(widened.size_hint().0, points.len().saturating_sub(n + 1));
+    }
+
+    // The working state takes half the space
+    assert!(std::mem::size_of::<LevelState<u16>>() < std::mem::size_of::<LevelState<u32>>());
+}
+
+fn unpack(p: u32) -> [u32; 2] {
+    [p & 0xffff, p >> 16]
+}
+
+#[test]
+fn packed_u16x2_matches_scan() {
+    for &size in &[
+        [0, 3],
+        [1, 7],
+        [4, 4],
+        [13, 9],
+        [300, 200],
+        [1, 65536],
+        [65536, 2],
+    ] {
+        let packed: Vec<u32> = HilbertScan32::new(size).packed_u16x2().collect();
+        assert!(packed
+            .iter()
+            .map(|&p| unpack(p))
+            .eq(HilbertScan32::new(size)));
+
+        let packed: Vec<u32> = ArbHilbertScan32::new(size).packed_u16x2().collect();
+        assert!(packed
+            .iter()
+            .map(|&p| unpack(p))
+            .eq(ArbHilbertScan32::new(size)));
+
+        // `fill_packed` in chunks of various lengths
+        for &chunk in &[1, 5, 64, 1000] {
+            let mut scan = HilbertScan32::new(size).packed_u16x2();
+            let mut buffer = vec![0; chunk];
+            let mut filled = Vec::new();
+            loop {
+                let count = scan.fill_packed(&mut buffer);
+                if count == 0 {
+                    break;
+                }
+                filled.extend_from_slice(&buffer[..count]);
+            }
+            let expected: Vec<u32> = HilbertScan32::new(size).packed_u16x2().collect();
+            assert_eq!(filled, expected, "{:?} {}", size, chunk);
+        }
+    }
+
+    let scan = HilbertScanCore::with_origin([LevelState::default(); 32], [16, 16], [0xfff0, 3]);
+    let packed: Vec<u32> = scan.clone().packed_u16x2().collect();
+    assert!(packed.iter().map(|&p| unpack(p)).eq(scan));
+}
+
+#[test]
+fn packed_u16x2_rejects_large_coordinates() {
+    for &size in &[[65537, 1], [3, 65537], [100_000, 100_000]] {
+        assert_eq!(
+            HilbertScan32::new(size).try_packed_u16x2().unwrap_err(),
+            ScanError::CoordinateOverflow
+        );
+        assert_eq!(
+            ArbHilbertScan32::new(size).try_packed_u16x2().unwrap_err(),
+            ScanError::CoordinateOverflow
+        );
+    }
+    let scan = HilbertScanCore::with_origin([LevelState::default(); 32], [16, 16], [0xfff1, 0]);
+    assert!(scan.try_packed_u16x2().is_err());
+
+    // Nothing to pack in an empty rectangle
+    assert!(HilbertScan32::new([0, 100_000]).try_packed_u16x2().is_ok());
+}
+
+#[test]
+#[should_panic(expected = "the coordinates don't fit in the output type")]
+fn packed_u16x2_panics() {
+    let _ = ArbHilbertScan32::new([70000, 7]).packed_u16x2();
+}
+
+/// Check that `points` visit every cell of the rectangle at `origin` exactly
+/// once, moving by one cell at a time.
+fn validate_curve(points: &[[u32; 2]], [w, h]: [u32; 2], origin: [u32; 2]) {
+    let mut visited = vec![false; (w * h) as usize];
+    for (i, &[x, y]) in points.iter().enumerate() {
+        let [x, y] = [x - origin[0], y - origin[1]];
+        assert!(x < w && y < h, "{:?}", [x, y]);
+        assert!(!visited[(y * w + x) as usize], "{:?}", [x, y]);
+        visited[(y * w + x) as usize] = true;
+        if i > 0 {
+            let [px, py] = points[i - 1];
+            let [px, py] = [px - origin[0], py - origin[1]];
+            assert_eq!(px.max(x) - px.min(x) + py.max(y) - py.min(y), 1);
+        }
+    }
+    assert!(visited.iter().all(|&x| x));
+}
+
+#[test]
+fn transformed_matches_mapped_scan() {
+    for &size in &[[1, 1], [1, 9], [9, 1], [2, 2], [6, 7], [11, 42], [40, 7]] {
+        for &transform in &Transform::ALL {
+            let [w, h] = size;
+            let brute_force = |[x, y]: [u32; 2]| match transform {
+                Transform::Identity => [x, y],
+                Transform::FlipX => [w - 1 - x, y],
+                Transform::FlipY => [x, h - 1 - y],
+                Transform::Transpose => [y, x],
+                Transform::Rot90 => [h - 1 - y, x],
+                Transform::Rot180 => [w - 1 - x, h - 1 - y],
+                Transform::Rot270 => [y, w - 1 - x],
+                Transform::AntiTranspose => [h - 1 - y, w - 1 - x],
+            };
+            let out_size = transform.size(size);
+            assert_eq!(out_size[0] * out_size[1], w * h);
+
+            let scan = HilbertScan32::new(size).transformed(transform);
+            assert_eq!(scan.size(), out_size);
+            let points: Vec<_> = scan.collect();
+            validate_curve(&points, out_size, [0, 0]);
+            assert!(points
+                .iter()
+                .cloned()
+                .eq(HilbertScan32::new(size).map(brute_force)));
+
+            // Entry and exit points
+            let start = HilbertScan32::start_point(size).unwrap();
+            let end = HilbertScan32::end_point(size).unwrap();
+            assert_eq!(points.first(), Some(&transform.apply(start, size)));
+            assert_eq!(points.last(), Some(&transform.apply(end, size)));
+
+            let points: Vec<_> = ArbHilbertScan32::new(size).transformed(transform).collect();
+            validate_curve(&points, out_size, [0, 0]);
+            assert!(points
+                .iter()
+                .cloned()
+                .eq(ArbHilbertScan32::new(size).map(brute_force)));
+            let end = ArbHilbertScan32::end_point(size).unwrap();
+            assert_eq!(points.last(), Some(&transform.apply(end, size)));
+
+            // `nth` and `fold`
+            let k = points.len() / 2;
+            let mut scan = HilbertScan32::new(size).transformed(transform);
+            let expected: Vec<_> = HilbertScan32::new(size).map(brute_force).collect();
+            assert_eq!(scan.nth(k), Some(expected[k]));
+            assert_eq!(
+                scan.fold(Vec::new(), |mut v, p| {
+                    v.push(p);
+                    v
+                }),
+                expected[k + 1..]
+            );
+
+            // With an origin
+            let origin = [100, 7];
+            let scan = HilbertScanCore::with_origin([LevelState::default(); 32], size, origin)
+                .transformed(transform);
+            let points: Vec<_> = scan.collect();
+            validate_curve(&points, out_size, origin);
+            assert!(points.iter().map(|&[x, y]| [x - 100, y - 7]).eq(expected));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "the coordinates don't fit in the output type")]
+fn transformed_checks_origin() {
+    let scan = HilbertScanCore::with_origin([LevelState::default(); 8], [2u8, 10], [250, 0]);
+    let _ = scan.transformed(Transform::Transpose);
+}
+
+#[test]
+fn adapter_types_are_nameable() {
+    let _: zhang_hilbert::Normalized<HilbertScan32, f32> = HilbertScan32::new([3, 3]).normalized();
+    let _: zhang_hilbert::Centered<HilbertScan32, i32> = HilbertScan32::new([3, 3]).centered();
+    let _: zhang_hilbert::Tuples<ArbHilbertScan32> = ArbHilbertScan32::new([3, 3]).tuples();
+    let _: zhang_hilbert::Widened<HilbertScan16, usize> = HilbertScan16::new([3, 3]).widened();
+    let _: zhang_hilbert::Transformed<HilbertScan32, u32> =
+        HilbertScan32::new([3, 3]).transformed(Transform::Rot90);
+    let _: zhang_hilbert::PackedU16x2<HilbertScan32> = HilbertScan32::new([3, 3]).packed_u16x2();
+    let _: zhang_hilbert::Translated<HilbertScan32, i64> =
+        HilbertScan32::new([3, 3]).translated([-1, -1]);
+}
@@ -0,0 +1,252 @@
+use zhang_hilbert::{
+    Algorithm, ArbHilbertScan32, ArbHilbertScanVec, Corner, CornerError, CurveVariant,
+    HilbertScan32, HilbertScanVec, LevelState, PrimaryAxis, ScanAny, ScanAny32, ScanBuilder,
+    ScanError, TileOrientation, Transform,
+};
+
+const SIZES: &[[u32; 2]] = &[
+    [0, 0],
+    [0, 5],
+    [1, 1],
+    [1, 9],
+    [4, 3],
+    [6, 7],
+    [32, 32],
+    [40, 7],
+];
+
+#[test]
+fn builder_matches_constructors() {
+    for &size in SIZES {
+        let scan = ScanBuilder::new(size).algorithm(Algorithm::Zhang).build();
+        assert_eq!(scan.algorithm(), Algorithm::Zhang);
+        assert!(scan.eq(HilbertScanVec::new_vec(size)));
+
+        let scan = ScanBuilder::new(size).build();
+        assert_eq!(scan.algorithm(), Algorithm::ZhangArb);
+        assert!(scan.eq(ArbHilbertScanVec::new_vec(size)));
+
+        let scan = ScanBuilder::new(size)
+            .algorithm(Algorithm::Zhang)
+            .storage([LevelState::default(); 32])
+            .build();
+        assert!(scan.eq(HilbertScan32::new(size)));
+
+        let scan = ScanBuilder::new(size)
+            .algorithm(Algorithm::ZhangArb)
+            .storage([LevelState::default(); 32])
+            .build();
+        assert!(scan.eq(ArbHilbertScan32::new(size)));
+    }
+}
+
+#[test]
+fn builder_delegates_iterator_methods() {
+    for &algorithm in &[Algorithm::Zhang, Algorithm::ZhangArb] {
+        let expected: Vec<_> = ScanBuilder::new([13u32, 9])
+            .algorithm(algorithm)
+            .build()
+            .collect();
+        let scan = || ScanBuilder::new([13u32, 9]).algorithm(algorithm).build();
+
+        assert_eq!(scan().size_hint(), (expected.len(), Some(expected.len())));
+        assert_eq!(scan().count(), expected.len());
+        assert_eq!(scan().last(), expected.last().cloned());
+        assert_eq!(scan().nth(50), Some(expected[50]));
+        assert_eq!(scan().fold(0, |n, _| n + 1), expected.len());
+    }
+}
+
+#[test]
+fn builder_too_small_storage() {
+    for &algorithm in &[Algorithm::Zhang, Algorithm::ZhangArb] {
+        let result = ScanBuilder::new([32u32, 32])
+            .algorithm(algorithm)
+            .storage(vec![LevelState::default(); 3])
+            .try_build();
+        assert_eq!(
+            result.unwrap_err(),
+            ScanError::StorageTooSmall {
+                required: 6,
+                provided: 3
+            }
+        );
+    }
+}
+
+#[test]
+fn scan_any_matches_concrete_types() {
+    for &size in SIZES {
+        let scan = ScanAny32::new(Algorithm::Zhang, size);
+        assert_eq!(scan.algorithm(), Algorithm::Zhang);
+        assert!(scan.eq(HilbertScan32::new(size)));
+
+        let scan = ScanAny32::new(Algorithm::ZhangArb, size);
+        assert_eq!(scan.algorithm(), Algorithm::ZhangArb);
+        assert!(scan.eq(ArbHilbertScan32::new(size)));
+
+        let scan = ScanAny::with_level_state_storage(
+            Algorithm::Zhang,
+            vec![LevelState::default(); 32],
+            size,
+        );
+        assert!(scan.eq(HilbertScan32::new(size)));
+    }
+}
+
+#[test]
+fn exit_corner_is_reached() {
+    let corners = [
+        Corner::BottomLeft,
+        Corner::BottomRight,
+        Corner::TopLeft,
+        Corner::TopRight,
+    ];
+    for &algorithm in &[Algorithm::Zhang, Algorithm::ZhangArb] {
+        for w in 1..=32u32 {
+            for h in 1..=32u32 {
+                for &corner in &corners {
+                    let builder = ScanBuilder::new([w, h]).algorithm(algorithm);
+                    match builder.exit_corner(corner) {
+                        Ok(scan) => {
+                            assert_eq!(scan.size(), [w, h]);
+                            let points: Vec<_> = scan.collect();
+                            assert_eq!(points.len(), (w * h) as usize);
+                            assert_eq!(
+                                points.last(),
+                                Some(&corner.point([w, h])),
+                                "{:?} {:?} {:?}",
+                                algorithm,
+                                [w, h],
+                                corner
+                            );
+                        }
+                        Err(e) => {
+                            assert_eq!(
+                                e,
+                                ScanError::UnreachableCorner(CornerError::EndsMidSide),
+                                "{:?} {:?}",
+                                algorithm,
+                                [w, h]
+                            );
+                            // Only happens with a mix of odd and even sides
+                            assert_eq!(algorithm, Algorithm::ZhangArb);
+                            assert_ne!(w % 2, h % 2);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // `Algorithm::Zhang` reaches every corner of a square
+    let scan = ScanBuilder::new([8u32, 8])
+        .algorithm(Algorithm::Zhang)
+        .storage([LevelState::default(); 32])
+        .exit_corner(Corner::TopRight)
+        .unwrap();
+    assert_eq!(scan.last(), Some([7, 7]));
+}
+
+#[test]
+fn exit_corner_honors_options() {
+    let corners = [
+        Corner::BottomLeft,
+        Corner::BottomRight,
+        Corner::TopLeft,
+        Corner::TopRight,
+    ];
+    for &(algorithm, variant) in &[
+        (Algorithm::Zhang, CurveVariant::Original),
+        (Algorithm::ZhangArb, CurveVariant::Modified),
+    ] {
+        for w in 1..=16u32 {
+            for h in 1..=16u32 {
+                for &corner in &corners {
+                    let builder = ScanBuilder::new([w, h])
+                        .algorithm(algorithm)
+                        .variant(variant)
+                        .primary_axis(PrimaryAxis::Y)
+                        .origin([10, 20]);
+                    if let Ok(scan) = builder.exit_corner(corner) {
+                        let [x, y] = corner.point([w, h]);
+                        let points: Vec<_> = scan.collect();
+                        assert_eq!(points.len(), (w * h) as usize);
+                        assert_eq!(
+                            points.last(),
+                            Some(&[x + 10, y + 20]),
+                            "{:?} {:?} {:?}",
+                            algorithm,
+                            [w, h],
+                            corner
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // `Algorithm::Zhang` leaves at the top-right corner with
+    // `CurveVariant::Original` and the bottom-right corner otherwise
+    let builder = ScanBuilder::new([4u32, 3]).algorithm(Algorithm::Zhang);
+    let scan = builder.clone().exit_corner(Corner::TopRight).unwrap();
+    assert_eq!(scan.transform(), Transform::FlipY);
+    let scan = builder
+        .variant(CurveVariant::Original)
+        .exit_corner(Corner::TopRight)
+        .unwrap();
+    assert_eq!(scan.transform(), Transform::Identity);
+}
+
+#[test]
+fn arb_rejects_original_variant() {
+    // `Algorithm::ZhangArb` is the default
+    let builder = ScanBuilder::new([40u32, 7]).variant(CurveVariant::Original);
+    assert_eq!(
+        builder.clone().try_build().unwrap_err(),
+        ScanError::UnsupportedVariant
+    );
+    assert_eq!(
+        builder
+            .clone()
+            .storage([LevelState::default(); 32])
+            .try_build()
+            .unwrap_err(),
+        ScanError::UnsupportedVariant
+    );
+    assert_eq!(
+        builder
+            .clone()
+            .exit_corner(Corner::BottomRight)
+            .unwrap_err(),
+        ScanError::UnsupportedVariant
+    );
+    assert_eq!(
+        builder
+            .clone()
+            .tile_orientation(TileOrientation::Normal)
+            .unwrap_err(),
+        ScanError::UnsupportedVariant
+    );
+    assert!(builder.algorithm(Algorithm::Zhang).try_build().is_ok());
+}
+
+#[test]
+#[should_panic(expected = "the curve variant isn't supported by the algorithm")]
+fn arb_original_variant_panics() {
+    let _ = ScanBuilder::new([40u32, 7])
+        .variant(CurveVariant::Original)
+        .build();
+}
+
+#[test]
+fn exit_corner_rejects_empty_rect() {
+    for &size in &[[0u32, 0], [0, 5], [5, 0]] {
+        assert_eq!(
+            ScanBuilder::new(size)
+                .exit_corner(Corner::BottomLeft)
+                .unwrap_err(),
+            ScanError::UnreachableCorner(CornerError::EmptyRect)
+        );
+    }
+}
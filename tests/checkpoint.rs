@@ -0,0 +1,75 @@
+use zhang_hilbert::{ArbHilbertScan32, Checkpointed, DividerKind, HilbertScan32, SaveState};
+
+/// A simple LCG
+fn rng(mut seed: u32) -> impl FnMut() -> u32 {
+    move || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        seed >> 8
+    }
+}
+
+/// Iterate over `new()` with checkpoints, stop at a random point, resume from
+/// the last checkpoint using `restore`, and check the concatenated output.
+fn check_resume<I, S>(
+    new: impl Fn() -> I,
+    restore: impl Fn(&S) -> I,
+    rand: &mut impl FnMut() -> u32,
+) where
+    I: Iterator<Item = [u32; 2]> + SaveState<Snapshot = S>,
+{
+    let expected: Vec<_> = new().collect();
+    for &every_n in &[1, 3, 17, 100] {
+        for _ in 0..10 {
+            let kill_at = rand() as usize % (expected.len() + 1);
+
+            // The points known to have been processed at the checkpoint
+            let mut committed = Vec::new();
+            let mut pending = Vec::new();
+            let mut last_snapshot = None;
+            for (point, snapshot) in Checkpointed::new(new(), every_n).take(kill_at) {
+                pending.push(point);
+                if let Some(snapshot) = snapshot {
+                    committed.append(&mut pending);
+                    last_snapshot = Some(snapshot);
+                }
+            }
+
+            let resumed = match &last_snapshot {
+                Some(snapshot) => restore(snapshot),
+                None => new(),
+            };
+            committed.extend(resumed);
+            assert_eq!(committed, expected, "{} {}", every_n, kill_at);
+        }
+    }
+}
+
+#[test]
+fn checkpointed_resume() {
+    let mut rand = rng(0x1234_5678);
+    for &size in &[[0, 4], [1, 9], [6, 7], [11, 42], [40, 7], [300, 3]] {
+        check_resume(
+            || HilbertScan32::new(size),
+            |s| HilbertScan32::restore(s, Default::default()),
+            &mut rand,
+        );
+        for &kind in &[DividerKind::Strip, DividerKind::Grid { tile_side: 5 }] {
+            check_resume(
+                || ArbHilbertScan32::with_divider_kind(Default::default(), size, kind),
+                |s| ArbHilbertScan32::restore(s, Default::default()),
+                &mut rand,
+            );
+        }
+    }
+}
+
+#[test]
+fn checkpointed_interval() {
+    let flags: Vec<_> = Checkpointed::new(HilbertScan32::new([4, 3]), 5)
+        .map(|(_, snapshot)| snapshot.is_some())
+        .collect();
+    assert_eq!(
+        flags,
+        [false, false, false, false, true, false, false, false, false, true, false, false]
+    );
+}
@@ -0,0 +1,86 @@
+use zhang_hilbert::{ClosedHilbertScan, ClosedHilbertScan32, LevelState, ScanError};
+
+/// Check that `points` visits every cell exactly once, and that every two
+/// consecutive points, including the last and the first, are adjacent.
+fn validate_cycle(size: [u32; 2], points: &[[u32; 2]]) {
+    let [w, h] = size;
+    let mut visited = vec![false; (w * h) as usize];
+    for &[x, y] in points {
+        assert!(x < w && y < h, "{:?} is out of bounds", [x, y]);
+        let cell = &mut visited[(x + y * w) as usize];
+        assert!(!*cell, "{:?} was visited twice", [x, y]);
+        *cell = true;
+    }
+    assert_eq!(points.len(), visited.len());
+
+    for (i, &p) in points.iter().enumerate() {
+        let q = points[(i + 1) % points.len()];
+        let dist = (p[0] as i64 - q[0] as i64).abs() + (p[1] as i64 - q[1] as i64).abs();
+        assert_eq!(dist, 1, "{:?} and {:?} are not adjacent", p, q);
+    }
+}
+
+#[test]
+fn even_sizes_are_closed() {
+    for w in (2..=64).step_by(2) {
+        for h in (2..=64).step_by(2) {
+            let scan = ClosedHilbertScan32::new([w, h]);
+            assert_eq!(scan.size(), [w, h]);
+            assert_eq!(scan.size_hint().0, (w * h) as usize);
+            let points: Vec<_> = scan.collect();
+            assert_eq!(points[0], [0, 0]);
+            validate_cycle([w, h], &points);
+        }
+    }
+}
+
+#[test]
+fn mixed_parity_sizes_are_closed() {
+    for w in 2..=17 {
+        for h in 2..=17 {
+            if w % 2 == 1 && h % 2 == 1 {
+                continue;
+            }
+            let points: Vec<_> = ClosedHilbertScan32::new([w, h]).collect();
+            validate_cycle([w, h], &points);
+        }
+    }
+}
+
+#[test]
+fn size_hint_is_exact() {
+    let mut scan = ClosedHilbertScan32::new([6, 5]);
+    for remaining in (0..30).rev() {
+        scan.next().unwrap();
+        assert_eq!(scan.remaining_len(), remaining);
+        assert_eq!(
+            scan.size_hint(),
+            (remaining as usize, Some(remaining as usize))
+        );
+    }
+    assert_eq!(scan.next(), None);
+}
+
+#[test]
+fn impossible_sizes_are_rejected() {
+    for &size in &[[0, 0], [0, 4], [1, 1], [1, 2], [4, 1], [3, 3], [5, 7]] {
+        assert_eq!(
+            ClosedHilbertScan32::try_new(size).unwrap_err(),
+            ScanError::NoClosedScan,
+            "{:?}",
+            size
+        );
+    }
+}
+
+#[test]
+fn small_storage_is_rejected() {
+    let result = ClosedHilbertScan::try_with_level_state_storage(
+        [
+            vec![LevelState::default(); 1],
+            vec![LevelState::default(); 1],
+        ],
+        [64u32, 64],
+    );
+    assert!(matches!(result, Err(ScanError::StorageTooSmall { .. })));
+}
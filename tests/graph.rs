@@ -0,0 +1,46 @@
+use zhang_hilbert::{edge_list, ArbHilbertScan32};
+
+#[test]
+fn edges_form_a_path() {
+    for w in 0..=32 {
+        for h in 0..=32 {
+            let edges = edge_list([w, h]);
+            let len = (w * h) as usize;
+            assert_eq!(edges.len(), len.saturating_sub(1), "{:?}", [w, h]);
+
+            // Every edge connects adjacent cells, and the edges are chained
+            for &(p, q) in &edges {
+                let d = |a: u32, b: u32| a.max(b) - a.min(b);
+                assert_eq!(d(p[0], q[0]) + d(p[1], q[1]), 1, "{:?} {:?}", p, q);
+            }
+            for pair in edges.windows(2) {
+                assert_eq!(pair[0].1, pair[1].0);
+            }
+
+            // The path visits every cell once, so the graph is connected and
+            // acyclic
+            let mut visited = vec![false; len];
+            let cells = edges
+                .first()
+                .map(|e| e.0)
+                .into_iter()
+                .chain(edges.iter().map(|e| e.1));
+            for [x, y] in cells {
+                let cell = &mut visited[(x + y * w) as usize];
+                assert!(!*cell, "{:?} was visited twice", [x, y]);
+                *cell = true;
+            }
+            if len > 1 {
+                assert!(visited.iter().all(|&x| x), "{:?}", [w, h]);
+            }
+        }
+    }
+}
+
+#[test]
+fn edges_follow_scan() {
+    let points: Vec<_> = ArbHilbertScan32::new([13, 5]).collect();
+    let edges: Vec<_> = points.windows(2).map(|p| (p[0], p[1])).collect();
+    assert_eq!(edge_list([13, 5]), edges);
+    assert_eq!(edge_list([1, 1]), []);
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "heapless")]
+use zhang_hilbert::{
+    num_levels_for_size, ArbHilbertScan32, ArbHilbertScanHeapless, HilbertScan32,
+    HilbertScanHeapless, ScanError,
+};
+
+const SIZES: &[[u32; 2]] = &[
+    [0, 0],
+    [0, 5],
+    [1, 1],
+    [1, 9],
+    [4, 3],
+    [6, 7],
+    [16, 16],
+    [11, 42],
+    [40, 7],
+    [300, 200],
+];
+
+#[test]
+fn heapless_matches_array() {
+    for &size in SIZES {
+        let scan = HilbertScanHeapless::<u32, 12>::new_heapless(size);
+        assert_eq!(scan.level_states().len(), num_levels_for_size(size));
+        assert!(scan.eq(HilbertScan32::new(size)), "{:?}", size);
+
+        let scan = ArbHilbertScanHeapless::<u32, 12>::new_heapless(size);
+        assert!(scan.eq(ArbHilbertScan32::new(size)), "{:?}", size);
+    }
+}
+
+#[test]
+fn heapless_capacity_too_small() {
+    let size = [300, 200];
+    assert_eq!(
+        HilbertScanHeapless::<u32, 4>::try_new_heapless(size).unwrap_err(),
+        ScanError::StorageTooSmall {
+            required: num_levels_for_size(size),
+            provided: 4,
+        }
+    );
+    assert!(ArbHilbertScanHeapless::<u32, 4>::try_new_heapless(size).is_err());
+
+    // Empty rectangles don't use the storage at all
+    assert!(HilbertScanHeapless::<u32, 0>::try_new_heapless([0, 1 << 20]).is_ok());
+}
@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use zhang_hilbert::{HilbertKey, HilbertScan32};
+
+#[test]
+fn btree_map_follows_scan_order() {
+    for &size in &[[1, 9], [6, 7], [11, 42], [32, 32], [40, 7]] {
+        let expected: Vec<_> = HilbertScan32::new(size).collect();
+
+        // Insert the points in a shuffled order
+        let mut points = expected.clone();
+        let mut seed = 12345u32;
+        for i in (1..points.len()).rev() {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            points.swap(i, (seed >> 8) as usize % (i + 1));
+        }
+
+        let map: BTreeMap<_, ()> = points
+            .iter()
+            .map(|&p| (HilbertKey::new(size, p), ()))
+            .collect();
+
+        let actual: Vec<_> = map.keys().map(HilbertKey::point).collect();
+        assert_eq!(actual, expected, "{:?}", size);
+        for (i, key) in map.keys().enumerate() {
+            assert_eq!(key.rank(), i as u64);
+            assert_eq!(key.size(), size);
+        }
+    }
+}
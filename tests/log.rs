@@ -0,0 +1,68 @@
+#![cfg(feature = "log")]
+use std::sync::Mutex;
+use zhang_hilbert::{ArbHilbertScan32, HilbertScan32};
+
+struct CapturingLogger {
+    messages: Mutex<Vec<String>>,
+}
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.target().starts_with("zhang_hilbert")
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.enabled(record.metadata()) {
+            let message = record.args().to_string();
+            self.messages.lock().unwrap().push(message);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger {
+    messages: Mutex::new(Vec::new()),
+};
+
+fn capture(f: impl FnOnce()) -> Vec<String> {
+    LOGGER.messages.lock().unwrap().clear();
+    f();
+    std::mem::take(&mut *LOGGER.messages.lock().unwrap())
+}
+
+// The logger is global, so everything is done in a single test
+#[test]
+fn trace_events() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    // 12×10 is divided into 4×4 blocks
+    let messages = capture(|| HilbertScan32::new([12, 10]).for_each(drop));
+    let count = |prefix| messages.iter().filter(|m| m.starts_with(prefix)).count();
+    assert_eq!(count("entering block "), 16, "{:#?}", messages);
+    assert_eq!(
+        count("extra subdivision") + count("no extra subdivision"),
+        16,
+        "{:#?}",
+        messages
+    );
+
+    // The last block of 6×7 has a helper row
+    let messages = capture(|| HilbertScan32::new([6, 7]).for_each(drop));
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.starts_with("starting helper row")),
+        "{:#?}",
+        messages
+    );
+
+    // 40×7 is divided into six parts
+    let messages = capture(|| ArbHilbertScan32::new([40, 7]).for_each(drop));
+    let count = messages
+        .iter()
+        .filter(|m| m.starts_with("starting part"))
+        .count();
+    assert_eq!(count, 6, "{:#?}", messages);
+}
@@ -0,0 +1,122 @@
+use zhang_hilbert::{
+    Algorithm, ArbHilbertScan32, HilbertScan32, MaskedScan, ScanBuilder, ScanError, ScanSize,
+    Transform,
+};
+
+/// A deterministic pseudo-random mask
+fn mask(size: [u32; 2], seed: u32) -> Vec<bool> {
+    let mut state = seed.wrapping_mul(2654435761) | 1;
+    (0..size[0] * size[1])
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state & 3 != 0
+        })
+        .collect()
+}
+
+fn check<I>(scan: I, mask: &[bool])
+where
+    I: Iterator<Item = [u32; 2]> + ScanSize<Coord = u32> + Clone,
+{
+    let w = scan.size()[0] as usize;
+    let expected: Vec<_> = scan
+        .clone()
+        .filter(|&[x, y]| mask[y as usize * w + x as usize])
+        .collect();
+
+    let masked = MaskedScan::new(scan, mask).unwrap();
+    assert_eq!(masked.len(), mask.iter().filter(|&&x| x).count());
+    let (ranks, points): (Vec<_>, Vec<_>) = masked.unzip();
+    assert_eq!(points, expected);
+    assert!(ranks.iter().copied().eq(0..expected.len() as u64));
+}
+
+#[test]
+fn matches_filtered_scan() {
+    for w in 0..20 {
+        for h in 0..20 {
+            let mask = mask([w, h], w * 31 + h);
+            check(HilbertScan32::new([w, h]), &mask);
+            check(ArbHilbertScan32::new([w, h]), &mask);
+            check(
+                ScanBuilder::new([w, h]).algorithm(Algorithm::Zhang).build(),
+                &mask,
+            );
+            if w > 0 && h > 0 {
+                // The mask is in the coordinate space of the transformed scan
+                check(
+                    HilbertScan32::new([h, w]).transformed(Transform::Rot90),
+                    &mask,
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn empty_and_full_masks() {
+    let empty = vec![false; 35];
+    let mut scan = MaskedScan::new(HilbertScan32::new([5, 7]), &empty).unwrap();
+    assert_eq!(scan.len(), 0);
+    assert_eq!(scan.next(), None);
+
+    let full = vec![true; 35];
+    let scan = MaskedScan::new(HilbertScan32::new([5, 7]), &full).unwrap();
+    assert_eq!(scan.len(), 35);
+    assert!(scan.map(|(_, p)| p).eq(HilbertScan32::new([5, 7])));
+}
+
+#[test]
+fn len_decreases() {
+    let mask = mask([6, 6], 1);
+    let mut scan = MaskedScan::new(HilbertScan32::new([6, 6]), &mask).unwrap();
+    let mut len = scan.len();
+    while scan.next().is_some() {
+        len -= 1;
+        assert_eq!(scan.len(), len);
+    }
+    assert_eq!(len, 0);
+}
+
+#[test]
+fn size_mismatch() {
+    assert_eq!(
+        MaskedScan::new(HilbertScan32::new([3, 4]), &[true; 11]).unwrap_err(),
+        ScanError::MaskSizeMismatch {
+            required: 12,
+            provided: 11
+        }
+    );
+    assert!(MaskedScan::new(HilbertScan32::new([0, 4]), &[true; 1]).is_err());
+    assert!(MaskedScan::new(HilbertScan32::new([0, 4]), &[]).is_ok());
+}
+
+#[test]
+fn rejects_scan_in_progress() {
+    let mut scan = HilbertScan32::new([2, 2]);
+    scan.nth(1);
+    assert_eq!(
+        MaskedScan::new(scan, &[true; 4]).unwrap_err(),
+        ScanError::ScanInProgress
+    );
+
+    let mut scan = ScanBuilder::new([40u32, 7]).build();
+    scan.next();
+    assert_eq!(
+        MaskedScan::new(scan, &[true; 280]).unwrap_err(),
+        ScanError::ScanInProgress
+    );
+
+    // Rewinding makes it usable again
+    let mut scan = ArbHilbertScan32::new([40, 7]);
+    scan.nth(100);
+    scan.reset();
+    assert_eq!(MaskedScan::new(scan, &[true; 280]).unwrap().count(), 280);
+
+    // An exhausted scan of an empty rectangle is still at its start
+    let mut scan = HilbertScan32::new([0, 4]);
+    assert_eq!(scan.next(), None);
+    assert!(MaskedScan::new(scan, &[]).is_ok());
+}
@@ -0,0 +1,25 @@
+#![cfg(feature = "mint")]
+use zhang_hilbert::{ArbHilbertScan32, HilbertScan32, MintPoints};
+
+#[test]
+fn mint_points_match_arrays() {
+    for &size in &[[0, 3], [1, 7], [4, 4], [13, 9], [40, 7]] {
+        let expected: Vec<_> = HilbertScan32::new(size)
+            .map(|[x, y]| mint::Point2 { x, y })
+            .collect();
+        let points: Vec<_> = HilbertScan32::new(size).points_mint().collect();
+        assert_eq!(points, expected);
+
+        let expected: Vec<_> = ArbHilbertScan32::new(size)
+            .map(|[x, y]| mint::Point2 { x, y })
+            .collect();
+        let points: MintPoints<ArbHilbertScan32> = ArbHilbertScan32::new(size).points_mint();
+        assert_eq!(points.collect::<Vec<_>>(), expected);
+
+        let mut points = HilbertScan32::new(size).points_mint();
+        assert_eq!(
+            points.nth(2),
+            HilbertScan32::new(size).nth(2).map(Into::into)
+        );
+    }
+}
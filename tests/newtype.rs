@@ -0,0 +1,130 @@
+//! Checks that the scans accept a newtype coordinate that only implements
+//! the required items of `ScanCoord`. The newtype doesn't implement `Debug`
+//! either.
+use ndarray::Array2;
+use std::{
+    convert::TryFrom,
+    ops::{Add, BitAnd, BitOr, Div, Mul, Rem, Shl, Shr, Sub},
+};
+
+use zhang_hilbert::{ArbHilbertScan, ArbHilbertScan32, HilbertScan, HilbertScan32, ScanCoord};
+
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+struct Col(u32);
+
+macro_rules! binop {
+    ($($tr:ident::$f:ident),*) => {$(
+        impl $tr for Col {
+            type Output = Self;
+            fn $f(self, rhs: Self) -> Self {
+                Col($tr::$f(self.0, rhs.0))
+            }
+        }
+    )*};
+}
+
+binop!(
+    Add::add,
+    Sub::sub,
+    Mul::mul,
+    Div::div,
+    Rem::rem,
+    BitAnd::bitand,
+    BitOr::bitor
+);
+
+impl Shl<usize> for Col {
+    type Output = Self;
+    fn shl(self, rhs: usize) -> Self {
+        Col(self.0 << rhs)
+    }
+}
+
+impl Shr<usize> for Col {
+    type Output = Self;
+    fn shr(self, rhs: usize) -> Self {
+        Col(self.0 >> rhs)
+    }
+}
+
+impl ScanCoord for Col {
+    const BITS: u32 = 32;
+
+    fn to_u128(self) -> u128 {
+        self.0.into()
+    }
+
+    fn from_u128(x: u128) -> Option<Self> {
+        u32::try_from(x).ok().map(Col)
+    }
+}
+
+fn wrap(p: [u32; 2]) -> [Col; 2] {
+    [Col(p[0]), Col(p[1])]
+}
+
+fn unwrap(p: [Col; 2]) -> [u32; 2] {
+    [p[0].0, p[1].0]
+}
+
+fn validate_curve(scan: impl Iterator<Item = [u32; 2]>, [w, h]: [u32; 2]) {
+    let mut map: Array2<bool> = Array2::default([h as usize, w as usize]);
+    let mut last: Option<[u32; 2]> = None;
+    for p in scan {
+        let cell = &mut map[[p[1] as usize, p[0] as usize]];
+        assert!(!*cell, "{:?} has been already visited", p);
+        *cell = true;
+        if let Some(last) = last {
+            assert!(
+                (last[0] != p[0]) != (last[1] != p[1]),
+                "Invalid move: {:?} → {:?}",
+                last,
+                p
+            );
+        }
+        last = Some(p);
+    }
+    assert!(map.iter().all(|&x| x), "{:?}", [w, h]);
+}
+
+#[test]
+fn newtype_scan_patterns() {
+    for w in 0..32 {
+        for h in 0..32 {
+            let size = [w, h];
+            validate_curve(HilbertScan::<Col, 32>::new(wrap(size)).map(unwrap), size);
+            validate_curve(ArbHilbertScan::<Col, 32>::new(wrap(size)).map(unwrap), size);
+        }
+    }
+}
+
+#[test]
+fn newtype_matches_u32() {
+    for &size in &[[0, 0], [1, 1], [7, 5], [16, 16], [33, 6], [300, 200]] {
+        let expected: Vec<_> = HilbertScan32::new(size).collect();
+        let scan = HilbertScan::<Col, 32>::new(wrap(size));
+        assert_eq!(scan.size_hint().0, expected.len());
+        assert_eq!(scan.clone().last().map(unwrap), expected.last().cloned());
+        assert_eq!(scan.clone().map(unwrap).collect::<Vec<_>>(), expected);
+
+        let k = expected.len() / 3;
+        let mut scan = scan;
+        assert_eq!(scan.nth(k).map(unwrap), expected.get(k).cloned());
+        for (i, &p) in expected.iter().enumerate().skip(k + 1) {
+            assert_eq!(scan.rank(wrap(p)), i as u64);
+            assert_eq!(scan.next().map(unwrap), Some(p));
+        }
+    }
+}
+
+#[test]
+fn newtype_arb_matches_u32() {
+    for &size in &[[0, 0], [1, 1], [100, 7], [9, 40], [300, 200]] {
+        let expected: Vec<_> = ArbHilbertScan32::with_aspect_bound(size, 1).collect();
+        let actual: Vec<_> = ArbHilbertScan::<Col, 32>::with_aspect_bound(wrap(size), Col(1))
+            .map(unwrap)
+            .collect();
+        assert_eq!(actual, expected);
+    }
+}
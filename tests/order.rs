@@ -0,0 +1,143 @@
+use zhang_hilbert::{
+    Algorithm, ArbHilbertScan32, ArbHilbertScanCore, BoustrophedonScan, DividerKind, HilbertScan32,
+    HilbertScanCore, LevelState, PrimaryAxis, RowMajorScan, Scan, ScanAny32,
+};
+
+/// Check that `scan` visits every cell of `scan.size()` exactly once, from
+/// `entry_point` to `exit_point`. If `contiguous` is set, also check that
+/// every two consecutive points are adjacent.
+fn validate<S: Scan<Coord = u32>>(scan: S, contiguous: bool) {
+    let [w, h] = scan.size();
+    let (entry, exit) = (scan.entry_point(), scan.exit_point());
+    let len = (w * h) as usize;
+    assert_eq!(scan.size_hint(), (len, Some(len)), "{:?}", [w, h]);
+
+    let points: Vec<_> = scan.collect();
+    assert_eq!(points.len(), len, "{:?}", [w, h]);
+    assert_eq!(points.first().cloned(), entry, "{:?}", [w, h]);
+    assert_eq!(points.last().cloned(), exit, "{:?}", [w, h]);
+
+    let mut visited = vec![false; len];
+    for &[x, y] in &points {
+        assert!(x < w && y < h, "{:?} is out of bounds", [x, y]);
+        let cell = &mut visited[(x + y * w) as usize];
+        assert!(!*cell, "{:?} was visited twice", [x, y]);
+        *cell = true;
+    }
+
+    if contiguous {
+        for pair in points.windows(2) {
+            let [p, q] = [pair[0], pair[1]];
+            let d = |a: u32, b: u32| a.max(b) - a.min(b);
+            assert_eq!(d(p[0], q[0]) + d(p[1], q[1]), 1, "{:?} {:?}", p, q);
+        }
+    }
+}
+
+#[test]
+fn all_orders_are_valid() {
+    for w in 0..=24 {
+        for h in 0..=24 {
+            validate(HilbertScan32::new([w, h]), true);
+            validate(ArbHilbertScan32::new([w, h]), true);
+            validate(ScanAny32::new(Algorithm::Zhang, [w, h]), true);
+            validate(ScanAny32::new(Algorithm::ZhangArb, [w, h]), true);
+            validate(
+                HilbertScanCore::with_primary_axis(
+                    [LevelState::default(); 32],
+                    [w, h],
+                    PrimaryAxis::Y,
+                ),
+                true,
+            );
+            validate(
+                ArbHilbertScanCore::with_primary_axis(
+                    [LevelState::default(); 32],
+                    [w, h],
+                    PrimaryAxis::Y,
+                ),
+                true,
+            );
+            validate(BoustrophedonScan::new([w, h]), true);
+            validate(RowMajorScan::new([w, h]), w <= 1 || h <= 1);
+        }
+    }
+}
+
+#[test]
+fn simple_orders() {
+    let points: Vec<_> = RowMajorScan::new([3u32, 2]).collect();
+    assert_eq!(points, [[0, 0], [1, 0], [2, 0], [0, 1], [1, 1], [2, 1]]);
+
+    let points: Vec<_> = BoustrophedonScan::new([3u32, 2]).collect();
+    assert_eq!(points, [[0, 0], [1, 0], [2, 0], [2, 1], [1, 1], [0, 1]]);
+    assert_eq!(BoustrophedonScan::new([3u32, 3]).exit_point(), Some([2, 2]));
+
+    // Random access
+    for n in 0..8 {
+        assert_eq!(
+            RowMajorScan::new([3u32, 2]).nth(n),
+            points_nth(RowMajorScan::new([3, 2]), n)
+        );
+        assert_eq!(
+            BoustrophedonScan::new([3u32, 2]).nth(n),
+            points_nth(BoustrophedonScan::new([3, 2]), n)
+        );
+    }
+    let mut scan = BoustrophedonScan::new([3u32, 2]);
+    assert_eq!(scan.nth(3), Some([2, 1]));
+    assert_eq!(scan.remaining_len(), 2);
+    assert_eq!(scan.next(), Some([1, 1]));
+    assert_eq!(scan.nth(5), None);
+    assert_eq!(scan.next(), None);
+
+    // Other coordinate types
+    assert!(RowMajorScan::new([255u8, 255])
+        .eq(RowMajorScan::new([255u32, 255]).map(|[x, y]| [x as u8, y as u8])));
+    assert_eq!(
+        BoustrophedonScan::new([u128::MAX, 1]).nth(1 << 40),
+        Some([1 << 40, 0])
+    );
+}
+
+/// Get the `n`-th point by stepping.
+fn points_nth(mut scan: impl Iterator<Item = [u32; 2]>, n: usize) -> Option<[u32; 2]> {
+    for _ in 0..n {
+        scan.next()?;
+    }
+    scan.next()
+}
+
+#[test]
+fn entry_and_exit_points_include_origin() {
+    let scan = HilbertScanCore::with_origin([LevelState::default(); 32], [6u32, 7], [10, 20]);
+    assert_eq!(Scan::entry_point(&scan), Some([10, 20]));
+    // Don't use `last`, which is implemented by `exit_point`
+    assert_eq!(
+        Scan::exit_point(&scan),
+        scan.clone().fold(None, |_, p| Some(p))
+    );
+
+    let scan = ArbHilbertScanCore::with_origin(
+        [LevelState::default(); 32],
+        [40u32, 7],
+        DividerKind::Strip,
+        [10, 20],
+    );
+    assert_eq!(Scan::entry_point(&scan), Some([10, 20]));
+    assert_eq!(
+        Scan::exit_point(&scan),
+        scan.clone().fold(None, |_, p| Some(p))
+    );
+
+    // Unaffected by the progress
+    let mut scan = ArbHilbertScan32::new([40, 7]);
+    let exit = scan.exit_point();
+    scan.nth(100);
+    assert_eq!(scan.entry_point(), Some([0, 0]));
+    assert_eq!(scan.exit_point(), exit);
+
+    assert_eq!(HilbertScan32::new([0, 7]).exit_point(), None);
+    assert_eq!(RowMajorScan::new([0u32, 7]).entry_point(), None);
+    assert_eq!(BoustrophedonScan::new([7u32, 0]).exit_point(), None);
+}
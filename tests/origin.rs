@@ -0,0 +1,216 @@
+use zhang_hilbert::{
+    ArbHilbertScan32, ArbHilbertScanCore, ArbScanSnapshot, DividerKind, HilbertScan32,
+    HilbertScan8, HilbertScanCore, LevelState, ScanError, ScanSnapshot,
+};
+
+const SIZES: &[[u32; 2]] = &[
+    [0, 0],
+    [0, 5],
+    [1, 1],
+    [1, 9],
+    [9, 1],
+    [2, 2],
+    [4, 3],
+    [6, 7],
+    [16, 16],
+    [11, 42],
+    [40, 7],
+    [33, 33],
+];
+
+const ORIGINS: &[[u32; 2]] = &[[0, 0], [1, 0], [3, 7], [1000, 20], [1 << 31, 5]];
+
+fn translate(p: [u32; 2], origin: [u32; 2]) -> [u32; 2] {
+    [p[0] + origin[0], p[1] + origin[1]]
+}
+
+fn new(size: [u32; 2], origin: [u32; 2]) -> HilbertScan32 {
+    HilbertScanCore::with_origin(Default::default(), size, origin)
+}
+
+#[test]
+fn origin_matches_translated_scan() {
+    for &size in SIZES {
+        for &origin in ORIGINS {
+            let expected: Vec<_> = HilbertScan32::new(size)
+                .map(|p| translate(p, origin))
+                .collect();
+
+            let scan = new(size, origin);
+            assert_eq!(scan.origin(), origin);
+            assert_eq!(scan.clone().collect::<Vec<_>>(), expected);
+            assert_eq!(
+                scan.clone().fold(Vec::new(), |mut v, p| {
+                    v.push(p);
+                    v
+                }),
+                expected
+            );
+            assert_eq!(scan.last(), expected.last().cloned());
+
+            let mut scan = new(size, origin);
+            let mut buffer = [[0; 2]; 5];
+            let mut points = Vec::new();
+            loop {
+                let count = scan.fill_buffer(&mut buffer);
+                if count == 0 {
+                    break;
+                }
+                points.extend_from_slice(&buffer[..count]);
+            }
+            assert_eq!(points, expected, "{:?} {:?}", size, origin);
+
+            for &k in &[0, 1, expected.len() / 3, expected.len() / 2 + 1] {
+                let mut scan = new(size, origin);
+                assert_eq!(scan.nth(k), expected.get(k).cloned(), "{:?} {}", size, k);
+                assert!(scan.eq(expected.iter().cloned().skip(k + 1)));
+            }
+
+            let mut scan = new(size, origin);
+            for (i, &p) in expected.iter().enumerate() {
+                assert_eq!(scan.rank(p), i as u64);
+                assert_eq!(scan.next(), Some(p));
+            }
+        }
+    }
+}
+
+#[test]
+fn origin_with_rules_preserved_by_snapshot() {
+    for &size in SIZES {
+        let origin = [3, 7];
+        let expected: Vec<_> = new(size, origin).collect();
+        let mut scan = new(size, origin);
+        for k in 0..=expected.len() {
+            let bytes = scan.save_state().to_bytes();
+            let snapshot = ScanSnapshot::from_bytes(&bytes).unwrap();
+            let resumed = HilbertScan32::restore(&snapshot, Default::default());
+            assert_eq!(resumed.origin(), origin);
+            assert!(
+                resumed.eq(expected[k..].iter().cloned()),
+                "{:?} {}",
+                size,
+                k
+            );
+
+            let (level_states, parts) = scan.clone().into_raw_parts();
+            let resumed = HilbertScan32::from_raw_parts(level_states, parts);
+            assert!(resumed.eq(expected[k..].iter().cloned()));
+
+            scan.next();
+        }
+
+        let mut scan = new(size, origin);
+        scan.nth(expected.len() / 2);
+        scan.reset();
+        assert!(scan.eq(expected.iter().cloned()));
+    }
+}
+
+#[test]
+fn arb_origin_matches_translated_scan() {
+    for &size in SIZES {
+        for &origin in ORIGINS {
+            for &kind in &[
+                DividerKind::Strip,
+                DividerKind::Grid { tile_side: 5 },
+                DividerKind::AspectBound { max_ratio: 1 },
+            ] {
+                let expected: Vec<_> =
+                    ArbHilbertScan32::with_divider_kind(Default::default(), size, kind)
+                        .map(|p| translate(p, origin))
+                        .collect();
+                let new =
+                    || ArbHilbertScanCore::with_origin(Default::default(), size, kind, origin);
+
+                let scan: ArbHilbertScan32 = new();
+                assert_eq!(scan.origin(), origin);
+                assert_eq!(scan.clone().collect::<Vec<_>>(), expected);
+                assert_eq!(scan.last(), expected.last().cloned());
+
+                let mut scan: ArbHilbertScan32 = new();
+                let k = expected.len() / 3;
+                assert_eq!(scan.nth(k), expected.get(k).cloned());
+
+                let bytes = scan.save_state().to_bytes();
+                let snapshot = ArbScanSnapshot::from_bytes(&bytes).unwrap();
+                let resumed = ArbHilbertScan32::restore(&snapshot, Default::default());
+                assert!(resumed.eq(expected.iter().cloned().skip(k + 1)));
+
+                scan.reset();
+                assert!(scan.eq(expected.iter().cloned()));
+            }
+        }
+    }
+}
+
+#[test]
+fn origin_near_max() {
+    // The last row and column end exactly at `u8::MAX`
+    for &size in &[
+        [5u8, 7],
+        [16, 16],
+        [255, 1],
+        [1, 255],
+        [128, 3],
+        [200, 101],
+        [255, 254],
+    ] {
+        let origin = [u8::MAX - (size[0] - 1), u8::MAX - (size[1] - 1)];
+        let expected: Vec<_> = HilbertScan8::new(size)
+            .map(|[x, y]| [x + origin[0], y + origin[1]])
+            .collect();
+        let mut scan = HilbertScanCore::with_origin([LevelState::default(); 8], size, origin);
+        assert_eq!(scan.clone().collect::<Vec<_>>(), expected);
+        assert!(expected.contains(&[u8::MAX, u8::MAX]));
+        let i = expected.len() - 1;
+        assert_eq!(scan.rank(expected[i]), i as u64);
+
+        let scan = ArbHilbertScanCore::with_origin(
+            [LevelState::default(); 8],
+            size,
+            DividerKind::Strip,
+            origin,
+        );
+        assert_eq!(scan.count(), expected.len());
+
+        // Shift the origin by one along an axis where it doesn't overflow by
+        // itself
+        let mut shifted = origin;
+        let axis = (size[0] == 1) as usize;
+        shifted[axis] += 1;
+        assert_eq!(
+            HilbertScanCore::try_with_origin([LevelState::default(); 8], size, shifted)
+                .unwrap_err(),
+            ScanError::CoordinateOverflow
+        );
+        assert_eq!(
+            ArbHilbertScanCore::try_with_origin(
+                [LevelState::default(); 8],
+                size,
+                DividerKind::Strip,
+                shifted,
+            )
+            .unwrap_err(),
+            ScanError::CoordinateOverflow
+        );
+    }
+
+    // Nothing to translate in an empty rectangle
+    assert!(
+        HilbertScanCore::try_with_origin([LevelState::default(); 8], [0u8, 9], [255, 255]).is_ok()
+    );
+}
+
+#[test]
+#[should_panic(expected = "the coordinates don't fit in the output type")]
+fn set_size_checks_origin() {
+    let mut scan = new([4, 4], [u32::MAX - 3, 0]);
+    scan.set_size([5, 4]);
+}
+
+#[test]
+#[should_panic(expected = "is outside the rectangle")]
+fn rank_rejects_point_before_origin() {
+    new([4, 4], [10, 10]).rank([9, 10]);
+}
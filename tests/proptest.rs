@@ -0,0 +1,53 @@
+use proptest::prelude::*;
+use std::collections::HashSet;
+use zhang_hilbert::{ArbHilbertScan32, CurveVariant, HilbertScan32, HilbertScanCore, LevelState};
+
+/// Check that `scan` visits every cell of the `w × h` rectangle exactly once,
+/// moving by one cell at a time. Unlike the exhaustive tests, this doesn't
+/// allocate a cell map, so it's usable for long and thin rectangles.
+fn validate_curve(
+    scan: impl Iterator<Item = [u32; 2]>,
+    [w, h]: [u32; 2],
+) -> Result<(), TestCaseError> {
+    let mut visited = HashSet::new();
+    let mut last: Option<[u32; 2]> = None;
+    for [x, y] in scan {
+        prop_assert!(x < w && y < h, "{:?} is outside", [x, y]);
+        prop_assert!(visited.insert([x, y]), "{:?} is visited twice", [x, y]);
+        if let Some([px, py]) = last {
+            let step = px.max(x) - px.min(x) + py.max(y) - py.min(y);
+            prop_assert_eq!(step, 1, "{:?} -> {:?}", [px, py], [x, y]);
+        }
+        last = Some([x, y]);
+    }
+    prop_assert_eq!(visited.len() as u64, u64::from(w) * u64::from(h));
+    Ok(())
+}
+
+/// Sizes including extreme aspect ratios in both orientations.
+fn size() -> impl Strategy<Value = [u32; 2]> {
+    prop_oneof![
+        (1..=300u32, 1..=300u32).prop_map(|(w, h)| [w, h]),
+        (1..=2000u32, 1..=8u32).prop_map(|(w, h)| [w, h]),
+        (1..=8u32, 1..=2000u32).prop_map(|(w, h)| [w, h]),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn zhang_visits_every_cell(size in size()) {
+        validate_curve(HilbertScan32::new(size), size)?;
+    }
+
+    #[test]
+    fn zhang_original_visits_every_cell(size in size()) {
+        let scan =
+            HilbertScanCore::with_variant([LevelState::default(); 32], size, CurveVariant::Original);
+        validate_curve(scan, size)?;
+    }
+
+    #[test]
+    fn arb_visits_every_cell(size in size()) {
+        validate_curve(ArbHilbertScan32::new(size), size)?;
+    }
+}
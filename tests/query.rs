@@ -0,0 +1,135 @@
+use zhang_hilbert::{
+    cover_intervals, predecessor, successor, windowed, HilbertScan32, HilbertScanVec,
+};
+
+#[test]
+fn cover_intervals_matches_brute_force() {
+    let mut seed = 12345u32;
+    let mut rand = |n: u32| {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (seed >> 8) % n
+    };
+
+    for w in 1..=32 {
+        for h in 1..=32 {
+            let points: Vec<_> = HilbertScan32::new([w, h]).collect();
+            for _ in 0..8 {
+                let origin = [rand(w + 2), rand(h + 2)];
+                let size = [rand(w + 2), rand(h + 2)];
+
+                let expected: Vec<u64> = (0..points.len() as u64)
+                    .filter(|&i| {
+                        let p = points[i as usize];
+                        (0..2).all(|k| p[k] >= origin[k] && p[k] - origin[k] < size[k])
+                    })
+                    .collect();
+
+                let intervals = cover_intervals([w, h], origin, size);
+                for pair in intervals.windows(2) {
+                    // Sorted, non-overlapping, and non-adjacent
+                    assert!(pair[0].1 < pair[1].0, "{:?}", intervals);
+                }
+                let actual: Vec<u64> = intervals.iter().flat_map(|&(s, e)| s..e).collect();
+                assert_eq!(actual, expected, "{:?} {:?} {:?}", [w, h], origin, size);
+            }
+        }
+    }
+}
+
+#[test]
+fn cover_intervals_whole() {
+    for &size in &[[1u32, 1], [1, 9], [6, 7], [64, 64], [300, 201]] {
+        let len = u64::from(size[0] * size[1]);
+        assert_eq!(cover_intervals(size, [0, 0], size), [(0, len)]);
+        assert_eq!(
+            cover_intervals(size, [0, 0], [u32::MAX, u32::MAX]),
+            [(0, len)]
+        );
+        assert_eq!(cover_intervals(size, size, [1, 1]), []);
+        assert_eq!(cover_intervals(size, [0, 0], [0, 5]), []);
+    }
+}
+
+#[test]
+fn cover_intervals_large() {
+    // The number of intervals doesn't depend on the number of points
+    let size = [1u64 << 30, 3 << 29];
+    let intervals = cover_intervals(size, [12345, 67890], [3, 2]);
+    let len: u64 = intervals.iter().map(|&(s, e)| e - s).sum();
+    assert_eq!(len, 6);
+}
+
+#[test]
+fn successor_and_predecessor_follow_scan() {
+    for &size in &[
+        [1u32, 1],
+        [1, 9],
+        [9, 1],
+        [6, 7],
+        [11, 42],
+        [32, 32],
+        [40, 7],
+    ] {
+        let points: Vec<_> = HilbertScan32::new(size).collect();
+
+        let forward: Vec<_> =
+            std::iter::successors(Some(points[0]), |&p| successor(size, p)).collect();
+        assert_eq!(forward, points, "{:?}", size);
+
+        let mut backward: Vec<_> =
+            std::iter::successors(points.last().cloned(), |&p| predecessor(size, p)).collect();
+        backward.reverse();
+        assert_eq!(backward, points, "{:?}", size);
+    }
+}
+
+#[test]
+fn windowed_matches_filter() {
+    for &size in &[
+        [1u32, 1],
+        [1, 9],
+        [9, 1],
+        [6, 7],
+        [11, 42],
+        [32, 32],
+        [40, 7],
+    ] {
+        let points: Vec<_> = HilbertScan32::new(size).collect();
+        // Every window position and size, including the ones touching or
+        // crossing the edges and the 1×1 ones
+        for x in 0..=size[0] {
+            for y in 0..=size[1] {
+                for &window_size in &[[1, 1], [2, 3], [5, 1], [4, 4], [size[0] + 1, 2]] {
+                    let expected: Vec<(u64, [u32; 2])> = (0..)
+                        .zip(points.iter().cloned())
+                        .filter(|&(_, p)| {
+                            (0..2).all(|k| p[k] >= [x, y][k] && p[k] - [x, y][k] < window_size[k])
+                        })
+                        .collect();
+
+                    let scan = windowed(size, [x, y], window_size);
+                    assert_eq!(scan.size_hint().0, expected.len());
+                    let actual: Vec<_> = scan.ranked().collect();
+                    assert_eq!(
+                        actual,
+                        expected,
+                        "{:?} {:?} {:?}",
+                        size,
+                        [x, y],
+                        window_size
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn windowed_large() {
+    let size = [1u64 << 30, 3 << 29];
+    let actual: Vec<_> = windowed(size, [12345, 67890], [3, 2]).ranked().collect();
+    assert_eq!(actual.len(), 6);
+    for (index, point) in actual {
+        assert_eq!(HilbertScanVec::new_vec(size).rank(point), index);
+    }
+}
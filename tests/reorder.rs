@@ -0,0 +1,67 @@
+use zhang_hilbert::{
+    fill_index_map, reorder_from_curve, reorder_to_curve, sort_by_curve, HilbertScan32,
+};
+
+#[test]
+fn index_map_is_inverse_of_scan() {
+    for w in 0..24 {
+        for h in 0..24 {
+            let len = (w * h) as usize;
+            let mut map = vec![u32::MAX; len];
+            fill_index_map([w, h], &mut map);
+
+            // `map` is a permutation of `0..len`
+            let mut inverse = vec![None; len];
+            for (cell, &i) in map.iter().enumerate() {
+                assert!(inverse[i as usize].is_none(), "{:?} {}", [w, h], i);
+                inverse[i as usize] = Some([cell as u32 % w, cell as u32 / w]);
+            }
+
+            // Inverting `map` reproduces the scan
+            let inverse: Vec<_> = inverse.into_iter().map(Option::unwrap).collect();
+            assert_eq!(inverse, HilbertScan32::new([w, h]).collect::<Vec<_>>());
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn index_map_wrong_len() {
+    fill_index_map([3, 4], &mut [0; 11]);
+}
+
+#[test]
+fn reorder_round_trip() {
+    for w in 0..=32 {
+        for h in 0..=32 {
+            let original: Vec<u32> = (0..w * h).collect();
+
+            let mut data = original.clone();
+            reorder_to_curve([w, h], &mut data);
+            let expected: Vec<u32> = HilbertScan32::new([w, h]).map(|[x, y]| y * w + x).collect();
+            assert_eq!(data, expected, "{:?}", [w, h]);
+
+            reorder_from_curve([w, h], &mut data);
+            assert_eq!(data, original, "{:?}", [w, h]);
+        }
+    }
+}
+
+#[test]
+fn sort_by_curve_matches_scan() {
+    for &size in &[[1, 9], [6, 7], [11, 42], [32, 32], [40, 7], [300, 201]] {
+        let scan: Vec<_> = HilbertScan32::new(size).collect();
+
+        // Take every third point and shuffle them
+        let expected: Vec<_> = scan.iter().cloned().step_by(3).collect();
+        let mut points = expected.clone();
+        let mut seed = 12345u32;
+        for i in (1..points.len()).rev() {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            points.swap(i, (seed >> 8) as usize % (i + 1));
+        }
+
+        sort_by_curve(size, &mut points);
+        assert_eq!(points, expected, "{:?}", size);
+    }
+}
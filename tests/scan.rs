@@ -1,6 +1,13 @@
 use ndarray::Array2;
 
-use zhang_hilbert::{ArbHilbertScan32, HilbertScan32};
+use zhang_hilbert::{
+    checked_num_levels_for_size, levels_for_max_dim, max_levels_for_bits, num_levels_for_size,
+    num_levels_for_size_u32, num_levels_for_size_u64, storage_requirement, turn_stats, Algorithm,
+    ArbHilbertScan, ArbHilbertScan128, ArbHilbertScan16, ArbHilbertScan32, ArbHilbertScan64,
+    ArbHilbertScan8, ArbHilbertScanCore, ArbHilbertScanUsize, ArbHilbertScanVec, DividerKind,
+    HilbertScan, HilbertScan128, HilbertScan16, HilbertScan32, HilbertScan64, HilbertScan8,
+    HilbertScanCore, HilbertScanUsize, HilbertScanVec, LevelState, ScanBuilder, ScanError,
+};
 
 fn validate_curve(scan: impl Iterator<Item = [u32; 2]>, [w, h]: [u32; 2]) {
     let mut map: Array2<usize> = Array2::zeros([h as usize, w as usize]);
@@ -43,6 +50,24 @@ fn normal_scan32_patterns() {
     }
 }
 
+#[test]
+fn degenerate_scan32_patterns() {
+    for n in 1..=64 {
+        let line: Vec<_> = (0..n).map(|i| [0, i]).collect();
+        assert_eq!(HilbertScan32::new([1, n]).collect::<Vec<_>>(), line);
+        validate_curve(HilbertScan32::new([1, n]), [1, n]);
+        validate_curve(ArbHilbertScan32::new([1, n]), [1, n]);
+
+        let line: Vec<_> = (0..n).map(|i| [i, 0]).collect();
+        assert_eq!(HilbertScan32::new([n, 1]).collect::<Vec<_>>(), line);
+        validate_curve(HilbertScan32::new([n, 1]), [n, 1]);
+        validate_curve(ArbHilbertScan32::new([n, 1]), [n, 1]);
+
+        assert_eq!(num_levels_for_size([1, n]), 1);
+        assert_eq!(num_levels_for_size([n, 1]), 1);
+    }
+}
+
 #[test]
 fn arb_scan32_patterns() {
     for w in 0..32 {
@@ -52,4 +77,1558 @@ fn arb_scan32_patterns() {
             validate_curve(scan, [w, h]);
         }
     }
+
+    // Lines
+    for n in (1..300).chain(vec![1000, 4097]) {
+        let line: Vec<_> = (0..n).map(|i| [i, 0]).collect();
+        assert_eq!(ArbHilbertScan32::new([n, 1]).collect::<Vec<_>>(), line);
+        validate_curve(ArbHilbertScan32::new([n, 1]), [n, 1]);
+        assert_eq!(ArbHilbertScan32::parts([n, 1]).count(), 1);
+
+        let line: Vec<_> = (0..n).map(|i| [0, i]).collect();
+        assert_eq!(ArbHilbertScan32::new([1, n]).collect::<Vec<_>>(), line);
+        validate_curve(ArbHilbertScan32::new([1, n]), [1, n]);
+        assert_eq!(ArbHilbertScan32::parts([1, n]).count(), 1);
+    }
+
+    // Strips of two rows, which are scanned by a boustrophedon over pairs of
+    // cells. The scan leaves at the far end of the strip.
+    for n in (1..300).chain(vec![1000, 4096, 4097]) {
+        let strip: Vec<_> = (0..n)
+            .flat_map(|i| {
+                if i % 2 == 0 {
+                    [[i, 0], [i, 1]]
+                } else {
+                    [[i, 1], [i, 0]]
+                }
+            })
+            .collect();
+        assert_eq!(ArbHilbertScan32::new([n, 2]).collect::<Vec<_>>(), strip);
+        validate_curve(ArbHilbertScan32::new([n, 2]), [n, 2]);
+        assert_eq!(ArbHilbertScan32::parts([n, 2]).count(), 1);
+        assert_eq!(ArbHilbertScan32::end_point([n, 2]), Some([n - 1, n % 2]));
+
+        if n == 2 {
+            // The square is scanned along the X axis
+            continue;
+        }
+        let strip: Vec<_> = strip.iter().map(|&[x, y]| [y, x]).collect();
+        assert_eq!(ArbHilbertScan32::new([2, n]).collect::<Vec<_>>(), strip);
+        validate_curve(ArbHilbertScan32::new([2, n]), [2, n]);
+        assert_eq!(ArbHilbertScan32::parts([2, n]).count(), 1);
+        assert_eq!(ArbHilbertScan32::end_point([2, n]), Some([n % 2, n - 1]));
+    }
+}
+
+#[test]
+fn aspect_bound_scan32_patterns() {
+    for &max_ratio in &[1, 2, 3, 10] {
+        for w in 0..32 {
+            for h in 0..32 {
+                println!("=== {:?} {} ===", [w, h], max_ratio);
+                let scan = ArbHilbertScan32::with_aspect_bound([w, h], max_ratio);
+                validate_curve(scan, [w, h]);
+            }
+        }
+
+        for &size in &[[300, 3], [3, 300], [1000, 2], [257, 31]] {
+            let scan = ArbHilbertScan32::with_aspect_bound(size, max_ratio);
+            validate_curve(scan, size);
+        }
+    }
+}
+
+#[test]
+fn aspect_bound_is_guaranteed_from_2() {
+    for &max_ratio in &[2, 3, 4, 7, 10] {
+        let kind = DividerKind::AspectBound { max_ratio };
+        for w in 1..100 {
+            for &h in &[1, 2, 3, 5, 16, 33] {
+                for &size in &[[w, h], [h, w]] {
+                    for (_, [pw, ph]) in ArbHilbertScan32::parts_with_divider_kind(size, kind) {
+                        assert!(
+                            pw.max(ph) <= pw.min(ph) * max_ratio,
+                            "{:?} {} {:?}",
+                            size,
+                            max_ratio,
+                            [pw, ph]
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn grid_arb_scan32_patterns() {
+    for &size in &[
+        [1, 1],
+        [5, 1],
+        [1, 5],
+        [7, 5],
+        [40, 7],
+        [7, 40],
+        [64, 64],
+        [65, 63],
+        [101, 3],
+        [3, 101],
+        [1000, 2],
+        [2, 1000],
+        [127, 33],
+        [200, 150],
+    ] {
+        for &tile_side in &[0, 2, 3, 4, 7, 16, 33, 1000] {
+            println!("=== {:?} {} ===", size, tile_side);
+            let kind = DividerKind::Grid { tile_side };
+            let new = || ArbHilbertScan32::with_divider_kind(Default::default(), size, kind);
+            validate_curve(new(), size);
+
+            let expected: Vec<_> = new().collect();
+            assert_eq!(new().count(), expected.len());
+            assert_eq!(new().last(), expected.last().cloned());
+            assert_eq!(
+                ArbHilbertScan32::end_point_with_divider_kind(size, kind),
+                expected.last().cloned()
+            );
+            for k in (0..expected.len() + 1).step_by(7) {
+                assert_eq!(new().nth(k), expected.get(k).cloned(), "{}", k);
+            }
+        }
+    }
+}
+
+#[test]
+fn parts_match_scan() {
+    for &kind in &[
+        DividerKind::Strip,
+        DividerKind::Grid { tile_side: 5 },
+        DividerKind::AspectBound { max_ratio: 2 },
+    ] {
+        for &size in &[
+            [0, 5],
+            [1, 1],
+            [9, 1],
+            [1, 9],
+            [6, 7],
+            [40, 7],
+            [7, 40],
+            [11, 42],
+            [300, 3],
+            [3, 300],
+        ] {
+            let mut map: Array2<u32> = Array2::zeros([size[1] as usize, size[0] as usize]);
+            let mut scan = ArbHilbertScan32::with_divider_kind(Default::default(), size, kind);
+
+            for (origin, part_size) in ArbHilbertScan32::parts_with_divider_kind(size, kind) {
+                for y in origin[1]..origin[1] + part_size[1] {
+                    for x in origin[0]..origin[0] + part_size[0] {
+                        map[[y as usize, x as usize]] += 1;
+                    }
+                }
+
+                // The scan visits the part's cells before moving on to the
+                // next part
+                for _ in 0..part_size[0] * part_size[1] {
+                    let [x, y] = scan.next().unwrap();
+                    assert!(
+                        x >= origin[0]
+                            && y >= origin[1]
+                            && x - origin[0] < part_size[0]
+                            && y - origin[1] < part_size[1],
+                        "{:?} {:?} {:?}",
+                        [x, y],
+                        origin,
+                        part_size
+                    );
+                }
+            }
+            assert_eq!(scan.next(), None);
+            assert!(map.iter().all(|&count| count == 1), "{:?}", map);
+        }
+    }
+}
+
+#[test]
+fn reset_restarts_scan() {
+    for &size in &[[0, 5], [1, 7], [7, 1], [6, 7], [4, 3], [31, 17], [40, 7]] {
+        let mut scan = HilbertScan32::new(size);
+        let first: Vec<_> = scan.by_ref().collect();
+        scan.reset();
+        let second: Vec<_> = scan.by_ref().collect();
+        assert_eq!(first, second, "{:?}", size);
+
+        let mut scan = ArbHilbertScan32::new(size);
+        let first: Vec<_> = scan.by_ref().collect();
+        scan.reset();
+        let second: Vec<_> = scan.by_ref().collect();
+        assert_eq!(first, second, "{:?}", size);
+    }
+}
+
+#[test]
+fn reset_partially_consumed_scan() {
+    let expected: Vec<_> = ArbHilbertScan32::new([40, 7]).collect();
+    let mut scan = ArbHilbertScan32::new([40, 7]);
+    scan.by_ref().take(100).for_each(drop);
+    scan.reset();
+    assert_eq!(scan.collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn end_point_matches_scan() {
+    for w in 0..64 {
+        for h in 0..64 {
+            assert_eq!(
+                HilbertScan32::end_point([w, h]),
+                HilbertScan32::new([w, h]).last(),
+                "{:?}",
+                [w, h]
+            );
+            assert_eq!(
+                ArbHilbertScan32::end_point([w, h]),
+                ArbHilbertScan32::new([w, h]).last(),
+                "{:?}",
+                [w, h]
+            );
+            assert_eq!(
+                HilbertScan32::start_point([w, h]),
+                HilbertScan32::new([w, h]).next(),
+            );
+            assert_eq!(
+                ArbHilbertScan32::start_point([w, h]),
+                ArbHilbertScan32::new([w, h]).next(),
+            );
+        }
+    }
+}
+
+#[test]
+fn set_size_reuses_storage() {
+    let sizes = [[11, 42], [0, 3], [32, 32], [1, 9], [40, 7], [5, 5], [9, 1]];
+
+    let mut scan = HilbertScanCore::with_level_state_storage(
+        vec![LevelState::default(); num_levels_for_size([32u32, 32])],
+        [0, 0],
+    );
+    for &size in &sizes {
+        scan.set_size(size);
+        validate_curve(scan.by_ref(), size);
+    }
+
+    let mut scan = ArbHilbertScan32::new([0, 0]);
+    for &size in &sizes {
+        scan.set_size(size);
+        validate_curve(scan.by_ref(), size);
+    }
+}
+
+#[test]
+#[should_panic(expected = "required: 6, provided: 3")]
+fn too_small_storage() {
+    HilbertScanCore::with_level_state_storage(vec![LevelState::default(); 3], [32u32, 32]);
+}
+
+#[test]
+#[should_panic(expected = "required: 21, provided: 4")]
+fn too_small_array_storage() {
+    // Copying the pattern of `HilbertScan32` to `u64`
+    let level_states = [LevelState::<u64>::default(); 4];
+    HilbertScanCore::with_level_state_storage(level_states, [1 << 40, 1 << 20]);
+}
+
+#[test]
+#[should_panic(expected = "required: 6, provided: 3")]
+fn arb_too_small_storage() {
+    ArbHilbertScanCore::with_level_state_storage(vec![LevelState::default(); 3], [320u32, 32]);
+}
+
+#[test]
+#[should_panic(expected = "required: 6, provided: 3")]
+fn set_size_too_small_storage() {
+    let mut scan =
+        HilbertScanCore::with_level_state_storage(vec![LevelState::default(); 3], [4u32, 4]);
+    scan.set_size([32, 32]);
+}
+
+#[test]
+#[should_panic(expected = "the rectangle is too large")]
+fn set_size_rejects_unsupported_size() {
+    let mut scan = HilbertScan64::new_array([1, 1]);
+    scan.set_size([u64::MAX, u64::MAX]);
+}
+
+#[test]
+#[should_panic(expected = "the rectangle is too large")]
+fn arb_set_size_rejects_unsupported_size() {
+    let mut scan = ArbHilbertScan128::new_array([1, 1]);
+    scan.set_size([1 << 64, 1]);
+}
+
+#[test]
+fn last_and_count_match_naive() {
+    for &size in &[
+        [0, 4],
+        [1, 9],
+        [9, 1],
+        [4, 3],
+        [6, 7],
+        [11, 42],
+        [32, 32],
+        [40, 7],
+    ] {
+        let len = (size[0] * size[1]) as usize;
+        for &k in &[
+            0,
+            1,
+            2,
+            len / 3,
+            len / 2,
+            len.saturating_sub(1),
+            len,
+            len + 1,
+        ] {
+            let naive: Vec<_> = HilbertScan32::new(size).skip(k).collect();
+            let mut scan = HilbertScan32::new(size);
+            scan.by_ref().take(k).for_each(drop);
+            assert_eq!(scan.count(), naive.len(), "{:?} {}", size, k);
+            let mut scan = HilbertScan32::new(size);
+            scan.by_ref().take(k).for_each(drop);
+            assert_eq!(scan.last(), naive.last().cloned(), "{:?} {}", size, k);
+
+            let naive: Vec<_> = ArbHilbertScan32::new(size).skip(k).collect();
+            let mut scan = ArbHilbertScan32::new(size);
+            scan.by_ref().take(k).for_each(drop);
+            assert_eq!(scan.count(), naive.len(), "{:?} {}", size, k);
+            let mut scan = ArbHilbertScan32::new(size);
+            scan.by_ref().take(k).for_each(drop);
+            assert_eq!(scan.last(), naive.last().cloned(), "{:?} {}", size, k);
+        }
+    }
+}
+
+#[test]
+fn raw_parts_round_trip() {
+    // A simple LCG for choosing the pause points
+    let mut seed = 0x1234_5678u32;
+    let mut rand = move || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        seed >> 8
+    };
+
+    for &size in &[[1, 9], [4, 3], [6, 7], [11, 42], [32, 32], [40, 7]] {
+        let expected: Vec<_> = HilbertScan32::new(size).collect();
+        for _ in 0..8 {
+            let k = rand() as usize % (expected.len() + 1);
+            let mut scan = HilbertScan32::new(size);
+            scan.by_ref().take(k).for_each(drop);
+
+            let (level_states, parts) = scan.into_raw_parts();
+            let scan = HilbertScan32::from_raw_parts(level_states, parts);
+            assert_eq!(scan.collect::<Vec<_>>(), &expected[k..], "{:?} {}", size, k);
+        }
+    }
+}
+
+#[test]
+fn nth_matches_stepping() {
+    fn check<I: Iterator<Item = [u32; 2]> + Clone>(mut scan: I, len: usize, size: [u32; 2]) {
+        // Note: `skip(k).next()` can't be used as the reference because it
+        // calls `nth`
+        let naive_nth = |mut scan: I, k: usize| {
+            for _ in 0..k {
+                scan.next();
+            }
+            scan.next()
+        };
+
+        for start in [0, 1, len / 3].iter().cloned() {
+            for k in (0..len + 2).step_by(3) {
+                assert_eq!(
+                    scan.clone().nth(k),
+                    naive_nth(scan.clone(), k),
+                    "{:?} {} {}",
+                    size,
+                    start,
+                    k
+                );
+            }
+            scan.nth(start);
+        }
+    }
+
+    for w in 0..20 {
+        for h in 0..20 {
+            let len = (w * h) as usize;
+            check(HilbertScan32::new([w, h]), len, [w, h]);
+            check(ArbHilbertScan32::new([w, h]), len, [w, h]);
+        }
+    }
+
+    for &size in &[[40, 7], [114, 51], [300, 3], [2, 129]] {
+        let len = (size[0] * size[1]) as usize;
+        check(HilbertScan32::new(size), len, size);
+        check(ArbHilbertScan32::new(size), len, size);
+    }
+}
+
+#[test]
+fn nth_repeated() {
+    for &size in &[[6, 7], [31, 17], [64, 64], [40, 7]] {
+        let expected: Vec<_> = ArbHilbertScan32::new(size).collect();
+        let mut scan = ArbHilbertScan32::new(size);
+        let mut i = 0;
+        for step in (0..).map(|x| x % 11) {
+            let p = scan.nth(step);
+            assert_eq!(p, expected.get(i + step).cloned(), "{:?} {}", size, i);
+            if p.is_none() {
+                break;
+            }
+            i += step + 1;
+        }
+    }
+}
+
+#[test]
+fn size_hint_is_exact() {
+    for &size in &[[0, 5], [1, 9], [6, 7], [11, 42], [40, 7], [300, 3]] {
+        let mut scan = HilbertScan32::new(size);
+        let mut arb_scan = ArbHilbertScan32::new(size);
+        let len = (size[0] * size[1]) as usize;
+        for i in 0..=len {
+            assert_eq!(scan.size_hint(), (len - i, Some(len - i)));
+            assert_eq!(arb_scan.size_hint(), (len - i, Some(len - i)));
+            scan.next();
+            arb_scan.next();
+        }
+        assert_eq!(scan.size_hint(), (0, Some(0)));
+        assert_eq!(arb_scan.size_hint(), (0, Some(0)));
+    }
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn size_hint_is_exact_at_u64_max() {
+    // Exactly `u64::MAX` points, which is also `usize::MAX`
+    let size = [u64::MAX, 1];
+    let mut scan = HilbertScan64::new_array(size);
+    let mut arb_scan = ArbHilbertScan64::new_array(size);
+    assert_eq!(scan.size_hint(), (usize::MAX, Some(usize::MAX)));
+    assert_eq!(arb_scan.size_hint(), (usize::MAX, Some(usize::MAX)));
+    scan.next();
+    arb_scan.next();
+    assert_eq!(scan.size_hint(), (usize::MAX - 1, Some(usize::MAX - 1)));
+    assert_eq!(arb_scan.size_hint(), (usize::MAX - 1, Some(usize::MAX - 1)));
+}
+
+#[test]
+fn debug_prints_active_levels() {
+    let is_level_line =
+        |line: &&str| line.trim_start().starts_with('L') && line.contains(": size=");
+
+    for &size in &[[1, 9], [5, 6], [11, 42], [256, 256], [300, 3]] {
+        let mut scan = HilbertScan32::new(size);
+        scan.nth(7);
+        let text = format!("{:?}", scan);
+        let num_lines = text.lines().filter(is_level_line).count();
+        assert_eq!(num_lines, scan.level_states().len(), "{}", text);
+
+        let text = format!("{:?}", ArbHilbertScan32::new(size));
+        assert!(text.contains("parts: "), "{}", text);
+        assert!(text.lines().any(|line| is_level_line(&line)), "{}", text);
+    }
+}
+
+#[test]
+fn try_constructors() {
+    // Undersized storage
+    let result =
+        HilbertScanCore::try_with_level_state_storage(vec![LevelState::default(); 3], [32u32, 32]);
+    assert_eq!(
+        result.unwrap_err(),
+        ScanError::StorageTooSmall {
+            required: 6,
+            provided: 3
+        }
+    );
+    let result = ArbHilbertScanCore::try_with_level_state_storage(
+        vec![LevelState::default(); 3],
+        [320u32, 32],
+    );
+    assert_eq!(
+        result.unwrap_err(),
+        ScanError::StorageTooSmall {
+            required: 6,
+            provided: 3
+        }
+    );
+    let result = HilbertScanCore::try_with_level_state_storage(Vec::new(), [1u32, 5]);
+    assert_eq!(
+        result.unwrap_err(),
+        ScanError::StorageTooSmall {
+            required: 1,
+            provided: 0
+        }
+    );
+
+    // Zero sizes don't use the storage at all
+    for &size in &[[0u32, 0], [0, 5], [7, 0]] {
+        let scan = HilbertScanCore::try_with_level_state_storage(Vec::new(), size).unwrap();
+        assert_eq!(scan.count(), 0);
+        for &kind in &[DividerKind::Strip, DividerKind::Grid { tile_side: 4 }] {
+            let scan = ArbHilbertScanCore::try_with_divider_kind(Vec::new(), size, kind).unwrap();
+            assert_eq!(scan.count(), 0);
+        }
+    }
+
+    // Too many points
+    let result = HilbertScanCore::try_with_level_state_storage(
+        vec![LevelState::default(); 64],
+        [u64::MAX, 2],
+    );
+    assert_eq!(result.unwrap_err(), ScanError::UnsupportedSize);
+    let result = ArbHilbertScanCore::try_with_level_state_storage(
+        vec![LevelState::default(); 64],
+        [u64::MAX, 2],
+    );
+    assert_eq!(result.unwrap_err(), ScanError::UnsupportedSize);
+
+    // Success
+    for &size in &[[1u32, 1], [1, 9], [11, 42], [40, 7], [32, 32]] {
+        let storage = vec![LevelState::default(); num_levels_for_size(size)];
+        let scan = HilbertScanCore::try_with_level_state_storage(storage.clone(), size).unwrap();
+        assert!(scan.eq(HilbertScan32::new(size)));
+        let scan = ArbHilbertScanCore::try_with_level_state_storage(storage, size).unwrap();
+        assert!(scan.eq(ArbHilbertScan32::new(size)));
+    }
+}
+
+#[test]
+fn fold_matches_next() {
+    for &size in &[
+        [0, 5],
+        [1, 9],
+        [9, 1],
+        [4, 3],
+        [6, 7],
+        [11, 42],
+        [32, 32],
+        [40, 7],
+    ] {
+        let expected: Vec<_> = HilbertScan32::new(size).collect();
+        for k in 0..=expected.len() {
+            let mut scan = HilbertScan32::new(size);
+            scan.by_ref().take(k).for_each(drop);
+
+            let mut actual = Vec::new();
+            scan.for_each(|p| actual.push(p));
+            assert_eq!(actual, &expected[k..], "{:?} {}", size, k);
+        }
+    }
+}
+
+#[test]
+fn fill_buffer_matches_next() {
+    for &size in &[
+        [0, 5],
+        [1, 9],
+        [9, 1],
+        [4, 3],
+        [6, 7],
+        [11, 42],
+        [32, 32],
+        [40, 7],
+    ] {
+        let expected: Vec<_> = HilbertScan32::new(size).collect();
+        for &buffer_len in &[1, 2, 3, 7, 64] {
+            let mut scan = HilbertScan32::new(size);
+            let mut buffer = vec![[0; 2]; buffer_len];
+            let mut actual = Vec::new();
+            loop {
+                let count = scan.fill_buffer(&mut buffer);
+                if count == 0 {
+                    break;
+                }
+                actual.extend_from_slice(&buffer[..count]);
+            }
+            assert_eq!(actual, expected, "{:?} {}", size, buffer_len);
+            assert_eq!(scan.next(), None);
+        }
+    }
+}
+
+#[test]
+fn empty_rects() {
+    for &size in &[
+        [0u32, 0],
+        [0, 1],
+        [0, 10],
+        [10, 0],
+        [0, u32::MAX],
+        [u32::MAX, 0],
+    ] {
+        let mut scan = HilbertScanCore::with_level_state_storage(&mut [][..], size);
+        assert_eq!(scan.size_hint(), (0, Some(0)));
+        assert_eq!(scan.next(), None);
+        assert_eq!(scan.next(), None);
+        assert_eq!(scan.fill_buffer(&mut [[0; 2]; 4]), 0);
+
+        let mut scan = HilbertScanCore::with_level_state_storage(&mut [][..], size);
+        assert_eq!(scan.nth(3), None);
+
+        assert_eq!(HilbertScan32::new(size).count(), 0);
+        assert_eq!(HilbertScan32::new(size).last(), None);
+
+        let mut scan = ArbHilbertScanCore::with_level_state_storage(&mut [][..], size);
+        assert_eq!(scan.size_hint(), (0, Some(0)));
+        assert_eq!(scan.next(), None);
+        assert_eq!(scan.next(), None);
+
+        assert_eq!(ArbHilbertScan32::new(size).count(), 0);
+    }
+}
+
+#[test]
+fn num_levels_variants() {
+    for w in 0..200u32 {
+        for h in 0..200u32 {
+            let n = num_levels_for_size([w, h]);
+            assert_eq!(num_levels_for_size_u32([w, h]), n);
+            assert_eq!(num_levels_for_size_u64([w as u64, h as u64]), n);
+            assert_eq!(
+                checked_num_levels_for_size([w, h]),
+                if w > 1 && h > 1 { Some(n) } else { None }
+            );
+        }
+    }
+    for &size in &[[u32::MAX, u32::MAX], [u32::MAX, 2], [1 << 31, 1 << 20]] {
+        assert_eq!(num_levels_for_size_u32(size), num_levels_for_size(size));
+    }
+    for &size in &[[u64::MAX, u64::MAX], [u64::MAX, 2], [1 << 63, 1 << 40]] {
+        assert_eq!(num_levels_for_size_u64(size), num_levels_for_size(size));
+    }
+}
+
+#[test]
+fn storage_requirement_matches_usage() {
+    // Record the deepest level written by the scan
+    let sizes = (0..100u32)
+        .flat_map(|w| (0..100u32).map(move |h| [w, h]))
+        .chain(vec![
+            [1920, 1080],
+            [1024, 768],
+            [5, 3000],
+            [3000, 6],
+            [1000, 999],
+        ]);
+    for size in sizes {
+        let req = storage_requirement(size);
+        assert_eq!(req.sufficient, num_levels_for_size(size));
+
+        let mut level_states = vec![LevelState::default(); req.sufficient + 1];
+        let mut scan = HilbertScanCore::with_level_state_storage(&mut level_states[..], size);
+        while scan.next().is_some() {}
+
+        let used = level_states
+            .iter()
+            .rposition(|st| *st != LevelState::default())
+            .map_or(0, |i| i + 1);
+        assert_eq!(used, req.exact, "{:?}", size);
+    }
+}
+
+#[test]
+fn rank_matches_scan() {
+    let sizes = (1..24)
+        .flat_map(|w| (1..24).map(move |h| [w, h]))
+        .chain(vec![
+            [64, 64],
+            [100, 37],
+            [3, 200],
+            [200, 5],
+            [128, 127],
+            [257, 256],
+        ]);
+    for size in sizes {
+        let points: Vec<_> = HilbertScan32::new(size).collect();
+        let mut scan = HilbertScan32::new(size);
+        for (i, &p) in points.iter().enumerate() {
+            assert_eq!(scan.rank(p), i as u64, "{:?} {:?}", size, p);
+            assert_eq!(scan.size_hint().0, points.len() - i, "{:?} {:?}", size, p);
+            assert_eq!(scan.next(), Some(p), "{:?} {:?}", size, p);
+        }
+        // The cursor is left in a consistent state
+        let p = points[points.len() / 2];
+        scan.rank(p);
+        assert!(
+            scan.eq(points[points.len() / 2..].iter().cloned()),
+            "{:?}",
+            size
+        );
+    }
+}
+
+#[test]
+fn vec_storage() {
+    for &size in &[[0, 0], [1, 9], [6, 7], [32, 32], [40, 7]] {
+        let expected: Vec<_> = HilbertScan32::new(size).collect();
+        assert!(HilbertScanVec::new_vec(size).eq(expected.iter().cloned()));
+
+        let expected: Vec<_> = ArbHilbertScan32::new(size).collect();
+        assert!(ArbHilbertScanVec::new_vec(size).eq(expected.iter().cloned()));
+    }
+
+    // Coordinates exceeding `u32`. The area must fit in `u64`, so no
+    // supported size needs more than 32 levels.
+    let max_side = u64::from(u32::MAX);
+    for &size in &[
+        [1u64 << 40, 3 << 20],
+        [(1 << 45) + 7, 1 << 17],
+        [max_side, max_side],
+    ] {
+        assert!(num_levels_for_size(size) <= 32);
+
+        let mut scan = HilbertScanVec::new_vec(size);
+        let first: Vec<_> = scan.by_ref().take(1000).collect();
+        assert_eq!(first[0], [0, 0]);
+        for w in first.windows(2) {
+            let d = [
+                w[0][0].max(w[1][0]) - w[0][0].min(w[1][0]),
+                w[0][1].max(w[1][1]) - w[0][1].min(w[1][1]),
+            ];
+            assert_eq!(d[0] + d[1], 1, "{:?}", w);
+        }
+        assert_eq!(scan.rank(first[500]), 500);
+
+        let mut scan = ArbHilbertScanVec::new_vec(size);
+        assert_eq!(scan.next(), Some([0, 0]));
+        assert_eq!(scan.size_hint().0 as u64, size[0] * size[1] - 1);
+    }
+}
+
+#[test]
+fn const_generic_levels() {
+    assert_eq!(levels_for_max_dim(0), 1);
+    assert_eq!(levels_for_max_dim(1), 1);
+    assert_eq!(levels_for_max_dim(255), 8);
+    assert_eq!(levels_for_max_dim(256), 9);
+    assert_eq!(levels_for_max_dim(u32::MAX), 32);
+
+    fn check<const LEVELS: usize>(max_dim: u16) {
+        for &size in &[
+            [max_dim, max_dim],
+            [max_dim, 1],
+            [3, max_dim],
+            [max_dim, max_dim / 2],
+        ] {
+            let expected: Vec<_> = HilbertScanVec::new_vec(size).collect();
+            let actual: Vec<_> = HilbertScan::<u16, LEVELS>::new_array(size).collect();
+            assert_eq!(actual, expected, "{:?}", size);
+            let actual: Vec<_> = ArbHilbertScan::<u16, LEVELS>::new_array(size).collect();
+            assert_eq!(actual.len(), expected.len(), "{:?}", size);
+        }
+
+        // One too large
+        let size = [max_dim + 1, max_dim + 1];
+        let result = HilbertScan::<u16, LEVELS>::try_new_array(size);
+        if num_levels_for_size(size) > LEVELS {
+            assert_eq!(
+                result.unwrap_err(),
+                ScanError::StorageTooSmall {
+                    required: LEVELS + 1,
+                    provided: LEVELS
+                }
+            );
+            assert!(ArbHilbertScan::<u16, LEVELS>::try_new_array(size).is_err());
+        } else {
+            assert!(result.is_ok());
+        }
+    }
+
+    check::<{ levels_for_max_dim(2) }>(2);
+    check::<{ levels_for_max_dim(3) }>(3);
+    check::<{ levels_for_max_dim(15) }>(15);
+    check::<{ levels_for_max_dim(16) }>(16);
+    check::<{ levels_for_max_dim(255) }>(255);
+    check::<{ levels_for_max_dim(256) }>(256);
+    check::<{ levels_for_max_dim(300) }>(300);
+}
+
+#[test]
+fn levels_for_bits() {
+    assert_eq!(max_levels_for_bits(0), 1);
+    assert_eq!(max_levels_for_bits(1), 1);
+    for bits in 2..=32 {
+        let max = u32::MAX >> (32 - bits);
+        assert_eq!(max_levels_for_bits(bits), num_levels_for_size([max, max]));
+        assert_eq!(max_levels_for_bits(bits), levels_for_max_dim(max));
+    }
+    assert_eq!(max_levels_for_bits(64), num_levels_for_size([u64::MAX; 2]));
+
+    // More levels than needed are accepted
+    type Scan17 = HilbertScan<u16, 17>;
+    type ArbScan17 = ArbHilbertScan<u16, 17>;
+    for &size in &[[u16::MAX, 3], [1000, 999], [7, 6]] {
+        assert!(Scan17::new_array(size)
+            .take(5000)
+            .eq(HilbertScan16::new(size).take(5000)));
+        assert!(ArbScan17::new_array(size)
+            .take(5000)
+            .eq(ArbHilbertScan16::new(size).take(5000)));
+    }
+
+    // `HilbertScan32` is an instance of `HilbertScan`
+    let scan: HilbertScan<u32, { max_levels_for_bits(32) }> = HilbertScan32::new([6, 7]);
+    assert_eq!(scan.count(), 42);
+}
+
+#[test]
+#[should_panic]
+fn const_generic_levels_too_small() {
+    HilbertScan::<u16, { levels_for_max_dim(255) }>::new_array([256, 256]);
+}
+
+#[test]
+fn nonzero_sizes() {
+    use std::num::{NonZeroU32, NonZeroU8};
+    for w in 1..40 {
+        for h in 1..40 {
+            let size = [NonZeroU32::new(w).unwrap(), NonZeroU32::new(h).unwrap()];
+            assert!(HilbertScan32::new_nonzero(size).eq(HilbertScan32::new([w, h])));
+        }
+    }
+
+    let size = [NonZeroU8::new(255).unwrap(), NonZeroU8::new(13).unwrap()];
+    let scan = HilbertScanCore::<u8, [LevelState<u8>; 8]>::new_nonzero(size);
+    assert!(scan.eq(HilbertScanCore::<u8, [LevelState<u8>; 8]>::new([255, 13])));
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn huge_area() {
+    // The areas exceed `u32::MAX`
+    for &size in &[
+        [100_000, 100_000],
+        [u32::MAX, 3],
+        [70_000, 65_537],
+        [1 << 16, 1 << 16],
+    ] {
+        let total = u64::from(size[0]) * u64::from(size[1]);
+
+        let scan = HilbertScan32::new(size);
+        assert_eq!(scan.remaining_len(), total);
+        let arb_scan = ArbHilbertScan32::new(size);
+        assert_eq!(arb_scan.remaining_len(), total);
+
+        let indices = [
+            0,
+            1,
+            total / 3,
+            u64::from(u32::MAX) + 7,
+            total - 2,
+            total - 1,
+        ];
+        for &index in indices.iter().filter(|&&i| i < total) {
+            let mut scan = HilbertScan32::new(size);
+            let p = scan.nth(index as usize).unwrap();
+            assert!(p[0] < size[0] && p[1] < size[1]);
+            assert_eq!(scan.remaining_len(), total - index - 1);
+            assert_eq!(scan.rank(p), index, "{:?} {}", size, index);
+
+            // `ArbHilbertScanCore::nth` skips parts one by one
+            if size[0] / size[1] < 1000 {
+                let mut arb_scan = ArbHilbertScan32::new(size);
+                let p = arb_scan.nth(index as usize).unwrap();
+                assert!(p[0] < size[0] && p[1] < size[1]);
+                assert_eq!(arb_scan.remaining_len(), total - index - 1);
+            }
+        }
+
+        assert_eq!(
+            HilbertScan32::new(size).last(),
+            HilbertScan32::new(size).nth((total - 1) as usize)
+        );
+    }
+}
+
+fn to_u32(p: [u64; 2]) -> [u32; 2] {
+    [p[0] as u32, p[1] as u32]
+}
+
+fn to_u64(p: [u32; 2]) -> [u64; 2] {
+    [u64::from(p[0]), u64::from(p[1])]
+}
+
+#[test]
+fn scan64_patterns() {
+    for w in (0..32).step_by(3) {
+        for h in 0..32 {
+            println!("=== {:?} ===", [w, h]);
+            let size = [u64::from(w), u64::from(h)];
+            validate_curve(HilbertScan64::new_array(size).map(to_u32), [w, h]);
+            validate_curve(ArbHilbertScan64::new_array(size).map(to_u32), [w, h]);
+        }
+    }
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn scan64_matches_scan32() {
+    for &size in &[
+        [u32::MAX, u32::MAX],
+        [4_000_000_000, 3],
+        [100_000, 100_000],
+        [1 << 31, 1 << 31],
+    ] {
+        let size64 = [u64::from(size[0]), u64::from(size[1])];
+        let total = u64::from(size[0]) * u64::from(size[1]);
+
+        assert!(HilbertScan64::new_array(size64)
+            .take(1000)
+            .map(to_u32)
+            .eq(HilbertScan32::new(size).take(1000)));
+        assert!(ArbHilbertScan64::new_array(size64)
+            .take(1000)
+            .map(to_u32)
+            .eq(ArbHilbertScan32::new(size).take(1000)));
+
+        for &index in &[total / 3, total / 2 + 1, total - 1] {
+            let mut scan64 = HilbertScan64::new_array(size64);
+            let mut scan32 = HilbertScan32::new(size);
+            let p = scan32.nth(index as usize);
+            assert_eq!(
+                scan64.nth(index as usize).map(to_u32),
+                p,
+                "{:?} {}",
+                size,
+                index
+            );
+            assert_eq!(scan64.rank(to_u64(p.unwrap())), index);
+        }
+    }
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn scan64_huge_dims() {
+    // The sides exceed `u32::MAX`
+    for &size in &[[1u64 << 40, 1 << 20], [(1 << 33) + 5, (1 << 29) + 3]] {
+        let mut last: Option<[u64; 2]> = None;
+        for p in HilbertScan64::new_array(size).take(10_000) {
+            assert!(p[0] < size[0] && p[1] < size[1]);
+            if let Some(last) = last {
+                assert_eq!((last[0] != p[0]) as u32 + (last[1] != p[1]) as u32, 1);
+                assert!(last[0].max(p[0]) - last[0].min(p[0]) <= 1);
+                assert!(last[1].max(p[1]) - last[1].min(p[1]) <= 1);
+            }
+            last = Some(p);
+        }
+
+        let total = size[0] * size[1];
+        let mut scan = HilbertScan64::new_array(size);
+        let p = scan.nth((total - 1) as usize).unwrap();
+        assert_eq!(scan.next(), None);
+        assert_eq!(HilbertScan64::new_array(size).rank(p), total - 1);
+    }
+}
+
+#[test]
+fn extra_subdivision_toggle_patterns() {
+    let new = |size, extra_subdivision| {
+        HilbertScanCore::with_extra_subdivision(
+            [LevelState::default(); 32],
+            size,
+            extra_subdivision,
+        )
+    };
+
+    for w in 0..=32 {
+        for h in 0..=32 {
+            println!("=== {:?} ===", [w, h]);
+            for &extra_subdivision in &[true, false] {
+                validate_curve(new([w, h], extra_subdivision), [w, h]);
+
+                let points: Vec<_> = new([w, h], extra_subdivision).collect();
+                assert_eq!(
+                    new([w, h], extra_subdivision).last(),
+                    points.last().cloned()
+                );
+                let mut scan = new([w, h], extra_subdivision);
+                for (i, &p) in points.iter().enumerate().step_by(5) {
+                    assert_eq!(scan.rank(p), i as u64);
+                    assert_eq!(new([w, h], extra_subdivision).nth(i), Some(p));
+                }
+            }
+        }
+    }
+
+    // Enabled by default
+    assert!(HilbertScan32::new([6, 6]).eq(new([6, 6], true)));
+    assert!(!HilbertScan32::new([6, 6]).eq(new([6, 6], false)));
+    assert!(new([6, 6], true).extra_subdivision());
+    assert!(!new([6, 6], false).extra_subdivision());
+
+    // The flag is preserved by snapshots
+    let mut scan = new([13, 9], false);
+    scan.nth(30);
+    let decoded = zhang_hilbert::ScanSnapshot::from_bytes(&scan.save_state().to_bytes()).unwrap();
+    let resumed = HilbertScan32::restore(&decoded, Default::default());
+    assert!(!resumed.extra_subdivision());
+    assert!(resumed.eq(scan));
+}
+
+#[test]
+fn min_subdivision_size_patterns() {
+    let new = |size, min_subdivision_size| {
+        HilbertScanCore::with_min_subdivision_size(
+            [LevelState::default(); 32],
+            size,
+            min_subdivision_size,
+        )
+    };
+
+    for &min_subdivision_size in &[3, 4, 8] {
+        let mut num_turns = 0;
+        for w in 0..=32 {
+            for h in 0..=32 {
+                println!("=== {:?} {} ===", [w, h], min_subdivision_size);
+                validate_curve(new([w, h], min_subdivision_size), [w, h]);
+
+                let points: Vec<_> = new([w, h], min_subdivision_size).collect();
+                assert_eq!(
+                    new([w, h], min_subdivision_size).last(),
+                    points.last().cloned()
+                );
+                let mut scan = new([w, h], min_subdivision_size);
+                for (i, &p) in points.iter().enumerate().step_by(5) {
+                    assert_eq!(scan.rank(p), i as u64);
+                    assert_eq!(new([w, h], min_subdivision_size).nth(i), Some(p));
+                }
+                num_turns += turn_stats(points).num_turns;
+            }
+        }
+        // Fewer turns mean longer zigzags and worse locality
+        eprintln!(
+            "min_subdivision_size = {}: {} turns in total",
+            min_subdivision_size, num_turns
+        );
+    }
+
+    for w in 0..=32 {
+        for h in 0..=32 {
+            // `3` is the default, and the smallest blocks are too small for
+            // `8` to make any difference
+            let disabled =
+                HilbertScanCore::with_extra_subdivision([LevelState::default(); 32], [w, h], false);
+            assert!(HilbertScan32::new([w, h]).eq(new([w, h], 3)));
+            assert!(disabled.eq(new([w, h], 8)));
+        }
+    }
+    assert!(!new([7, 7], 3).eq(new([7, 7], 4)));
+
+    assert_eq!(HilbertScan32::new([6, 6]).min_subdivision_size(), 3);
+    assert_eq!(new([6, 6], 0).min_subdivision_size(), 3);
+    assert_eq!(new([6, 6], 8).min_subdivision_size(), 8);
+    assert_eq!(new([6, 6], 1000).min_subdivision_size(), 255);
+
+    // The threshold is preserved by snapshots
+    let mut scan = new([13, 9], 4);
+    scan.nth(30);
+    let decoded = zhang_hilbert::ScanSnapshot::from_bytes(&scan.save_state().to_bytes()).unwrap();
+    let resumed = HilbertScan32::restore(&decoded, Default::default());
+    assert_eq!(resumed.min_subdivision_size(), 4);
+    assert!(resumed.eq(scan));
+}
+
+#[test]
+fn arb_min_subdivision_size_patterns() {
+    let builder = |size| {
+        ScanBuilder::new(size)
+            .algorithm(Algorithm::ZhangArb)
+            .storage([LevelState::default(); 32])
+    };
+    let new = |size, min_subdivision_size| {
+        builder(size)
+            .min_subdivision_size(min_subdivision_size)
+            .build()
+            .into_arb()
+            .unwrap()
+    };
+
+    for w in 0..=40 {
+        for h in 0..=40 {
+            for &min_subdivision_size in &[4, 8] {
+                validate_curve(new([w, h], min_subdivision_size), [w, h]);
+                let points: Vec<_> = new([w, h], min_subdivision_size).collect();
+                assert_eq!(
+                    new([w, h], min_subdivision_size).last(),
+                    points.last().cloned()
+                );
+            }
+            let disabled = builder([w, h]).extra_subdivision(false).build();
+            assert!(ArbHilbertScan32::new([w, h]).eq(new([w, h], 3)));
+            assert!(disabled.eq(new([w, h], 8)));
+        }
+    }
+
+    // Every part is affected, not just the first one
+    let size = [100, 7];
+    let first_part_len = ArbHilbertScan32::parts(size).next().unwrap().1[0] as usize * 7;
+    let default: Vec<_> = new(size, 3).collect();
+    let raised: Vec<_> = new(size, 8).collect();
+    let last_diff = (0..default.len()).rev().find(|&i| default[i] != raised[i]);
+    assert!(last_diff.unwrap() >= first_part_len);
+
+    // The threshold is preserved by snapshots and `reset`
+    let mut scan = new(size, 8);
+    scan.nth(300);
+    let decoded =
+        zhang_hilbert::ArbScanSnapshot::from_bytes(&scan.save_state().to_bytes()).unwrap();
+    let resumed = ArbHilbertScan32::restore(&decoded, Default::default());
+    assert!(resumed.eq(raised[301..].iter().cloned()));
+    scan.reset();
+    assert!(scan.eq(raised));
+}
+
+#[test]
+fn current_curve_type_samples() {
+    let mut scan = HilbertScan32::new([6, 7]);
+    // The first basic block (type 1 with a helper row)
+    assert_eq!(scan.current_curve_type(), 1);
+    let types: Vec<_> = std::iter::from_fn(|| {
+        let p = scan.next()?;
+        Some((p, scan.current_curve_type()))
+    })
+    .collect();
+    assert_eq!(types[0], ([0, 0], 1));
+    assert_eq!(types[5], ([1, 2], 1));
+    assert_eq!(types[6], ([1, 3], 7));
+    assert_eq!(types[14], ([2, 6], 6));
+    assert_eq!(types[18], ([4, 6], 5));
+    assert_eq!(types[26], ([3, 3], 4));
+    assert_eq!(types[41], ([5, 0], 5));
+    // The last point remains current after the scan is complete
+    assert_eq!(scan.current_curve_type(), 5);
+
+    for w in 1..24 {
+        for h in 1..24 {
+            let mut scan = HilbertScan32::new([w, h]);
+            let types: Vec<_> = std::iter::from_fn(|| {
+                let p = scan.next()?;
+                Some((p, scan.current_curve_type()))
+            })
+            .collect();
+
+            for (i, &(p, curve_type)) in types.iter().enumerate() {
+                assert!(curve_type < 8);
+
+                // `rank` moves the cursor to `p` without producing it
+                let mut scan = HilbertScan32::new([w, h]);
+                assert_eq!(scan.rank(p), i as u64);
+                assert_eq!(
+                    scan.current_curve_type(),
+                    curve_type,
+                    "{:?} {:?}",
+                    [w, h],
+                    p
+                );
+
+                if i % 5 == 0 {
+                    let mut scan = HilbertScan32::new([w, h]);
+                    scan.nth(i);
+                    assert_eq!(
+                        scan.current_curve_type(),
+                        curve_type,
+                        "{:?} {:?}",
+                        [w, h],
+                        p
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn widen<T: Into<u32>>(p: [T; 2]) -> [u32; 2] {
+    let [x, y] = p;
+    [x.into(), y.into()]
+}
+
+#[test]
+fn scan8_patterns() {
+    // The sides close to the boundaries of the levels and `u8::MAX`
+    for &w in &[
+        0u8, 1, 2, 3, 4, 63, 64, 65, 127, 128, 129, 191, 192, 253, 254, 255,
+    ] {
+        for h in (0..=255u8).step_by(5).chain(253..=255) {
+            let size = [w, h];
+            let size32 = widen(size);
+            assert!(HilbertScan8::new(size)
+                .map(widen)
+                .eq(HilbertScan32::new(size32)));
+            assert!(ArbHilbertScan8::new([h, w])
+                .map(widen)
+                .eq(ArbHilbertScan32::new([size32[1], size32[0]])));
+        }
+    }
+
+    for &size in &[[255u8, 255], [255, 254], [254, 255], [255, 3], [193, 255]] {
+        validate_curve(HilbertScan8::new(size).map(widen), widen(size));
+        validate_curve(ArbHilbertScan8::new(size).map(widen), widen(size));
+    }
+}
+
+#[test]
+#[ignore]
+fn scan8_patterns_exhaustive() {
+    // `validate_curve` would print too much for this many points
+    fn validate_quietly(scan: impl Iterator<Item = [u8; 2]>, [w, h]: [u8; 2]) {
+        let mut visited = vec![false; usize::from(w) * usize::from(h)];
+        let mut last: Option<[u8; 2]> = None;
+        for p in scan {
+            let i = usize::from(p[0]) + usize::from(p[1]) * usize::from(w);
+            assert!(p[0] < w && p[1] < h && !visited[i], "{:?} {:?}", [w, h], p);
+            visited[i] = true;
+            if let Some(last) = last {
+                let d = |a: u8, b: u8| a.max(b) - a.min(b);
+                assert_eq!(
+                    d(last[0], p[0]) + d(last[1], p[1]),
+                    1,
+                    "{:?} {:?}",
+                    [w, h],
+                    p
+                );
+            }
+            last = Some(p);
+        }
+        assert!(visited.iter().all(|&x| x), "{:?}", [w, h]);
+    }
+
+    for w in 0..=255u8 {
+        for h in 0..=255u8 {
+            validate_quietly(HilbertScan8::new([w, h]), [w, h]);
+            validate_quietly(ArbHilbertScan8::new([w, h]), [w, h]);
+        }
+    }
+}
+
+#[test]
+#[ignore]
+fn scan16_patterns_exhaustive() {
+    // One bit per cell to keep the memory usage low
+    fn validate_quietly(scan: impl Iterator<Item = [u16; 2]>, [w, h]: [u16; 2]) {
+        let area = usize::from(w) * usize::from(h);
+        let mut visited = vec![0u64; area.div_ceil(64)];
+        let mut last: Option<[u16; 2]> = None;
+        let mut count = 0;
+        for p in scan {
+            let i = usize::from(p[0]) + usize::from(p[1]) * usize::from(w);
+            let (word, bit) = (i / 64, 1 << (i % 64));
+            assert!(
+                p[0] < w && p[1] < h && visited[word] & bit == 0,
+                "{:?} {:?}",
+                [w, h],
+                p
+            );
+            visited[word] |= bit;
+            if let Some(last) = last {
+                let d = |a: u16, b: u16| a.max(b) - a.min(b);
+                assert_eq!(
+                    d(last[0], p[0]) + d(last[1], p[1]),
+                    1,
+                    "{:?} {:?}",
+                    [w, h],
+                    p
+                );
+            }
+            last = Some(p);
+            count += 1;
+        }
+        // Every point is in bounds and distinct, so this means every cell
+        // was visited
+        assert_eq!(count, area, "{:?}", [w, h]);
+    }
+
+    for w in 0..=256u16 {
+        for h in 0..=256u16 {
+            validate_quietly(HilbertScan16::new([w, h]), [w, h]);
+            validate_quietly(ArbHilbertScan16::new([w, h]), [w, h]);
+        }
+    }
+}
+
+#[test]
+fn scan16_patterns() {
+    let sides = [
+        2u16, 3, 5, 17, 255, 256, 257, 1000, 4097, 32767, 32768, 40001, 65534, 65535,
+    ];
+    for &w in &sides {
+        for &h in &sides {
+            let size = [w, h];
+            let size32 = widen(size);
+            let total = u64::from(w) * u64::from(h);
+
+            assert!(HilbertScan16::new(size)
+                .take(3000)
+                .map(widen)
+                .eq(HilbertScan32::new(size32).take(3000)));
+            assert!(ArbHilbertScan16::new(size)
+                .take(3000)
+                .map(widen)
+                .eq(ArbHilbertScan32::new(size32).take(3000)));
+            assert_eq!(
+                HilbertScan16::new(size).last().map(widen),
+                HilbertScan32::new(size32).last()
+            );
+
+            for &index in &[total / 3, total / 2 + 1, total - 1] {
+                let p = HilbertScan32::new(size32).nth(index as usize);
+                let mut scan = HilbertScan16::new(size);
+                assert_eq!(scan.nth(index as usize).map(widen), p, "{:?}", size);
+                let p = p.unwrap();
+                assert_eq!(scan.rank([p[0] as u16, p[1] as u16]), index);
+            }
+        }
+    }
+
+    for &size in &[[65535u16, 3], [3, 65535], [1000, 1001], [257, 255]] {
+        validate_curve(HilbertScan16::new(size).map(widen), widen(size));
+        validate_curve(ArbHilbertScan16::new(size).map(widen), widen(size));
+    }
+}
+
+#[test]
+fn aspect_bound_saturates_narrow_width() {
+    // `171 * 2` doesn't fit in `u8`
+    let kind8 = DividerKind::AspectBound { max_ratio: 2 };
+    let kind32 = DividerKind::AspectBound { max_ratio: 2 };
+    assert!(
+        ArbHilbertScanCore::with_divider_kind([LevelState::default(); 8], [255u8, 171], kind8)
+            .map(widen)
+            .eq(ArbHilbertScanCore::with_divider_kind(
+                [LevelState::default(); 32],
+                [255u32, 171],
+                kind32
+            ))
+    );
+}
+
+#[test]
+fn accessors() {
+    for &size in &[
+        [0, 0],
+        [0, 5],
+        [1, 9],
+        [11, 42],
+        [42, 11],
+        [32, 32],
+        [1920, 1080],
+    ] {
+        let scan = HilbertScan32::new(size);
+        assert_eq!(scan.size(), size);
+        assert_eq!(scan.level_states().len(), scan.num_levels());
+        if size[0] > 0 && size[1] > 0 {
+            assert_eq!(scan.num_levels(), num_levels_for_size(size));
+        }
+
+        let mut scan = ArbHilbertScan32::new(size);
+        assert_eq!(scan.size(), size);
+        let major_axis = scan.major_axis();
+        let empty = size[0] == 0 || size[1] == 0;
+        assert_eq!(major_axis, (!empty && size[1] > size[0]) as usize);
+
+        // The current part follows the produced points
+        let parts = ArbHilbertScan32::parts(size);
+        for (origin, part_size) in parts {
+            let len = part_size[0] * part_size[1];
+            for _ in 0..len {
+                scan.next().unwrap();
+                assert_eq!(scan.part_pos(), origin[major_axis]);
+                assert_eq!(scan.part_len(), part_size[major_axis]);
+                assert!(scan.level_states().len() <= num_levels_for_size(size));
+            }
+        }
+        assert_eq!(scan.next(), None);
+    }
+}
+
+#[test]
+fn scan_usize_patterns() {
+    let narrow = |p: [usize; 2]| [p[0] as u32, p[1] as u32];
+    for w in (0..32).step_by(5) {
+        for h in 0..32 {
+            let size = [w as usize, h as usize];
+            validate_curve(HilbertScanUsize::new_array(size).map(narrow), [w, h]);
+            validate_curve(ArbHilbertScanUsize::new_array(size).map(narrow), [w, h]);
+        }
+    }
+
+    assert_eq!(
+        num_levels_for_size([usize::MAX, usize::MAX]),
+        usize::BITS as usize
+    );
+
+    // The sides close to the boundaries of `usize`, whichever width it has.
+    // The areas must fit in `u64`. The `u64` scans of the same sizes serve
+    // as references.
+    let widen = |p: [usize; 2]| [p[0] as u64, p[1] as u64];
+    let half = usize::MAX / 2 + 1;
+    for &size in &[
+        [usize::MAX, 1],
+        [half - 1, 2],
+        [usize::MAX / 3, 3],
+        [usize::MAX / 5 - 1, 5],
+        [u32::MAX as usize, 7],
+        [3, usize::MAX / 3],
+    ] {
+        let size64 = widen(size);
+        assert!(HilbertScanUsize::new_array(size)
+            .take(2000)
+            .map(widen)
+            .eq(HilbertScan64::new_array(size64).take(2000)));
+        assert!(ArbHilbertScanUsize::new_array(size)
+            .take(2000)
+            .map(widen)
+            .eq(ArbHilbertScan64::new_array(size64).take(2000)));
+
+        let mut scan = HilbertScanUsize::new_array(size);
+        let last = scan.clone().last().unwrap();
+        assert_eq!(Some(widen(last)), HilbertScan64::new_array(size64).last());
+        let index = scan.rank(last);
+        assert_eq!(scan.next(), Some(last));
+        assert_eq!(scan.next(), None);
+        assert_eq!(index, size64[0] * size64[1] - 1);
+    }
+}
+
+#[test]
+fn reuse_level_states() {
+    // The storage is sized for the largest scan
+    let mut level_states = vec![LevelState::default(); num_levels_for_size([64u32, 64])];
+    let ptr = level_states.as_ptr();
+
+    for &size in &[[64, 64], [40, 7], [6, 7], [0, 3], [63, 50]] {
+        let mut scan = HilbertScanCore::with_level_state_storage(level_states, size);
+        assert!(scan.by_ref().eq(HilbertScan32::new(size)));
+        level_states = scan.into_level_states();
+
+        // Partially consumed scans also give back their storage
+        let mut scan = ArbHilbertScanCore::with_level_state_storage(level_states, size);
+        let half = (size[0] * size[1] / 2) as usize;
+        assert!(scan
+            .by_ref()
+            .take(half)
+            .eq(ArbHilbertScan32::new(size).take(half)));
+        level_states = scan.into_level_states();
+    }
+
+    assert_eq!(level_states.as_ptr(), ptr);
+}
+
+#[test]
+fn scan128_patterns() {
+    let narrow = |p: [u128; 2]| [p[0] as u32, p[1] as u32];
+    for w in (0..32u32).step_by(7) {
+        for h in 0..32u32 {
+            let size = [u128::from(w), u128::from(h)];
+            validate_curve(HilbertScan128::new_array(size).map(narrow), [w, h]);
+            validate_curve(ArbHilbertScan128::new_array(size).map(narrow), [w, h]);
+        }
+    }
+
+    for &size in &[[1000u32, 999], [4096, 4096], [40, 7000]] {
+        let size128 = [u128::from(size[0]), u128::from(size[1])];
+        assert!(HilbertScan128::new_array(size128)
+            .take(3000)
+            .map(narrow)
+            .eq(HilbertScan32::new(size).take(3000)));
+        assert!(ArbHilbertScan128::new_array(size128)
+            .take(3000)
+            .map(narrow)
+            .eq(ArbHilbertScan32::new(size).take(3000)));
+    }
+
+    // The number of points must fit in `u64`
+    assert_eq!(
+        HilbertScan128::try_new_array([1 << 64, 2]).unwrap_err(),
+        ScanError::UnsupportedSize
+    );
+    assert!(HilbertScan128::try_new_array([1 << 100, 0]).is_ok());
+}
+
+#[test]
+fn scan128_huge_coordinates() {
+    // Sides close to `u64::MAX`, compared against the `u64` scans
+    let widen = |p: [u64; 2]| [u128::from(p[0]), u128::from(p[1])];
+    for &size in &[
+        [u64::MAX, 1],
+        [u64::MAX / 3, 3],
+        [(1 << 63) - 1, 2],
+        [3, u64::MAX / 5],
+    ] {
+        let size128 = widen(size);
+        let total = size[0] * size[1];
+        for &index in &[0, 1, total / 3, total / 2 + 1, total - 2, total - 1] {
+            let expected = HilbertScan64::new_array(size)
+                .nth(index as usize)
+                .map(widen);
+            let mut scan = HilbertScan128::new_array(size128);
+            assert_eq!(scan.nth(index as usize), expected, "{:?} {}", size, index);
+            assert_eq!(scan.rank(expected.unwrap()), index);
+        }
+        assert_eq!(
+            HilbertScan128::new_array(size128).last(),
+            HilbertScan64::new_array(size).last().map(widen)
+        );
+    }
+
+    // The end points can be calculated for any size
+    let end_point = HilbertScan128::end_point;
+    assert_eq!(end_point([1 << 100, 1 << 100]), Some([(1 << 100) - 1, 0]));
+    assert_eq!(
+        end_point([u128::MAX - 1, u128::MAX]),
+        Some([u128::MAX - 2, 0])
+    );
+    for &size in &[
+        [(1u64 << 60) + 6, (1 << 60) + 7],
+        [(1 << 61) + 5, 1 << 40],
+        [u64::MAX, 9],
+    ] {
+        assert_eq!(
+            end_point(widen(size)),
+            HilbertScan64::end_point(size).map(widen)
+        );
+    }
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn scan_wide_area_across_widths() {
+    // The number of points (10^10) overflows `u32`
+    let size = [100_000u32, 100_000];
+    let size64 = to_u64(size);
+    let size128 = [u128::from(size[0]), u128::from(size[1])];
+    let total = 100_000u64 * 100_000;
+
+    assert_eq!(num_levels_for_size(size64), num_levels_for_size(size));
+    assert_eq!(num_levels_for_size(size128), num_levels_for_size(size));
+
+    let narrow = |p: [u128; 2]| [p[0] as u32, p[1] as u32];
+    assert!(HilbertScan128::new_array(size128)
+        .take(5000)
+        .map(narrow)
+        .eq(HilbertScan64::new_array(size64).take(5000).map(to_u32)));
+    assert!(ArbHilbertScan128::new_array(size128)
+        .take(5000)
+        .map(narrow)
+        .eq(ArbHilbertScan64::new_array(size64).take(5000).map(to_u32)));
+
+    for &index in &[u64::from(u32::MAX), u64::from(u32::MAX) + 1, total - 1] {
+        let mut scan64 = HilbertScan64::new_array(size64);
+        let mut scan128 = HilbertScan128::new_array(size128);
+        let p = scan64.nth(index as usize).unwrap();
+        assert_eq!(scan128.nth(index as usize).map(narrow), Some(to_u32(p)));
+        assert_eq!(scan64.next().map(to_u32), scan128.next().map(narrow));
+        assert_eq!(HilbertScan64::new_array(size64).rank(p), index);
+    }
 }
@@ -0,0 +1,49 @@
+//! Compile-time checks that the iterator types can be moved to and shared
+//! between threads.
+use zhang_hilbert::{
+    ArbHilbertScan32, ArbHilbertScan64, ArbHilbertScanCore, ArbHilbertScanVec, ArbScanSnapshot,
+    Centered, Checkpointed, HilbertScan32, HilbertScan64, HilbertScanCore, HilbertScanVec,
+    LevelState, Normalized, ScanAny32, ScanSnapshot, Translated, Tuples,
+};
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+fn assert_send_sync<T: Send + Sync>() {
+    assert_send::<T>();
+    assert_sync::<T>();
+}
+
+#[test]
+fn scans_are_send_sync() {
+    assert_send_sync::<HilbertScan32>();
+    assert_send_sync::<ArbHilbertScan32>();
+    assert_send_sync::<HilbertScan64>();
+    assert_send_sync::<ArbHilbertScan64>();
+    assert_send_sync::<HilbertScanVec<u32>>();
+    assert_send_sync::<ArbHilbertScanVec<u32>>();
+    assert_send_sync::<HilbertScanCore<u32, &mut [LevelState<u32>]>>();
+    assert_send_sync::<ArbHilbertScanCore<u32, &mut [LevelState<u32>]>>();
+    assert_send_sync::<ScanAny32>();
+}
+
+#[test]
+fn adapters_and_snapshots_are_send_sync() {
+    assert_send_sync::<Normalized<HilbertScan32, f32>>();
+    assert_send_sync::<Centered<ArbHilbertScan32, i64>>();
+    assert_send_sync::<Translated<HilbertScan32, i32>>();
+    assert_send_sync::<Tuples<ArbHilbertScan32>>();
+    assert_send_sync::<Checkpointed<HilbertScan32>>();
+    assert_send_sync::<ScanSnapshot<u32>>();
+    assert_send_sync::<ArbScanSnapshot<u32>>();
+}
+
+#[test]
+fn scan_on_another_thread() {
+    let scan = ArbHilbertScan32::new([40, 7]);
+    let expected: Vec<_> = scan.clone().collect();
+    let points = std::thread::spawn(move || scan.collect::<Vec<_>>())
+        .join()
+        .unwrap();
+    assert_eq!(points, expected);
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "serde")]
+use zhang_hilbert::{
+    ArbHilbertScan32, ArbScanSnapshot, DividerKind, HilbertScan32, LevelState, ScanSnapshot,
+};
+
+#[test]
+fn scan_snapshot_round_trip() {
+    for &size in &[[1, 9], [4, 3], [6, 7], [11, 42], [32, 32], [40, 7]] {
+        let expected: Vec<_> = HilbertScan32::new(size).collect();
+        for &k in &[0, 1, expected.len() / 3, expected.len() - 1, expected.len()] {
+            let mut scan = HilbertScan32::new(size);
+            scan.by_ref().take(k).for_each(drop);
+            let state = scan.save_state();
+
+            let json = serde_json::to_string(&state).unwrap();
+            let state2: ScanSnapshot<u32> = serde_json::from_str(&json).unwrap();
+            let scan = HilbertScan32::restore(&state2, [LevelState::default(); 32]);
+            assert_eq!(scan.collect::<Vec<_>>(), &expected[k..], "{:?} {}", size, k);
+
+            let bytes = bincode::serialize(&state).unwrap();
+            let state2: ScanSnapshot<u32> = bincode::deserialize(&bytes).unwrap();
+            let scan = HilbertScan32::restore(&state2, [LevelState::default(); 32]);
+            assert_eq!(scan.collect::<Vec<_>>(), &expected[k..], "{:?} {}", size, k);
+        }
+    }
+}
+
+#[test]
+fn arb_scan_snapshot_round_trip() {
+    for &kind in &[
+        DividerKind::Strip,
+        DividerKind::Grid { tile_side: 5 },
+        DividerKind::AspectBound { max_ratio: 3 },
+    ] {
+        for &size in &[[1, 9], [6, 7], [11, 42], [40, 7], [300, 3]] {
+            let new = || ArbHilbertScan32::with_divider_kind(Default::default(), size, kind);
+            let expected: Vec<_> = new().collect();
+            for &k in &[0, 1, expected.len() / 3, expected.len() - 1, expected.len()] {
+                let mut scan = new();
+                scan.by_ref().take(k).for_each(drop);
+                let state = scan.save_state();
+
+                let json = serde_json::to_string(&state).unwrap();
+                let state2: ArbScanSnapshot<u32> = serde_json::from_str(&json).unwrap();
+                let scan = ArbHilbertScan32::restore(&state2, Default::default());
+                assert_eq!(scan.collect::<Vec<_>>(), &expected[k..], "{:?} {}", size, k);
+
+                let bytes = bincode::serialize(&state).unwrap();
+                let state2: ArbScanSnapshot<u32> = bincode::deserialize(&bytes).unwrap();
+                let scan = ArbHilbertScan32::restore(&state2, Default::default());
+                assert_eq!(scan.collect::<Vec<_>>(), &expected[k..], "{:?} {}", size, k);
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use zhang_hilbert::{
+    write_csv, write_json, write_points_csv, write_points_json, write_tsv, ArbHilbertScan32,
+};
+
+#[test]
+fn write_2x2() {
+    let mut out = Vec::new();
+    write_csv(&mut out, [2, 2]).unwrap();
+    assert_eq!(out, b"0, 0\n0, 1\n1, 1\n1, 0\n");
+
+    let mut out = Vec::new();
+    write_tsv(&mut out, [2, 2]).unwrap();
+    assert_eq!(out, b"0\t0\n0\t1\n1\t1\n1\t0\n");
+
+    let mut out = Vec::new();
+    write_json(&mut out, [2, 2]).unwrap();
+    assert_eq!(
+        out,
+        b"[\n  [0, 0],\n  [0, 1],\n  [1, 1],\n  [1, 0]\n]\n" as &[u8]
+    );
+}
+
+#[test]
+fn write_empty() {
+    let mut out = Vec::new();
+    write_csv(&mut out, [0, 5]).unwrap();
+    assert_eq!(out, b"");
+
+    let mut out = Vec::new();
+    write_json(&mut out, [0, 5]).unwrap();
+    assert_eq!(out, b"[\n]\n");
+}
+
+#[test]
+fn write_json_parses() {
+    let size = [40, 7];
+    let mut out = Vec::new();
+    write_points_json(&mut out, ArbHilbertScan32::new(size)).unwrap();
+    let points: Vec<[u32; 2]> = serde_json::from_slice(&out).unwrap();
+    assert!(points.into_iter().eq(ArbHilbertScan32::new(size)));
+}
+
+#[test]
+fn write_propagates_errors() {
+    let mut buffer = [0u8; 10];
+    let err = write_points_csv(&mut buffer[..], ArbHilbertScan32::new([4, 4])).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+}
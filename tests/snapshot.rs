@@ -0,0 +1,139 @@
+use zhang_hilbert::{
+    ArbHilbertScan32, ArbScanSnapshot, DividerKind, HilbertScan32, ScanSnapshot, SnapshotError,
+};
+
+/// A simple LCG
+fn rng(mut seed: u32) -> impl FnMut() -> u32 {
+    move || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        seed >> 8
+    }
+}
+
+#[test]
+fn snapshot_bytes_round_trip() {
+    for &size in &[
+        [0, 4],
+        [1, 9],
+        [9, 1],
+        [4, 3],
+        [6, 7],
+        [11, 42],
+        [32, 32],
+        [40, 7],
+    ] {
+        let expected: Vec<_> = HilbertScan32::new(size).collect();
+        let mut scan = HilbertScan32::new(size);
+        for k in 0..=expected.len() {
+            let snapshot = scan.save_state();
+            let bytes = snapshot.to_bytes();
+            assert!(bytes.len() < 300, "{:?} {}", size, bytes.len());
+
+            let decoded = ScanSnapshot::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded, snapshot);
+            let resumed = HilbertScan32::restore(&decoded, Default::default());
+            assert_eq!(
+                resumed.collect::<Vec<_>>(),
+                &expected[k..],
+                "{:?} {}",
+                size,
+                k
+            );
+
+            scan.next();
+        }
+    }
+}
+
+#[test]
+fn arb_snapshot_bytes_round_trip() {
+    for &kind in &[
+        DividerKind::Strip,
+        DividerKind::Grid { tile_side: 5 },
+        DividerKind::AspectBound { max_ratio: 3 },
+    ] {
+        for &size in &[[0, 4], [1, 9], [9, 1], [6, 7], [11, 42], [40, 7], [300, 3]] {
+            let new = || ArbHilbertScan32::with_divider_kind(Default::default(), size, kind);
+            let expected: Vec<_> = new().collect();
+            let mut scan = new();
+            for k in 0..=expected.len() {
+                let snapshot = scan.save_state();
+                let decoded = ArbScanSnapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+                assert_eq!(decoded, snapshot);
+                let resumed = ArbHilbertScan32::restore(&decoded, Default::default());
+                assert_eq!(
+                    resumed.collect::<Vec<_>>(),
+                    &expected[k..],
+                    "{:?} {}",
+                    size,
+                    k
+                );
+
+                scan.next();
+            }
+        }
+    }
+}
+
+#[test]
+fn snapshot_bytes_corrupted() {
+    let mut rand = rng(0x1234_5678);
+
+    let mut scan = HilbertScan32::new([11, 42]);
+    scan.nth(200);
+    let bytes = scan.save_state().to_bytes();
+
+    let mut arb_scan = ArbHilbertScan32::new([300, 7]);
+    arb_scan.nth(1000);
+    let arb_bytes = arb_scan.save_state().to_bytes();
+
+    // Header errors
+    assert_eq!(
+        ScanSnapshot::<u32>::from_bytes(&[]),
+        Err(SnapshotError::Truncated)
+    );
+    assert_eq!(
+        ScanSnapshot::<u32>::from_bytes(&arb_bytes),
+        Err(SnapshotError::BadMagic)
+    );
+    assert_eq!(
+        ArbScanSnapshot::<u32>::from_bytes(&bytes),
+        Err(SnapshotError::BadMagic)
+    );
+    let mut future = bytes.clone();
+    future[1] = 0xff;
+    assert_eq!(
+        ScanSnapshot::<u32>::from_bytes(&future),
+        Err(SnapshotError::UnsupportedVersion(0xff))
+    );
+
+    for _ in 0..2000 {
+        for &(bytes, is_arb) in &[(&bytes, false), (&arb_bytes, true)] {
+            let mut corrupted = bytes.clone();
+            match rand() % 3 {
+                0 => {
+                    // Flip some bits
+                    let i = rand() as usize % corrupted.len();
+                    corrupted[i] ^= (rand() % 255 + 1) as u8;
+                }
+                1 => {
+                    // Truncate
+                    let len = rand() as usize % corrupted.len();
+                    corrupted.truncate(len);
+                }
+                _ => {
+                    // Replace with garbage
+                    for x in corrupted.iter_mut() {
+                        *x = rand() as u8;
+                    }
+                }
+            }
+
+            if is_arb {
+                assert!(ArbScanSnapshot::<u32>::from_bytes(&corrupted).is_err());
+            } else {
+                assert!(ScanSnapshot::<u32>::from_bytes(&corrupted).is_err());
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use zhang_hilbert::{
+    locality_stats, locality_stats_with_algorithm, turn_stats, Algorithm, HilbertScan32, TurnStats,
+};
+
+#[test]
+fn square_has_better_locality_than_strip() {
+    // Both have 65536 cells
+    let square = locality_stats_with_algorithm(Algorithm::Zhang, [256, 256]);
+    let strip = locality_stats_with_algorithm(Algorithm::Zhang, [16384, 4]);
+    assert!(
+        square.mean_step < strip.mean_step,
+        "{:?} {:?}",
+        square,
+        strip
+    );
+
+    // `ZhangArb` divides the strip into parts closer to square
+    let arb_strip = locality_stats_with_algorithm(Algorithm::ZhangArb, [16384, 4]);
+    assert!(arb_strip.mean_step < strip.mean_step);
+    assert!(arb_strip.max_step < strip.max_step);
+}
+
+#[test]
+fn lines() {
+    for len in 1..20 {
+        for &size in &[[len, 1], [1, len]] {
+            let stats = locality_stats(size);
+            let expected_mean = if len == 1 { 0.0 } else { 1.0 };
+            assert_eq!(stats.mean_step, expected_mean);
+            assert_eq!(stats.max_step, (len > 1) as u64);
+            assert_eq!(stats.turns, 0);
+        }
+    }
+}
+
+#[test]
+fn empty() {
+    for &size in &[[0, 0], [0, 5], [5, 0]] {
+        let stats = locality_stats(size);
+        assert_eq!(stats.mean_step, 0.0);
+        assert_eq!(stats.max_step, 0);
+        assert_eq!(stats.turns, 0);
+    }
+}
+
+#[test]
+fn four_by_four() {
+    //  5 ─ 6   9 ─ 10
+    //  │   │   │    │
+    //  4   7 ─ 8   11
+    //  │            │
+    //  3 ─ 2  13 ─ 12
+    //      │   │
+    //  0 ─ 1  14 ─ 15
+    //
+    // Straight at 4 and 11
+    let stats = locality_stats_with_algorithm(Algorithm::Zhang, [4, 4]);
+    assert_eq!(stats.turns, 12);
+    assert_eq!(stats.max_step, 13);
+
+    assert_eq!(
+        turn_stats(HilbertScan32::new([4, 4])),
+        TurnStats {
+            num_turns: 12,
+            num_straight: 2,
+            longest_run: 2,
+        }
+    );
+}
+
+#[test]
+fn turn_stats_of_short_paths() {
+    assert_eq!(turn_stats(Vec::<[u32; 2]>::new()), TurnStats::default());
+    assert_eq!(turn_stats(vec![[0u32, 0]]), TurnStats::default());
+    assert_eq!(
+        turn_stats(HilbertScan32::new([5, 1])),
+        TurnStats {
+            num_turns: 0,
+            num_straight: 3,
+            longest_run: 4,
+        }
+    );
+}
+
+#[test]
+fn turns_and_straights_add_up() {
+    for w in 1..20 {
+        for h in 1..20 {
+            let stats = turn_stats(HilbertScan32::new([w, h]));
+            let num_moves = (w * h - 1) as u64;
+            assert_eq!(
+                stats.num_turns + stats.num_straight,
+                num_moves.saturating_sub(1)
+            );
+            assert!(stats.longest_run < w.max(h) as u64);
+            assert_eq!(
+                stats.num_turns,
+                locality_stats_with_algorithm(Algorithm::Zhang, [w, h]).turns
+            );
+        }
+    }
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "futures")]
+use futures_core::Stream;
+use futures_executor::block_on_stream;
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+use zhang_hilbert::{ArbHilbertScan32, DividerKind, HilbertScan32, LevelState, ScanStream};
+
+#[test]
+fn stream_matches_scan() {
+    for &size in &[[0, 3], [1, 7], [4, 4], [13, 9], [40, 7], [100, 60]] {
+        let stream = ArbHilbertScan32::new(size).to_stream();
+        assert_eq!(stream.size_hint().0, (size[0] * size[1]) as usize);
+        assert!(block_on_stream(stream).eq(ArbHilbertScan32::new(size)));
+
+        let stream = HilbertScan32::new(size).to_stream().yield_every(7);
+        assert!(block_on_stream(stream).eq(HilbertScan32::new(size)));
+
+        let kind = DividerKind::Grid { tile_side: 8 };
+        let scan: ArbHilbertScan32 =
+            ArbHilbertScan32::with_divider_kind([LevelState::default(); 32], size, kind);
+        let expected: Vec<_> = scan.clone().collect();
+        let stream: ScanStream<ArbHilbertScan32> = scan.to_stream().yield_every(1);
+        assert_eq!(block_on_stream(stream).collect::<Vec<_>>(), expected);
+    }
+}
+
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn stream_yields_periodically() {
+    let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let mut stream = ArbHilbertScan32::new([5, 2]).to_stream().yield_every(3);
+    let mut polls = Vec::new();
+    loop {
+        match Pin::new(&mut stream).poll_next(&mut cx) {
+            Poll::Ready(Some(_)) => polls.push(true),
+            Poll::Ready(None) => break,
+            Poll::Pending => polls.push(false),
+        }
+    }
+
+    // 10 points with a `Pending` after every 3 points
+    let expected = [
+        true, true, true, false, true, true, true, false, true, true, true, false, true,
+    ];
+    assert_eq!(polls, expected);
+    assert_eq!(counter.0.load(Ordering::Relaxed), 3);
+
+    // Without `yield_every`, every poll is ready
+    let mut stream = HilbertScan32::new([5, 2]).to_stream();
+    for _ in 0..10 {
+        assert!(matches!(
+            Pin::new(&mut stream).poll_next(&mut cx),
+            Poll::Ready(Some(_))
+        ));
+    }
+    assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(None));
+}
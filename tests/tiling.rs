@@ -0,0 +1,390 @@
+use zhang_hilbert::{
+    tile_sequence, tile_sequence_with_algorithm, tiling_contract, tiling_contract_with_algorithm,
+    Algorithm, ArbHilbertScan32, ExitGuarantee, ScanBuilder, ScanError, TileOrder, TileOrientation,
+    TiledScan, Transform,
+};
+
+/// Concatenate the scans of `n` chained tiles.
+fn chain(algorithm: Algorithm, tile_size: [u32; 2], n: u64) -> Option<Vec<[u32; 2]>> {
+    let tiles = match tile_sequence_with_algorithm(algorithm, tile_size, n) {
+        Ok(tiles) => tiles,
+        Err(ScanError::NoSeamlessTiling) => return None,
+        Err(e) => panic!("{}", e),
+    };
+    let mut points = Vec::new();
+    for tile in tiles {
+        let scan = ScanBuilder::new(tile_size)
+            .algorithm(algorithm)
+            .tile_orientation(tile.orientation)
+            .unwrap();
+        assert_eq!(scan.transform(), tile.transform);
+        points.extend(scan.map(|p| tile.to_global(p)));
+    }
+    Some(points)
+}
+
+/// Check that `points` visits every cell exactly once, and that every two
+/// consecutive points are adjacent.
+fn validate_path(size: [u32; 2], points: &[[u32; 2]]) {
+    let [w, h] = size;
+    let mut visited = vec![false; (w * h) as usize];
+    for &[x, y] in points {
+        assert!(x < w && y < h, "{:?} is out of bounds", [x, y]);
+        let cell = &mut visited[(x + y * w) as usize];
+        assert!(!*cell, "{:?} was visited twice", [x, y]);
+        *cell = true;
+    }
+    assert_eq!(points.len(), visited.len());
+
+    for pair in points.windows(2) {
+        let [p, q] = [pair[0], pair[1]];
+        let dist = (p[0] as i64 - q[0] as i64).abs() + (p[1] as i64 - q[1] as i64).abs();
+        assert_eq!(dist, 1, "{:?} and {:?} are not adjacent", p, q);
+    }
+}
+
+#[test]
+fn chained_tiles_form_a_path() {
+    for &algorithm in &[Algorithm::Zhang, Algorithm::ZhangArb] {
+        for w in 1..=20 {
+            for h in 1..=20 {
+                for n in 1..=3 {
+                    if let Some(points) = chain(algorithm, [w, h], n) {
+                        validate_path([w * n as u32, h], &points);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn wide_and_square_tiles_can_be_chained() {
+    for &algorithm in &[Algorithm::Zhang, Algorithm::ZhangArb] {
+        for w in 1..=32 {
+            for h in 1..=w {
+                if w % 2 == 1 && h % 2 == 0 && h > 2 {
+                    // The scan leaves in the middle of the right side
+                    continue;
+                }
+                assert!(
+                    chain(algorithm, [w, h], 2).is_some(),
+                    "{:?} {:?}",
+                    algorithm,
+                    [w, h]
+                );
+            }
+        }
+    }
+}
+
+/// Check the tiling guarantee on the raw scans, independently of the tiling
+/// helpers: a tile abuts the next tile translated by its width, and the scan
+/// of the first tile leaves next to where the scan of the second tile enters,
+/// possibly after transforming the latter.
+#[test]
+fn abutting_tiles_connect() {
+    for w in 1..=24u32 {
+        for h in 1..=w {
+            if w % 2 == 1 && h % 2 == 0 && h > 2 {
+                // The scan leaves in the middle of the right side
+                continue;
+            }
+            let end = ArbHilbertScan32::new([w, h])
+                .fold(None, |_, p| Some(p))
+                .unwrap();
+
+            let connects = |transform: Transform| {
+                let mut scan = ArbHilbertScan32::new(transform.size([w, h])).transformed(transform);
+                let [x, y] = scan.next().unwrap();
+                is_adjacent([end[0] as u64, end[1] as u64], [(x + w) as u64, y as u64])
+            };
+
+            if w % 2 == 0 {
+                // Leaves at the bottom-right corner, so the tiles connect
+                // without any transformation
+                assert_eq!(end, [w - 1, 0], "{:?}", [w, h]);
+                assert!(connects(Transform::Identity), "{:?}", [w, h]);
+            } else {
+                assert!(
+                    Transform::ALL.iter().any(|&t| connects(t)),
+                    "{:?} ends at {:?}",
+                    [w, h],
+                    end
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn orientations_alternate() {
+    let orientations: Vec<_> = tile_sequence([3u32, 3], 4)
+        .unwrap()
+        .map(|tile| (tile.index, tile.origin, tile.orientation))
+        .collect();
+    assert_eq!(
+        orientations,
+        [
+            (0, [0, 0], TileOrientation::Normal),
+            (1, [3, 0], TileOrientation::Mirrored),
+            (2, [6, 0], TileOrientation::Normal),
+            (3, [9, 0], TileOrientation::Mirrored),
+        ]
+    );
+}
+
+#[test]
+fn empty_tiles_are_rejected() {
+    assert_eq!(
+        tile_sequence([0u32, 4], 2).unwrap_err(),
+        ScanError::NoSeamlessTiling
+    );
+    assert_eq!(
+        ScanBuilder::new([4u32, 0])
+            .tile_orientation(TileOrientation::Normal)
+            .unwrap_err(),
+        ScanError::NoSeamlessTiling
+    );
+}
+
+#[test]
+fn overflowing_rows_are_rejected() {
+    assert!(tile_sequence([16u8, 16], 16).is_ok());
+    assert_eq!(
+        tile_sequence([16u8, 16], 17).unwrap_err(),
+        ScanError::CoordinateOverflow
+    );
+}
+
+#[test]
+fn contracts_match_scans() {
+    for &algorithm in &[Algorithm::Zhang, Algorithm::ZhangArb] {
+        for w in 1..=128u32 {
+            for h in 1..=128u32 {
+                let contract = tiling_contract_with_algorithm(algorithm, [w, h]).unwrap();
+
+                // Don't use `last`, which is implemented by `end_point`
+                let mut scan = ScanBuilder::new([w, h]).algorithm(algorithm).build();
+                let entry = scan.next().unwrap();
+                let exit = scan.fold(entry, |_, p| p);
+                assert_eq!(
+                    [contract.entry, contract.exit],
+                    [entry, exit],
+                    "{:?} {:?}",
+                    algorithm,
+                    [w, h]
+                );
+
+                let expected_exit = match algorithm {
+                    _ if w == 1 || h == 1 => Some([w - 1, h - 1]),
+                    Algorithm::Zhang if w % 2 == 0 => Some([w - 1, 0]),
+                    Algorithm::ZhangArb if h > w && h % 2 == 0 => Some([0, h - 1]),
+                    Algorithm::ZhangArb if h <= w && w % 2 == 0 => Some([w - 1, 0]),
+                    _ => None,
+                };
+                assert_eq!(contract.is_guaranteed(), expected_exit.is_some());
+                if let Some(expected_exit) = expected_exit {
+                    assert_eq!(contract.exit, expected_exit);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn contracts_of_empty_rects() {
+    assert_eq!(tiling_contract([0u32, 5]), None);
+    assert_eq!(
+        tiling_contract_with_algorithm(Algorithm::Zhang, [5u32, 0]),
+        None
+    );
+    assert_eq!(
+        tiling_contract([4u32, 4]).unwrap().guarantee,
+        ExitGuarantee::Guaranteed
+    );
+}
+
+/// Check that `points` covers the `columns`×`rows` tiles of the size
+/// `tile_size` at the origin, visiting every cell exactly once.
+fn validate_tiles(tile_size: [u32; 2], [columns, rows]: [u64; 2], points: &[[u64; 2]]) {
+    let [w, h] = [tile_size[0] as u64 * columns, tile_size[1] as u64 * rows];
+    let mut visited = vec![false; (w * h) as usize];
+    for &[x, y] in points {
+        assert!(x < w && y < h, "{:?} is out of bounds", [x, y]);
+        let cell = &mut visited[(x + y * w) as usize];
+        assert!(!*cell, "{:?} was visited twice", [x, y]);
+        *cell = true;
+    }
+    assert_eq!(points.len(), visited.len());
+}
+
+fn is_adjacent(p: [u64; 2], q: [u64; 2]) -> bool {
+    p[0].max(q[0]) - p[0].min(q[0]) + p[1].max(q[1]) - p[1].min(q[1]) == 1
+}
+
+#[test]
+fn tiled_scan_covers_tiles() {
+    for &tile_size in &[[1, 1], [4, 4], [5, 3], [3, 7], [16, 9]] {
+        let area = (tile_size[0] * tile_size[1]) as usize;
+
+        // A single row
+        let points: Vec<_> = TiledScan::new(tile_size).take(area * 5).collect();
+        validate_tiles(tile_size, [5, 1], &points);
+
+        for &order in &[TileOrder::RowMajor, TileOrder::Boustrophedon] {
+            let points: Vec<_> = TiledScan::new(tile_size)
+                .columns(3)
+                .order(order)
+                .take(area * 12)
+                .collect();
+            validate_tiles(tile_size, [3, 4], &points);
+
+            // Every tile is a plain scan
+            for (i, chunk) in points.chunks(area).enumerate() {
+                let [tx, ty] = [
+                    chunk[0][0] / tile_size[0] as u64,
+                    chunk[0][1] / tile_size[1] as u64,
+                ];
+                let expected_tx = match order {
+                    TileOrder::Boustrophedon if ty % 2 == 1 => 2 - i as u64 % 3,
+                    _ => i as u64 % 3,
+                };
+                assert_eq!([tx, ty], [expected_tx, i as u64 / 3]);
+                let origin = [tx * tile_size[0] as u64, ty * tile_size[1] as u64];
+                assert!(chunk.iter().copied().eq(ArbHilbertScan32::new(tile_size)
+                    .map(|[x, y]| [origin[0] + x as u64, origin[1] + y as u64])));
+            }
+        }
+    }
+}
+
+#[test]
+fn mirrored_tiled_scan_is_continuous_in_rows() {
+    for &tile_size in &[[1, 1], [4, 4], [5, 3], [3, 3], [16, 9], [6, 2]] {
+        let area = (tile_size[0] * tile_size[1]) as usize;
+
+        let points: Vec<_> = TiledScan::new(tile_size)
+            .mirrored()
+            .take(area * 5)
+            .collect();
+        validate_tiles(tile_size, [5, 1], &points);
+        assert!(points.windows(2).all(|w| is_adjacent(w[0], w[1])));
+
+        for &order in &[TileOrder::RowMajor, TileOrder::Boustrophedon] {
+            let points: Vec<_> = TiledScan::new(tile_size)
+                .columns(4)
+                .order(order)
+                .mirrored()
+                .take(area * 12)
+                .collect();
+            validate_tiles(tile_size, [4, 3], &points);
+            for row in points.chunks(area * 4) {
+                assert!(row.windows(2).all(|w| is_adjacent(w[0], w[1])));
+            }
+        }
+    }
+}
+
+#[test]
+fn tiled_scan_skips_to_tile() {
+    for &order in &[TileOrder::RowMajor, TileOrder::Boustrophedon] {
+        let full = TiledScan::new([5, 3]).columns(4).order(order).mirrored();
+        let points: Vec<_> = full.clone().take(15 * 12).collect();
+
+        let mut scan = full;
+        for i in (0..12).rev() {
+            let tile = [points[i * 15][0] / 5, points[i * 15][1] / 3];
+            scan.skip_to_tile(tile);
+            assert_eq!(scan.tile(), tile);
+            assert!(scan
+                .by_ref()
+                .take(15 * (12 - i))
+                .eq(points[i * 15..].iter().copied()));
+        }
+    }
+}
+
+#[test]
+fn tiled_scan_rejects_bad_tiles() {
+    assert_eq!(
+        TiledScan::try_new([0, 3]).unwrap_err(),
+        ScanError::UnsupportedSize
+    );
+    assert_eq!(
+        TiledScan::new([3, 8]).try_mirrored().unwrap_err(),
+        ScanError::NoSeamlessTiling
+    );
+}
+
+#[test]
+#[should_panic]
+fn tiled_scan_skip_to_unvisited_tile() {
+    TiledScan::new([4, 4]).skip_to_tile([0, 1]);
+}
+
+#[test]
+fn brick_tiled_scan_covers_canvas() {
+    for &tile_size in &[[4, 4], [5, 3], [7, 2], [2, 6]] {
+        for offset in 0..tile_size[0] {
+            for &order in &[TileOrder::RowMajor, TileOrder::Boustrophedon] {
+                let scan = TiledScan::new(tile_size)
+                    .columns(3)
+                    .order(order)
+                    .brick_offset(offset);
+                let area = (tile_size[0] * tile_size[1]) as usize;
+                let points: Vec<_> = scan.take(area * 3 * 4).collect();
+                validate_tiles(tile_size, [3, 4], &points);
+
+                // Every tile is a scan of its own size
+                let mut rest = &points[..];
+                let mut scan = TiledScan::new(tile_size)
+                    .columns(3)
+                    .order(order)
+                    .brick_offset(offset);
+                while !rest.is_empty() {
+                    let [x0, y0] = scan.next().unwrap();
+                    let [tx, ty] = scan.tile();
+                    let widths: &[u32] = if offset != 0 && ty % 2 == 1 {
+                        &[offset, tile_size[0], tile_size[0], tile_size[0] - offset]
+                    } else {
+                        &[tile_size[0]; 3]
+                    };
+                    let size = [widths[tx as usize], tile_size[1]];
+                    let len = (size[0] * size[1]) as usize;
+                    assert!(rest[..len]
+                        .iter()
+                        .copied()
+                        .eq(ArbHilbertScan32::new(size)
+                            .map(|[x, y]| [x0 + x as u64, y0 + y as u64])));
+                    rest = &rest[len..];
+                    scan.by_ref().take(len - 1).for_each(drop);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn mirrored_brick_tiled_scan_covers_canvas() {
+    let tile_size = [5, 3];
+    let points: Vec<_> = TiledScan::new(tile_size)
+        .columns(4)
+        .order(TileOrder::Boustrophedon)
+        .brick_offset(2)
+        .mirrored()
+        .take(15 * 4 * 3)
+        .collect();
+    validate_tiles(tile_size, [4, 3], &points);
+
+    // The even rows have no partial tiles and are continuous
+    for row in [&points[..60], &points[120..]] {
+        assert!(row.windows(2).all(|w| is_adjacent(w[0], w[1])));
+    }
+}
+
+#[test]
+#[should_panic]
+fn brick_offset_too_large() {
+    TiledScan::new([4, 4]).columns(2).brick_offset(4);
+}
@@ -0,0 +1,13 @@
+#![cfg(feature = "nightly")]
+use zhang_hilbert::{ArbHilbertScan32, HilbertScan32};
+
+#[test]
+fn collect_allocates_once() {
+    let v: Vec<_> = HilbertScan32::new([1000, 999]).collect();
+    assert_eq!(v.len(), 999_000);
+    assert_eq!(v.capacity(), v.len());
+
+    let v: Vec<_> = ArbHilbertScan32::new([3000, 401]).collect();
+    assert_eq!(v.len(), 1_203_000);
+    assert_eq!(v.capacity(), v.len());
+}
@@ -0,0 +1,81 @@
+//! These tests are meant to be run by Miri as well to check that the
+//! uninitialized storage is never read:
+//!
+//! ```text
+//! cargo +nightly miri test --test uninit
+//! ```
+use std::mem::MaybeUninit;
+
+use zhang_hilbert::{
+    num_levels_for_size, ArbHilbertScan32, ArbHilbertScanCore, HilbertScan32, HilbertScanCore,
+    LevelState, ScanError,
+};
+
+const SIZES: &[[u32; 2]] = &[
+    [0, 0],
+    [0, 5],
+    [1, 1],
+    [1, 9],
+    [9, 1],
+    [2, 2],
+    [4, 3],
+    [6, 7],
+    [8, 8],
+    [11, 14],
+    [16, 5],
+    [27, 9],
+];
+
+#[test]
+fn uninit_storage() {
+    for &size in SIZES {
+        let expected: Vec<_> = HilbertScan32::new(size).collect();
+
+        let mut storage = [MaybeUninit::<LevelState<u32>>::uninit(); 32];
+        let mut scan = HilbertScanCore::with_uninit_storage(&mut storage, size);
+        let actual: Vec<_> = scan.by_ref().collect();
+        assert_eq!(actual, expected, "{:?}", size);
+
+        // Exercise the other operations touching the level states
+        scan.reset();
+        assert!(!format!("{:?}", scan).is_empty());
+        if !expected.is_empty() {
+            let snapshot = scan.save_state();
+            let restored = HilbertScan32::restore(&snapshot, Default::default());
+            assert!(restored.eq(expected.iter().cloned()), "{:?}", size);
+
+            let mut storage = [MaybeUninit::<LevelState<u32>>::uninit(); 32];
+            let mut scan = HilbertScanCore::with_uninit_storage(&mut storage, size);
+            scan.nth(expected.len() / 2);
+            let last = expected.len() - 1;
+            assert_eq!(scan.rank(expected[last]), last as u64, "{:?}", size);
+        }
+
+        let expected: Vec<_> = ArbHilbertScan32::new(size).collect();
+        let mut storage = [MaybeUninit::<LevelState<u32>>::uninit(); 32];
+        let scan = ArbHilbertScanCore::with_uninit_storage(&mut storage, size);
+        assert!(scan.eq(expected.iter().cloned()), "{:?}", size);
+    }
+}
+
+#[test]
+fn uninit_storage_exact_len() {
+    for &size in SIZES {
+        let mut storage = vec![MaybeUninit::<LevelState<u32>>::uninit(); num_levels_for_size(size)];
+        let scan = HilbertScanCore::with_uninit_storage(&mut storage, size);
+        assert!(scan.eq(HilbertScan32::new(size)), "{:?}", size);
+    }
+}
+
+#[test]
+fn uninit_storage_too_small() {
+    let mut storage = [MaybeUninit::<LevelState<u32>>::uninit(); 2];
+    let result = HilbertScanCore::try_with_uninit_storage(&mut storage, [32, 32]);
+    assert_eq!(
+        result.unwrap_err(),
+        ScanError::StorageTooSmall {
+            required: 6,
+            provided: 2
+        }
+    );
+}
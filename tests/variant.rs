@@ -0,0 +1,309 @@
+use zhang_hilbert::{
+    Algorithm, ArbHilbertScan32, ArbHilbertScanCore, ArbScanSnapshot, CurveVariant, DivisionRule,
+    HilbertScan32, HilbertScanCore, LevelState, PrimaryAxis, ScanBuilder, ScanSnapshot,
+};
+
+/// Render a scan in the format used by the `hilbertgen` example.
+fn render(points: &[[u32; 2]], [w, h]: [u32; 2]) -> Vec<String> {
+    let mut grid = vec![vec![' '; w as usize * 2 - 1]; h as usize];
+    let mut last_dir: Option<[i32; 2]> = None;
+    for pair in points.windows(2) {
+        let [[x0, y0], [x1, y1]] = [pair[0], pair[1]];
+        let [x0, y0] = [x0 as usize * 2, (h - 1 - y0) as usize];
+        let [x1, y1] = [x1 as usize * 2, (h - 1 - y1) as usize];
+        let dir = [
+            (x1 as i32 - x0 as i32).signum(),
+            (y1 as i32 - y0 as i32).signum(),
+        ];
+        grid[y0][x0] = match (last_dir, dir) {
+            (None, [_, 0]) | (Some([_, 0]), [_, 0]) => '-',
+            (Some([_, 1]), [_, 0]) => '\'',
+            (Some([_, -1]), [_, 0]) => ',',
+            (None, _) | (Some([0, _]), _) => '|',
+            (_, [_, 1]) => ',',
+            (_, _) => '\'',
+        };
+        if dir[1] == 0 {
+            grid[y0][(x0 + x1) / 2] = '-';
+        }
+        last_dir = Some(dir);
+    }
+    let [x, y] = points[points.len() - 1];
+    grid[(h - 1 - y) as usize][x as usize * 2] = match last_dir {
+        Some([_, 0]) => '-',
+        _ => '|',
+    };
+    grid.into_iter()
+        .map(|row| row.into_iter().collect::<String>().trim_end().to_owned())
+        .collect()
+}
+
+fn scan(size: [u32; 2], variant: CurveVariant) -> Vec<[u32; 2]> {
+    HilbertScanCore::with_variant([LevelState::default(); 32], size, variant).collect()
+}
+
+fn validate(points: &[[u32; 2]], [w, h]: [u32; 2]) {
+    let mut visited = vec![false; (w * h) as usize];
+    for &[x, y] in points {
+        assert!(x < w && y < h);
+        assert!(!std::mem::replace(&mut visited[(x + y * w) as usize], true));
+    }
+    assert!(visited.iter().all(|&x| x));
+    for pair in points.windows(2) {
+        let [[x0, y0], [x1, y1]] = [pair[0], pair[1]];
+        assert_eq!(
+            (x0 as i32 - x1 as i32).abs() + (y0 as i32 - y1 as i32).abs(),
+            1
+        );
+    }
+}
+
+#[test]
+fn variants_match_docs() {
+    let cases: &[([u32; 2], &[&str], &[&str])] = &[
+        (
+            [6, 7],
+            &[
+                ",---, ,---,",
+                "'-, '-' ,-'",
+                ",-' ,-, '-,",
+                "'-, | '---'",
+                ",-' '-, ,--",
+                "'-, ,-' '-,",
+                "--' '-----'",
+            ],
+            &[
+                ",---, ,---,",
+                "'-, '-' ,-'",
+                ",-' ,-, '-,",
+                "'-, | '---'",
+                ",-' '-----,",
+                "'-, ,-----'",
+                "--' '------",
+            ],
+        ),
+        (
+            [4, 3],
+            &[",------", "'-----,", "------'"],
+            &[",-----,", "'-, ,-'", "--' '--"],
+        ),
+    ];
+
+    for &(size, original, modified) in cases {
+        let original_points = scan(size, CurveVariant::Original);
+        let modified_points = scan(size, CurveVariant::Modified);
+        validate(&original_points, size);
+        validate(&modified_points, size);
+        assert_eq!(render(&original_points, size), original);
+        assert_eq!(render(&modified_points, size), modified);
+
+        // `Modified` is the default
+        assert!(HilbertScan32::new(size).eq(modified_points.iter().cloned()));
+    }
+}
+
+#[test]
+fn variants_are_valid() {
+    for w in 1..48 {
+        for h in 1..48 {
+            for &variant in &[CurveVariant::Modified, CurveVariant::Original] {
+                let points = scan([w, h], variant);
+                validate(&points, [w, h]);
+
+                let end_point = HilbertScan32::end_point_with_variant([w, h], variant);
+                assert_eq!(end_point, points.last().cloned());
+                let scan =
+                    HilbertScanCore::with_variant([LevelState::default(); 32], [w, h], variant);
+                assert_eq!(scan.last(), end_point);
+            }
+        }
+    }
+}
+
+#[test]
+fn variant_survives_snapshot() {
+    let mut scan = HilbertScanCore::with_variant(
+        [LevelState::default(); 32],
+        [6u32, 7],
+        CurveVariant::Original,
+    );
+    scan.nth(20);
+    let snapshot = scan.save_state();
+    let decoded = zhang_hilbert::ScanSnapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+    let resumed = HilbertScan32::restore(&decoded, Default::default());
+    assert_eq!(resumed.variant(), CurveVariant::Original);
+    assert!(resumed.eq(scan));
+}
+
+#[test]
+fn division_rules_are_valid() {
+    for w in 1..48 {
+        for h in 1..48 {
+            let new = || {
+                HilbertScanCore::with_division_rule(
+                    [LevelState::default(); 32],
+                    [w, h],
+                    DivisionRule::PaperExact,
+                )
+            };
+            let points: Vec<_> = new().collect();
+            validate(&points, [w, h]);
+            assert_eq!(new().last(), points.last().cloned());
+
+            // Random access relies on the same rule
+            let mut scan = new();
+            for (i, &p) in points.iter().enumerate().step_by(7) {
+                assert_eq!(scan.rank(p), i as u64);
+                assert_eq!(new().nth(i), Some(p));
+            }
+        }
+    }
+
+    // The rules differ for the sides of the form `3⋅2ⁿ`
+    for &size in &[[24, 24], [48, 48], [96, 96], [24, 17], [5, 48]] {
+        let fast: Vec<_> = HilbertScan32::new(size).collect();
+        let paper: Vec<_> = HilbertScanCore::with_division_rule(
+            [LevelState::default(); 32],
+            size,
+            DivisionRule::PaperExact,
+        )
+        .collect();
+        assert_ne!(fast, paper, "{:?}", size);
+    }
+
+    // ... and agree for the sides that never produce such a part
+    for &size in &[[16, 16], [64, 64], [17, 33], [5, 9]] {
+        let fast: Vec<_> = HilbertScan32::new(size).collect();
+        let paper: Vec<_> = HilbertScanCore::with_division_rule(
+            [LevelState::default(); 32],
+            size,
+            DivisionRule::PaperExact,
+        )
+        .collect();
+        assert_eq!(fast, paper, "{:?}", size);
+    }
+}
+
+fn transpose(points: &[[u32; 2]]) -> Vec<[u32; 2]> {
+    points.iter().map(|&[x, y]| [y, x]).collect()
+}
+
+#[test]
+fn primary_axes_are_transposes() {
+    for w in 1..48 {
+        for h in 1..48 {
+            let new = |size, axis| {
+                HilbertScanCore::with_primary_axis([LevelState::default(); 32], size, axis)
+            };
+            let points: Vec<_> = new([w, h], PrimaryAxis::Y).collect();
+            validate(&points, [w, h]);
+            let x_points: Vec<_> = new([h, w], PrimaryAxis::X).collect();
+            assert_eq!(points, transpose(&x_points), "{:?}", [w, h]);
+            assert_eq!(new([w, h], PrimaryAxis::Y).last(), points.last().cloned());
+
+            // Random access relies on the same rule
+            let mut scan = new([w, h], PrimaryAxis::Y);
+            for (i, &p) in points.iter().enumerate().step_by(7) {
+                assert_eq!(scan.rank(p), i as u64);
+                assert_eq!(new([w, h], PrimaryAxis::Y).nth(i), Some(p));
+            }
+
+            let new_arb = || {
+                ArbHilbertScan32::with_primary_axis(
+                    [LevelState::default(); 32],
+                    [w, h],
+                    PrimaryAxis::Y,
+                )
+            };
+            let points: Vec<_> = new_arb().collect();
+            validate(&points, [w, h]);
+            assert_eq!(new_arb().last(), points.last().cloned());
+            if w == h {
+                let x_points: Vec<_> = ArbHilbertScan32::new([w, w]).collect();
+                assert_eq!(points, transpose(&x_points), "{:?}", [w, h]);
+            } else {
+                // Non-square rectangles are always divided along the longer
+                // side
+                assert!(points.into_iter().eq(ArbHilbertScan32::new([w, h])));
+            }
+        }
+    }
+}
+
+#[test]
+fn primary_axis_survives_snapshot() {
+    let mut scan =
+        HilbertScanCore::with_primary_axis([LevelState::default(); 32], [6u32, 7], PrimaryAxis::Y);
+    scan.nth(20);
+    let decoded = ScanSnapshot::from_bytes(&scan.save_state().to_bytes()).unwrap();
+    let resumed = HilbertScan32::restore(&decoded, Default::default());
+    assert_eq!(resumed.primary_axis(), PrimaryAxis::Y);
+    assert!(resumed.eq(scan));
+
+    let mut scan = ArbHilbertScanCore::with_primary_axis(
+        [LevelState::default(); 32],
+        [9u32, 9],
+        PrimaryAxis::Y,
+    );
+    scan.nth(20);
+    let decoded = ArbScanSnapshot::from_bytes(&scan.save_state().to_bytes()).unwrap();
+    let mut resumed = ArbHilbertScan32::restore(&decoded, Default::default());
+    assert_eq!(resumed.primary_axis(), PrimaryAxis::Y);
+    assert!(resumed.clone().eq(scan));
+
+    resumed.reset();
+    assert_eq!(resumed.major_axis(), 1);
+}
+
+/// Start configuring an `Algorithm::Zhang` scan with an array-based working
+/// area.
+fn zhang(size: [u32; 2]) -> ScanBuilder<u32, [LevelState<u32>; 32]> {
+    ScanBuilder::new(size)
+        .algorithm(Algorithm::Zhang)
+        .storage([LevelState::default(); 32])
+}
+
+#[test]
+fn options_combine() {
+    for &size in &[[4u32, 3], [6, 7], [24, 17], [11, 42]] {
+        let [w, h] = size;
+        let transposed: Vec<_> = zhang([h, w])
+            .variant(CurveVariant::Original)
+            .division_rule(DivisionRule::PaperExact)
+            .extra_subdivision(false)
+            .build()
+            .collect();
+        let mut scan = zhang(size)
+            .variant(CurveVariant::Original)
+            .division_rule(DivisionRule::PaperExact)
+            .extra_subdivision(false)
+            .primary_axis(PrimaryAxis::Y)
+            .origin([10, 20])
+            .build()
+            .into_plain()
+            .unwrap();
+        assert_eq!(scan.variant(), CurveVariant::Original);
+        assert_eq!(scan.division_rule(), DivisionRule::PaperExact);
+        assert!(!scan.extra_subdivision());
+        assert_eq!(scan.primary_axis(), PrimaryAxis::Y);
+        assert_eq!(scan.origin(), [10, 20]);
+
+        let expected: Vec<_> = transpose(&transposed)
+            .into_iter()
+            .map(|[x, y]| [x + 10, y + 20])
+            .collect();
+        assert_eq!(scan.exit_point(), expected.last().cloned(), "{:?}", size);
+        assert!(scan.clone().eq(expected.iter().cloned()), "{:?}", size);
+        assert_eq!(scan.rank(expected[5]), 5);
+
+        // `Algorithm::ZhangArb` scans the parts with the same rules
+        let arb = ScanBuilder::new(size)
+            .storage([LevelState::default(); 32])
+            .division_rule(DivisionRule::PaperExact)
+            .extra_subdivision(false)
+            .build();
+        let points: Vec<_> = arb.clone().collect();
+        validate(&points, size);
+        assert_eq!(arb.last(), points.last().cloned());
+    }
+}